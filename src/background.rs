@@ -0,0 +1,80 @@
+use crate::{
+    component::{Background, Hero, Render},
+    effects::ScreenFlash,
+    system::FRAME_DURATION,
+};
+use specs::{Join, ReadStorage, System, Write, WriteStorage};
+use std::time::Duration;
+
+#[derive(Default, Debug, Clone)]
+pub struct BackgroundStages {
+    pub stages: Vec<(i32, String)>,
+    pub current: usize,
+    pub fade: ScreenFlash,
+}
+
+impl BackgroundStages {
+    pub fn new(stages: &[(i32, String)], fade: ScreenFlash) -> BackgroundStages {
+        let mut stages = stages.to_vec();
+        stages.sort_by_key(|(threshold, _)| *threshold);
+        BackgroundStages {
+            stages,
+            fade,
+            ..BackgroundStages::default()
+        }
+    }
+}
+
+pub struct BackgroundStageSystem;
+
+impl<'a> System<'a> for BackgroundStageSystem {
+    type SystemData = (
+        ReadStorage<'a, Hero>,
+        ReadStorage<'a, Background>,
+        WriteStorage<'a, Render>,
+        Write<'a, BackgroundStages>,
+    );
+
+    fn run(&mut self, (hero, background, mut render, mut stages): Self::SystemData) {
+        if stages.fade.remaining > FRAME_DURATION {
+            stages.fade.remaining -= FRAME_DURATION;
+        } else {
+            stages.fade.remaining = Duration::from_millis(0);
+        }
+        let score: i32 = (&hero).join().map(|hero| hero.score).sum();
+        let stage = get_background_stage(&stages.stages, score);
+        if stage <= stages.current {
+            return;
+        }
+        stages.current = stage;
+        let sprite = stages.stages[stage - 1].1.clone();
+        for (render, _) in (&mut render, &background).join() {
+            render.sprite = sprite.clone();
+        }
+        stages.fade.start();
+    }
+}
+
+pub fn get_background_stage(stages: &[(i32, String)], score: i32) -> usize {
+    stages
+        .iter()
+        .filter(|(threshold, _)| score >= *threshold)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_are_sorted_and_counted_by_threshold() {
+        let stages = BackgroundStages::new(
+            &[(200, "night".to_string()), (100, "dusk".to_string())],
+            ScreenFlash::default(),
+        );
+        assert_eq!(stages.stages[0].1, "dusk");
+        assert_eq!(get_background_stage(&stages.stages, 99), 0);
+        assert_eq!(get_background_stage(&stages.stages, 150), 1);
+        assert_eq!(get_background_stage(&stages.stages, 200), 2);
+    }
+}
@@ -0,0 +1,757 @@
+use crate::{
+    component::{
+        Acceleration, Boss, ChargeAttack, DashEntrance, DespawnAnim, Dive, DroneHost, Enemy,
+        Faction, FreezeFor, Hero, Mimic, MimicShot, MirrorHeroX, Position, Render, SelfShield,
+        Velocity,
+    },
+    enemy::FireballConfig,
+    resources::{HeroShots, PlayField},
+    system::FRAME_DURATION,
+};
+use quicksilver::geom::{Rectangle, Shape, Vector};
+use specs::{Entities, Entity, Join, LazyUpdate, Read, ReadStorage, System, WriteStorage};
+use std::time::Duration;
+
+pub struct DashEntranceSystem;
+
+impl<'a> System<'a> for DashEntranceSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, DashEntrance>,
+        WriteStorage<'a, Velocity>,
+        WriteStorage<'a, Acceleration>,
+        Read<'a, LazyUpdate>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut dash_entrance, mut vel, mut acceleration, lazy): Self::SystemData,
+    ) {
+        let elapsed = FRAME_DURATION.as_secs_f32();
+        for (e, dash_entrance, vel) in (&entities, &mut dash_entrance, &mut vel).join() {
+            if !dash_entrance.dashing {
+                if dash_entrance.telegraph > FRAME_DURATION {
+                    dash_entrance.telegraph -= FRAME_DURATION;
+                    vel.velocity = Vector::ZERO;
+                    continue;
+                }
+                dash_entrance.telegraph = Duration::from_millis(0);
+                dash_entrance.dashing = true;
+                if dash_entrance.deceleration > 0.0 {
+                    vel.velocity =
+                        dash_entrance.pattern_velocity.normalize() * dash_entrance.dash_speed;
+                    dash_entrance.pattern_acceleration = acceleration
+                        .insert(
+                            e,
+                            Acceleration {
+                                acceleration: -dash_entrance.deceleration,
+                                max_speed: None,
+                            },
+                        )
+                        .unwrap();
+                    continue;
+                }
+            }
+            let next_speed = vel.velocity.len() - dash_entrance.deceleration * elapsed;
+            if dash_entrance.deceleration <= 0.0
+                || next_speed <= dash_entrance.pattern_velocity.len()
+            {
+                vel.velocity = dash_entrance.pattern_velocity;
+                match dash_entrance.pattern_acceleration {
+                    Some(pattern_acceleration) => {
+                        acceleration.insert(e, pattern_acceleration).unwrap();
+                    }
+                    None => {
+                        acceleration.remove(e);
+                    }
+                }
+                lazy.remove::<DashEntrance>(e);
+            }
+        }
+    }
+}
+
+pub struct DroneSystem;
+
+impl<'a> System<'a> for DroneSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, DroneHost>,
+        ReadStorage<'a, DespawnAnim>,
+        Read<'a, LazyUpdate>,
+    );
+
+    fn run(&mut self, (entities, mut drone_host, despawn_anim, lazy): Self::SystemData) {
+        for (e, drone_host, _) in (&entities, &mut drone_host, !&despawn_anim).join() {
+            if drone_host.timer > FRAME_DURATION {
+                drone_host.timer -= FRAME_DURATION;
+                continue;
+            }
+            drone_host.timer = drone_host.interval;
+            if drone_host.drones_alive >= drone_host.max_drones {
+                continue;
+            }
+            let angle = crate::enemy::get_drone_angle(drone_host.spawned, drone_host.max_drones);
+            let config = drone_host.drone.clone();
+            drone_host.spawned += 1;
+            lazy.exec_mut(move |world| {
+                crate::enemy::create_drone(world, e, angle, config);
+            });
+        }
+    }
+}
+
+pub struct SeparationSystem {
+    pub strength: f32,
+}
+
+impl<'a> System<'a> for SeparationSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Render>,
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, Boss>,
+    );
+
+    fn run(&mut self, (entities, mut pos, render, enemy, boss): Self::SystemData) {
+        let time_step = 0.0167;
+        let enemies: Vec<(Entity, Rectangle)> = (&entities, &pos, &render, &enemy, !&boss)
+            .join()
+            .filter_map(|(e, pos, render, _, _)| {
+                render
+                    .bounding_box
+                    .map(|bounding_box| (e, bounding_box.with_center(pos.position)))
+            })
+            .collect();
+
+        for &(e, area) in enemies.iter() {
+            let mut push = Vector::ZERO;
+            for &(other, other_area) in enemies.iter() {
+                if other == e || !area.overlaps(&other_area) {
+                    continue;
+                }
+                push += get_separation_direction(e, area.center(), other, other_area.center());
+            }
+            if let Some(pos) = pos.get_mut(e) {
+                pos.position += push * self.strength * time_step;
+            }
+        }
+    }
+}
+
+pub struct MirrorHeroXSystem;
+
+impl<'a> System<'a> for MirrorHeroXSystem {
+    type SystemData = (
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Hero>,
+        ReadStorage<'a, MirrorHeroX>,
+        Read<'a, PlayField>,
+    );
+
+    fn run(&mut self, (mut pos, hero, mirror, play_field): Self::SystemData) {
+        let time_step = 0.0167;
+        let hero_x = match (&pos, &hero).join().map(|(pos, _)| pos.position.x).next() {
+            Some(hero_x) => hero_x,
+            None => return,
+        };
+
+        for (pos, mirror) in (&mut pos, &mirror).join() {
+            let target_x =
+                crate::enemy::get_mirror_target_x(hero_x, mirror.inverted, play_field.size.x);
+            let step = mirror.speed * time_step;
+            let distance = target_x - pos.position.x;
+            pos.position.x += distance.max(-step).min(step);
+        }
+    }
+}
+
+pub struct MimicSystem;
+
+impl<'a> System<'a> for MimicSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Hero>,
+        WriteStorage<'a, Mimic>,
+        Read<'a, HeroShots>,
+        Read<'a, LazyUpdate>,
+        ReadStorage<'a, DespawnAnim>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, pos, hero, mut mimic, hero_shots, lazy, despawn_anim): Self::SystemData,
+    ) {
+        let hero_position = (&pos, &hero).join().map(|(pos, _)| pos.position).next();
+
+        for (e, pos, mimic, _) in (&entities, &pos, &mut mimic, !&despawn_anim).join() {
+            for shot in hero_shots.shots.iter() {
+                mimic.pending.push(MimicShot {
+                    remaining: mimic.delay,
+                    sprite: shot.sprite.clone(),
+                    speed: shot.speed,
+                });
+            }
+            for shot in mimic.pending.iter_mut() {
+                shot.remaining = if shot.remaining > FRAME_DURATION {
+                    shot.remaining - FRAME_DURATION
+                } else {
+                    Duration::from_millis(0)
+                };
+            }
+            let (ready, pending): (Vec<MimicShot>, Vec<MimicShot>) = mimic
+                .pending
+                .drain(..)
+                .partition(|shot| shot.remaining == Duration::from_millis(0));
+            mimic.pending = pending;
+
+            let aim = crate::enemy::get_mimic_aim(pos.position, hero_position);
+            for shot in ready {
+                let fireball_config = FireballConfig {
+                    sprite: shot.sprite,
+                    position: pos.position,
+                    velocity: aim * shot.speed,
+                    damage_over_time: None,
+                    contact_damage: None,
+                };
+                crate::enemy::create_fireball(
+                    lazy.create_entity(&entities),
+                    Some(e.id()),
+                    Faction::Enemy,
+                    fireball_config,
+                );
+            }
+        }
+    }
+}
+
+pub struct DiveSystem;
+
+impl<'a> System<'a> for DiveSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Hero>,
+        WriteStorage<'a, Dive>,
+        WriteStorage<'a, Velocity>,
+        Read<'a, LazyUpdate>,
+        ReadStorage<'a, DespawnAnim>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, pos, hero, mut dive, mut vel, lazy, despawn_anim): Self::SystemData,
+    ) {
+        let heroes: Vec<Vector> = (&pos, &hero).join().map(|(pos, _)| pos.position).collect();
+
+        for (e, pos, dive, vel, _) in (&entities, &pos, &mut dive, &mut vel, !&despawn_anim).join()
+        {
+            if dive.target.is_some() {
+                continue;
+            }
+            if dive.hover > FRAME_DURATION {
+                dive.hover -= FRAME_DURATION;
+                continue;
+            }
+            dive.hover = Duration::from_millis(0);
+            let target = heroes.iter().min_by(|a, b| {
+                a.distance(pos.position)
+                    .partial_cmp(&b.distance(pos.position))
+                    .unwrap()
+            });
+            if let Some(&target) = target {
+                dive.target = Some(target);
+                vel.velocity = crate::enemy::get_dive_velocity(pos.position, target, dive.speed);
+                lazy.insert(
+                    e,
+                    Acceleration {
+                        acceleration: dive.acceleration,
+                        max_speed: dive.max_speed,
+                    },
+                );
+            }
+        }
+    }
+}
+
+pub struct ChargeAttackSystem;
+
+impl<'a> System<'a> for ChargeAttackSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Hero>,
+        WriteStorage<'a, ChargeAttack>,
+        WriteStorage<'a, Velocity>,
+        Read<'a, LazyUpdate>,
+        ReadStorage<'a, DespawnAnim>,
+        ReadStorage<'a, FreezeFor>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            pos,
+            hero,
+            mut charge_attack,
+            mut vel,
+            lazy,
+            despawn_anim,
+            freeze,
+        ): Self::SystemData,
+    ) {
+        let frame_ms = FRAME_DURATION.as_millis() as u64;
+        let hero_position = (&pos, &hero).join().map(|(pos, _)| pos.position).next();
+
+        for (e, pos, charge_attack, vel, _, _) in (
+            &entities,
+            &pos,
+            &mut charge_attack,
+            &mut vel,
+            !&despawn_anim,
+            !&freeze,
+        )
+            .join()
+        {
+            let was_charging = charge_attack.charging;
+            let fire = crate::enemy::advance_charge_attack(charge_attack, frame_ms);
+            if charge_attack.charging && !was_charging {
+                charge_attack.saved_velocity = Some(vel.velocity);
+                vel.velocity = Vector::ZERO;
+            }
+            if !fire {
+                continue;
+            }
+            if let Some(saved_velocity) = charge_attack.saved_velocity.take() {
+                vel.velocity = saved_velocity;
+            }
+
+            let aim = match hero_position {
+                Some(hero_position) => hero_position - pos.position,
+                None => Vector::new(-1.0, 0.0),
+            };
+            for direction in crate::enemy::get_emitter_directions(charge_attack.pattern, aim) {
+                let fireball_config = FireballConfig {
+                    sprite: charge_attack.projectile_sprite.clone(),
+                    position: pos.position,
+                    velocity: direction * charge_attack.projectile_speed,
+                    damage_over_time: None,
+                    contact_damage: None,
+                };
+                crate::enemy::create_fireball(
+                    lazy.create_entity(&entities),
+                    Some(e.id()),
+                    Faction::Enemy,
+                    fireball_config,
+                );
+            }
+        }
+    }
+}
+
+pub struct SelfShieldSystem;
+
+impl<'a> System<'a> for SelfShieldSystem {
+    type SystemData = WriteStorage<'a, SelfShield>;
+
+    fn run(&mut self, mut self_shield: Self::SystemData) {
+        for self_shield in (&mut self_shield).join() {
+            self_shield.timer += FRAME_DURATION;
+            let window = if self_shield.active {
+                self_shield.up
+            } else {
+                self_shield.down
+            };
+            if self_shield.timer >= window {
+                self_shield.timer -= window;
+                self_shield.active = !self_shield.active;
+            }
+        }
+    }
+}
+
+fn get_separation_direction(
+    e: Entity,
+    position: Vector,
+    other: Entity,
+    other_position: Vector,
+) -> Vector {
+    let away = position - other_position;
+    if away.len2() > 0.0 {
+        away.normalize()
+    } else if e.id() > other.id() {
+        Vector::new(1.0, 0.0)
+    } else {
+        Vector::new(-1.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Fireball, FollowParent, Health, PlayerId},
+        enemy::{
+            ChargerConfig, DashEntranceConfig, DiverConfig, DroneHostConfig, EmitterPattern,
+            MimicConfig,
+        },
+        hero::HeroConfig,
+        resources::HeroShot,
+        scene::{create_world, SceneConfig},
+        system::{CollisionSystem, FollowParentSystem, OutOfBoundsSystem, WalkSystem},
+        test_support::{create_test_enemy, fire_hero_fireball, run, set_bounding_box},
+        wave_clear::WaveClear,
+    };
+    use specs::{Builder, World};
+
+    #[test]
+    fn separation_pushes_only_overlapping_enemies_apart() {
+        let mut world = create_world(&SceneConfig::default());
+        let left = create_test_enemy(&mut world, Vector::new(400, 300), 1);
+        let right = create_test_enemy(&mut world, Vector::new(420, 300), 1);
+        let alone = create_test_enemy(&mut world, Vector::new(100, 100), 1);
+
+        run(&mut world, SeparationSystem { strength: 60.0 });
+
+        let pos = world.read_storage::<Position>();
+        let x = |e: Entity| pos.get(e).unwrap().position.x;
+        assert!(x(left) < 400.0);
+        assert!(x(right) > 420.0);
+        assert_eq!(pos.get(alone).unwrap().position, Vector::new(100, 100));
+    }
+
+    #[test]
+    fn mirror_enemy_follows_hero_x_or_its_mirror() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            position: Vector::new(200, 500),
+            ..HeroConfig::default()
+        };
+        crate::hero::create_hero(&mut world, config, PlayerId::One);
+        let mut create_mirror = |inverted| {
+            world
+                .create_entity()
+                .with(Position {
+                    position: Vector::new(300, 100),
+                })
+                .with(MirrorHeroX {
+                    inverted,
+                    speed: 60.0,
+                })
+                .build()
+        };
+        let follower = create_mirror(false);
+        let inverted = create_mirror(true);
+
+        run(&mut world, MirrorHeroXSystem);
+
+        let pos = world.read_storage::<Position>();
+        assert!(pos.get(follower).unwrap().position.x < 300.0);
+        assert!(pos.get(inverted).unwrap().position.x > 300.0);
+    }
+
+    #[test]
+    fn self_shield_blocks_hits_only_during_its_up_window() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let enemy = create_test_enemy(&mut world, Vector::new(400, 300), 3);
+        world
+            .write_storage::<SelfShield>()
+            .insert(
+                enemy,
+                SelfShield {
+                    up: Duration::from_millis(100),
+                    down: Duration::from_millis(200),
+                    timer: Duration::from_millis(0),
+                    active: true,
+                },
+            )
+            .unwrap();
+        let hit = |world: &mut World, frames: u32| {
+            for _ in 0..frames {
+                run(world, SelfShieldSystem);
+            }
+            fire_hero_fireball(world, hero, Vector::new(400, 300));
+            run(world, CollisionSystem);
+            world.read_storage::<Health>().get(enemy).unwrap().current
+        };
+
+        assert_eq!(hit(&mut world, 5), 3);
+        assert_eq!(hit(&mut world, 1), 2);
+        assert_eq!(hit(&mut world, 11), 1);
+        assert_eq!(hit(&mut world, 1), 1);
+    }
+
+    #[test]
+    fn mimic_returns_the_shot_after_its_delay_even_on_top_of_the_hero() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            position: Vector::new(400, 300),
+            ..HeroConfig::default()
+        };
+        crate::hero::create_hero(&mut world, config, PlayerId::One);
+        let mimic = crate::enemy::create_mimic(
+            &mut world,
+            MimicConfig {
+                position: Vector::new(400, 300),
+                velocity: Vector::ZERO,
+                delay_ms: 50,
+                ..MimicConfig::default()
+            },
+        );
+        world.write_resource::<HeroShots>().shots.push(HeroShot {
+            sprite: "tiro".to_string(),
+            speed: 600.0,
+        });
+        let fireballs = |world: &World| {
+            (
+                &world.read_storage::<Fireball>(),
+                &world.read_storage::<Velocity>(),
+            )
+                .join()
+                .map(|(fireball, vel)| (fireball.owner_id, fireball.faction, vel.velocity))
+                .collect::<Vec<_>>()
+        };
+
+        run(&mut world, MimicSystem);
+        world.write_resource::<HeroShots>().shots.clear();
+        run(&mut world, MimicSystem);
+        assert!(fireballs(&world).is_empty());
+        run(&mut world, MimicSystem);
+
+        assert_eq!(
+            fireballs(&world),
+            vec![(Some(mimic.id()), Faction::Enemy, Vector::new(-600, 0))]
+        );
+    }
+
+    #[test]
+    fn charger_fires_when_the_charge_completes_and_resets() {
+        let mut world = create_world(&SceneConfig::default());
+        let charger = crate::enemy::create_charger(
+            &mut world,
+            ChargerConfig {
+                position: Vector::new(600, 300),
+                velocity: Vector::new(-50, 0),
+                cooldown_ms: 32,
+                charge_ms: 48,
+                pattern: EmitterPattern::Ring(4),
+                ..ChargerConfig::default()
+            },
+        );
+        let fireballs = |world: &World| world.read_storage::<Fireball>().join().count();
+        let velocity = |world: &World| {
+            world
+                .read_storage::<Velocity>()
+                .get(charger)
+                .unwrap()
+                .velocity
+        };
+
+        let mut fired_at = None;
+        for frame in 1..=6 {
+            run(&mut world, ChargeAttackSystem);
+            if frame == 2 {
+                assert_eq!(velocity(&world), Vector::ZERO);
+            }
+            if fired_at.is_none() && fireballs(&world) > 0 {
+                fired_at = Some(frame);
+            }
+        }
+
+        assert_eq!(fired_at, Some(5));
+        assert_eq!(fireballs(&world), 4);
+        assert_eq!(velocity(&world), Vector::new(-50, 0));
+        let charge_attack = world.read_storage::<ChargeAttack>();
+        let charge_attack = charge_attack.get(charger).unwrap();
+        assert!(!charge_attack.charging);
+        assert_eq!(crate::enemy::get_charge_progress(charge_attack), None);
+    }
+
+    #[test]
+    fn drones_shield_their_host_past_the_edge_until_destroyed() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let host = crate::enemy::create_drone_host(&mut world, DroneHostConfig::default());
+        set_bounding_box(&mut world, host, Vector::new(40, 40));
+        let host_position = world.read_storage::<Position>().get(host).unwrap().position;
+        let health = |world: &World| world.read_storage::<Health>().get(host).unwrap().current;
+
+        run(&mut world, DroneSystem);
+        run(&mut world, FollowParentSystem);
+        run(&mut world, OutOfBoundsSystem);
+        let drones: Vec<Entity> = (&world.entities(), &world.read_storage::<FollowParent>())
+            .join()
+            .map(|(e, _)| e)
+            .collect();
+        assert_eq!(drones.len(), 1);
+        assert!(world.is_alive(drones[0]));
+        assert!(!world.read_resource::<WaveClear>().escaped);
+        let drones_alive = world
+            .read_storage::<DroneHost>()
+            .get(host)
+            .unwrap()
+            .drones_alive;
+        assert_eq!(drones_alive, 1);
+
+        fire_hero_fireball(&mut world, hero, host_position);
+        run(&mut world, CollisionSystem);
+        assert_eq!(health(&world), 3);
+
+        world.delete_entity(drones[0]).unwrap();
+        run(&mut world, FollowParentSystem);
+        fire_hero_fireball(&mut world, hero, host_position);
+        run(&mut world, CollisionSystem);
+        assert_eq!(health(&world), 2);
+    }
+
+    #[test]
+    fn diver_hovers_still_then_dives_at_the_hero_position_it_locked() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero_config = HeroConfig {
+            position: Vector::new(100, 450),
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, hero_config, PlayerId::One);
+        let config = DiverConfig {
+            position: Vector::new(700, 150),
+            hover_ms: 60,
+            ..DiverConfig::default()
+        };
+        let diver = crate::enemy::create_diver(&mut world, config);
+        let velocity = |world: &World| {
+            world
+                .read_storage::<Velocity>()
+                .get(diver)
+                .unwrap()
+                .velocity
+        };
+        let step = |world: &mut World| {
+            run(world, DiveSystem);
+            run(world, WalkSystem);
+        };
+
+        for _ in 0..3 {
+            step(&mut world);
+            assert_eq!(velocity(&world), Vector::ZERO);
+        }
+        assert_eq!(
+            world
+                .read_storage::<Position>()
+                .get(diver)
+                .unwrap()
+                .position,
+            Vector::new(700, 150)
+        );
+
+        step(&mut world);
+        let direction = (Vector::new(100, 450) - Vector::new(700, 150)).normalize();
+        assert!((velocity(&world).normalize() - direction).len() < 0.001);
+
+        world
+            .write_storage::<Position>()
+            .get_mut(hero)
+            .unwrap()
+            .position = Vector::new(100, 50);
+        step(&mut world);
+        assert!((velocity(&world).normalize() - direction).len() < 0.001);
+        assert!(velocity(&world).len() > DiverConfig::default().dive_speed);
+    }
+
+    fn create_dashing_enemy(
+        world: &mut World,
+        config: &DashEntranceConfig,
+        acceleration: Option<Acceleration>,
+    ) -> Entity {
+        let velocity = Vector::new(-50, 0);
+        let enemy = create_test_enemy(world, Vector::new(700, 300), 1);
+        world
+            .write_storage::<Velocity>()
+            .insert(enemy, Velocity { velocity })
+            .unwrap();
+        if let Some(acceleration) = acceleration {
+            world
+                .write_storage::<Acceleration>()
+                .insert(enemy, acceleration)
+                .unwrap();
+        }
+        world
+            .write_storage::<DashEntrance>()
+            .insert(enemy, crate::enemy::create_dash_entrance(config, velocity))
+            .unwrap();
+        enemy
+    }
+
+    fn get_speed(world: &World, enemy: Entity) -> f32 {
+        world
+            .read_storage::<Velocity>()
+            .get(enemy)
+            .unwrap()
+            .velocity
+            .len()
+    }
+
+    fn step_dash_entrance(world: &mut World) {
+        run(world, DashEntranceSystem);
+        run(world, WalkSystem);
+    }
+
+    #[test]
+    fn dash_entrance_telegraphs_then_decelerates_to_its_pattern_speed() {
+        let mut world = create_world(&SceneConfig::default());
+        let pattern_acceleration = Acceleration {
+            acceleration: 10.0,
+            max_speed: Some(80.0),
+        };
+        let config = DashEntranceConfig {
+            telegraph_ms: 50,
+            dash_speed: 400.0,
+            deceleration: 6000.0,
+            ..DashEntranceConfig::default()
+        };
+        let dasher = create_dashing_enemy(&mut world, &config, Some(pattern_acceleration));
+
+        for _ in 0..2 {
+            step_dash_entrance(&mut world);
+            assert_eq!(get_speed(&world, dasher), 0.0);
+        }
+
+        step_dash_entrance(&mut world);
+        let mut previous = get_speed(&world, dasher);
+        assert!(previous > 250.0);
+        while world.read_storage::<DashEntrance>().get(dasher).is_some() {
+            step_dash_entrance(&mut world);
+            assert!(get_speed(&world, dasher) < previous);
+            previous = get_speed(&world, dasher);
+        }
+        assert!((get_speed(&world, dasher) - 50.0).abs() < 1.0);
+        let acceleration = *world.read_storage::<Acceleration>().get(dasher).unwrap();
+        assert_eq!(acceleration.acceleration, pattern_acceleration.acceleration);
+        assert_eq!(acceleration.max_speed, pattern_acceleration.max_speed);
+    }
+
+    #[test]
+    fn dash_entrance_without_deceleration_ends_once_the_telegraph_expires() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = DashEntranceConfig {
+            telegraph_ms: 50,
+            deceleration: 0.0,
+            ..DashEntranceConfig::default()
+        };
+        let enemy = create_dashing_enemy(&mut world, &config, None);
+
+        for _ in 0..2 {
+            step_dash_entrance(&mut world);
+            assert_eq!(get_speed(&world, enemy), 0.0);
+            assert!(world.read_storage::<DashEntrance>().get(enemy).is_some());
+        }
+
+        step_dash_entrance(&mut world);
+        assert_eq!(get_speed(&world, enemy), 50.0);
+        assert!(world.read_storage::<DashEntrance>().get(enemy).is_none());
+        assert!(world.read_storage::<Acceleration>().get(enemy).is_none());
+    }
+}
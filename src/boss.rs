@@ -0,0 +1,291 @@
+use crate::{
+    component::{Boss, BossHealer, ChangeSprite, DespawnAnim, Position, Render, Shooter, Velocity},
+    effects::ScreenShake,
+    resources::SystemErrors,
+    system::FRAME_DURATION,
+};
+use quicksilver::{
+    geom::{Rectangle, Shape, Vector},
+    graphics::Color,
+};
+use specs::{Entities, Entity, Join, Read, ReadStorage, System, Write, WriteStorage};
+use std::time::Duration;
+
+const BOSS_HEALER_REACH: f32 = 40.0;
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct FinalPhase {
+    pub music_started: bool,
+    pub tint: Option<Color>,
+    pub shake: bool,
+}
+
+pub struct BossPhaseSystem;
+
+impl<'a> System<'a> for BossPhaseSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Boss>,
+        WriteStorage<'a, ChangeSprite>,
+        WriteStorage<'a, Shooter>,
+        Read<'a, FinalPhase>,
+        Write<'a, ScreenShake>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut boss,
+            mut change_sprite,
+            mut shooter,
+            final_phase,
+            mut screen_shake,
+        ): Self::SystemData,
+    ) {
+        for (e, boss) in (&entities, &mut boss).join() {
+            if boss.invulnerable > FRAME_DURATION {
+                boss.invulnerable -= FRAME_DURATION;
+            } else {
+                boss.invulnerable = Duration::from_millis(0);
+            }
+
+            if boss.enraged || boss.normal_lives > 0 {
+                continue;
+            }
+            boss.enraged = true;
+            boss.invulnerable = boss.phase_transition_invuln;
+            if final_phase.shake {
+                screen_shake.start();
+            }
+
+            let heal = (boss.maximum_lives as f32 * boss.phase_heal_fraction).round() as i32;
+            boss.lives = (boss.lives + heal).min(boss.maximum_lives);
+
+            if let Some(change_sprite) = change_sprite.get_mut(e) {
+                change_sprite.do_change = true;
+            }
+            if let Some(shooter) = shooter.get_mut(e) {
+                shooter.maximum_fireballs = 4;
+            }
+        }
+    }
+}
+
+pub struct BossHealerSystem;
+
+impl<'a> System<'a> for BossHealerSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Render>,
+        ReadStorage<'a, BossHealer>,
+        WriteStorage<'a, Boss>,
+        WriteStorage<'a, Velocity>,
+        ReadStorage<'a, DespawnAnim>,
+        Write<'a, SystemErrors>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            pos,
+            render,
+            boss_healer,
+            mut boss,
+            mut vel,
+            despawn_anim,
+            mut errors,
+        ): Self::SystemData,
+    ) {
+        let bosses: Vec<(Entity, Vector, Option<Rectangle>)> = (&entities, &pos, &render, &boss)
+            .join()
+            .map(|(e, pos, render, _)| {
+                let area = render
+                    .bounding_box
+                    .map(|bounding_box| bounding_box.with_center(pos.position));
+                (e, pos.position, area)
+            })
+            .collect();
+        let (e_boss, boss_position, boss_area) = match bosses.first() {
+            Some(&boss) => boss,
+            None => return,
+        };
+
+        for (e, pos, render, boss_healer, vel, _) in (
+            &entities,
+            &pos,
+            &render,
+            &boss_healer,
+            &mut vel,
+            !&despawn_anim,
+        )
+            .join()
+        {
+            vel.velocity =
+                crate::enemy::get_dive_velocity(pos.position, boss_position, boss_healer.speed);
+            let reached = match (render.bounding_box, boss_area) {
+                (Some(bounding_box), Some(boss_area)) => {
+                    bounding_box.with_center(pos.position).overlaps(&boss_area)
+                }
+                _ => pos.position.distance(boss_position) <= BOSS_HEALER_REACH,
+            };
+            if !reached {
+                continue;
+            }
+            if let Some(boss) = boss.get_mut(e_boss) {
+                crate::enemy::heal_boss(boss, boss_healer.amount);
+            }
+            crate::system::delete_entity(&entities, e, &mut errors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::PlayerId,
+        enemy::{BossConfig, BossHealerConfig},
+        hero::HeroConfig,
+        scene::{create_world, SceneConfig},
+        system::{CollisionSystem, WalkSystem},
+        test_support::{fire_hero_fireball, run, set_bounding_box},
+    };
+    use specs::World;
+
+    fn transition_boss_phase(lives: i32) -> i32 {
+        let mut world = create_world(&SceneConfig::default());
+        crate::enemy::create_boss(
+            &mut world,
+            BossConfig {
+                lives: 20,
+                phase_heal_fraction: 0.25,
+                ..BossConfig::default()
+            },
+        );
+        for boss in (&mut world.write_storage::<Boss>()).join() {
+            boss.lives = lives;
+            boss.normal_lives = 0;
+        }
+
+        run(&mut world, BossPhaseSystem);
+
+        let boss = world.read_storage::<Boss>();
+        let boss = (&boss).join().next().unwrap();
+        assert!(boss.enraged);
+        boss.lives
+    }
+
+    #[test]
+    fn boss_ignores_fireballs_during_phase_transition_window() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        crate::enemy::create_boss(
+            &mut world,
+            BossConfig {
+                position: Vector::new(400, 400),
+                normal_lives: 0,
+                phase_transition_invuln_ms: 100,
+                ..BossConfig::default()
+            },
+        );
+        let boss = (&world.entities(), &world.read_storage::<Boss>())
+            .join()
+            .map(|(e, _)| e)
+            .next()
+            .unwrap();
+        set_bounding_box(&mut world, boss, Vector::new(60, 60));
+        let lives = |world: &World| world.read_storage::<Boss>().get(boss).unwrap().lives;
+
+        run(&mut world, BossPhaseSystem);
+        fire_hero_fireball(&mut world, hero, Vector::new(400, 400));
+        run(&mut world, CollisionSystem);
+        assert_eq!(lives(&world), 10);
+
+        for _ in 0..6 {
+            run(&mut world, BossPhaseSystem);
+        }
+        fire_hero_fireball(&mut world, hero, Vector::new(400, 400));
+        run(&mut world, CollisionSystem);
+        assert_eq!(lives(&world), 9);
+    }
+
+    #[test]
+    fn boss_phase_transition_heals_fraction_of_max_lives() {
+        assert_eq!(transition_boss_phase(4), 9);
+        assert_eq!(transition_boss_phase(18), 20);
+    }
+
+    fn enrage_boss(boss_config: BossConfig) -> (bool, Option<Color>) {
+        let mut world = create_world(&SceneConfig {
+            boss_config: boss_config.clone(),
+            ..SceneConfig::default()
+        });
+        crate::enemy::create_boss(
+            &mut world,
+            BossConfig {
+                normal_lives: 0,
+                ..boss_config
+            },
+        );
+
+        run(&mut world, BossPhaseSystem);
+
+        assert!((&world.read_storage::<Boss>())
+            .join()
+            .all(|boss| boss.enraged));
+        let shaking = world.read_resource::<ScreenShake>().remaining > Duration::from_millis(0);
+        let tint = world.read_resource::<FinalPhase>().tint;
+        (shaking, tint)
+    }
+
+    #[test]
+    fn final_phase_tint_and_shake_are_opt_in() {
+        assert_eq!(enrage_boss(BossConfig::default()), (false, None));
+        let config = BossConfig {
+            final_phase_tint: Some([1.0, 0.0, 0.0, 1.0]),
+            final_phase_shake: true,
+            ..BossConfig::default()
+        };
+        assert_eq!(enrage_boss(config), (true, Some(Color::RED)));
+    }
+
+    #[test]
+    fn boss_healer_without_bounding_boxes_heals_the_boss_on_reach() {
+        let mut world = create_world(&SceneConfig::default());
+        crate::enemy::create_boss(
+            &mut world,
+            BossConfig {
+                position: Vector::new(600, 300),
+                lives: 5,
+                ..BossConfig::default()
+            },
+        );
+        let get_lives =
+            |world: &World| (&world.read_storage::<Boss>()).join().next().unwrap().lives;
+        for boss in (&mut world.write_storage::<Boss>()).join() {
+            boss.lives = 3;
+        }
+        let healer = crate::enemy::create_boss_healer(
+            &mut world,
+            BossHealerConfig {
+                position: Vector::new(500, 300),
+                heal_amount: 1,
+                speed: 300.0,
+                ..BossHealerConfig::default()
+            },
+        );
+
+        for _ in 0..60 {
+            if !world.is_alive(healer) {
+                break;
+            }
+            run(&mut world, BossHealerSystem);
+            run(&mut world, WalkSystem);
+        }
+        assert!(!world.is_alive(healer));
+        assert_eq!(get_lives(&world), 4);
+    }
+}
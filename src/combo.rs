@@ -0,0 +1,180 @@
+use crate::{
+    component::{Combo, Hero, Overdrive, Position},
+    resources::{SoundPriority, SoundQueue},
+    system::FRAME_DURATION,
+};
+use quicksilver::geom::Vector;
+use specs::{Join, ReadStorage, System, Write, WriteStorage};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone)]
+pub struct ComboMeterReading {
+    pub position: Vector,
+    pub level: f32,
+    pub fill: f32,
+    pub flashing: bool,
+}
+
+#[derive(Default)]
+pub struct ComboMeter {
+    pub readings: Vec<ComboMeterReading>,
+}
+
+pub struct ComboSystem;
+
+impl<'a> System<'a> for ComboSystem {
+    type SystemData = (WriteStorage<'a, Hero>, WriteStorage<'a, Combo>);
+
+    fn run(&mut self, (mut hero, mut combo): Self::SystemData) {
+        for (hero, combo) in (&mut hero, &mut combo).join() {
+            if hero.lives < combo.last_lives {
+                crate::hero::break_combo(hero, combo);
+            }
+            combo.last_lives = hero.lives;
+            combo.flash_remaining = combo
+                .flash_remaining
+                .checked_sub(FRAME_DURATION)
+                .unwrap_or_default();
+
+            if combo.timer > FRAME_DURATION {
+                combo.timer -= FRAME_DURATION;
+            } else {
+                combo.timer = Duration::from_millis(0);
+                crate::hero::break_combo(hero, combo);
+            }
+            hero.score_multiplier = hero.base_score_multiplier * combo.multiplier as f32;
+        }
+    }
+}
+
+pub struct OverdriveSystem;
+
+impl<'a> System<'a> for OverdriveSystem {
+    type SystemData = (
+        ReadStorage<'a, Combo>,
+        WriteStorage<'a, Overdrive>,
+        Write<'a, SoundQueue>,
+    );
+
+    fn run(&mut self, (combo, mut overdrive, mut sound_queue): Self::SystemData) {
+        for (combo, overdrive) in (&combo, &mut overdrive).join() {
+            if crate::hero::update_overdrive(overdrive, combo, FRAME_DURATION) {
+                sound_queue.push(&overdrive.sound, SoundPriority::High);
+            }
+        }
+    }
+}
+
+pub struct ComboMeterSystem;
+
+impl<'a> System<'a> for ComboMeterSystem {
+    type SystemData = (
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Combo>,
+        Write<'a, ComboMeter>,
+    );
+
+    fn run(&mut self, (pos, combo, mut combo_meter): Self::SystemData) {
+        combo_meter.readings = (&pos, &combo)
+            .join()
+            .map(|(pos, combo)| ComboMeterReading {
+                position: pos.position,
+                level: crate::hero::get_combo_meter_level(combo),
+                fill: crate::hero::get_combo_meter_fill(combo),
+                flashing: combo.flash_remaining > Duration::from_millis(0),
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::PlayerId,
+        hero::{ComboConfig, HeroConfig},
+        scene::{create_world, SceneConfig},
+        test_support::run,
+    };
+    use specs::World;
+
+    #[test]
+    fn expired_combo_resets_multiplier_and_applies_penalty_once() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            combo: Some(ComboConfig {
+                window_ms: 50,
+                max_multiplier: 4,
+                break_penalty: 30,
+                break_flash_ms: 100,
+            }),
+            ..HeroConfig::default()
+        };
+        let e = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        {
+            let mut hero = world.write_storage::<Hero>();
+            let hero = hero.get_mut(e).unwrap();
+            hero.score = 100;
+            let mut combo = world.write_storage::<Combo>();
+            let combo = combo.get_mut(e).unwrap();
+            crate::hero::register_combo_kill(combo);
+            crate::hero::register_combo_kill(combo);
+        }
+
+        run(&mut world, ComboSystem);
+        run(&mut world, ComboSystem);
+        assert_eq!(
+            world
+                .read_storage::<Hero>()
+                .get(e)
+                .unwrap()
+                .score_multiplier,
+            3.0
+        );
+
+        for _ in 0..5 {
+            run(&mut world, ComboSystem);
+        }
+        let hero = *world.read_storage::<Hero>().get(e).unwrap();
+        let combo = *world.read_storage::<Combo>().get(e).unwrap();
+        assert_eq!(combo.multiplier, 1);
+        assert_eq!(hero.score_multiplier, 1.0);
+        assert_eq!(hero.score, 70);
+        assert!(combo.flash_remaining > Duration::from_millis(0));
+    }
+
+    #[test]
+    fn combo_meter_drains_with_the_window_and_refills_on_a_kill() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            combo: Some(ComboConfig {
+                window_ms: 100,
+                max_multiplier: 4,
+                break_penalty: 0,
+                break_flash_ms: 0,
+            }),
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        let kill = |world: &mut World| {
+            crate::hero::register_combo_kill(world.write_storage::<Combo>().get_mut(hero).unwrap());
+        };
+        let fill = |world: &mut World| {
+            run(world, ComboSystem);
+            run(world, ComboMeterSystem);
+            world.read_resource::<ComboMeter>().readings[0].fill
+        };
+
+        kill(&mut world);
+        let mut fills = vec![fill(&mut world)];
+        for _ in 0..3 {
+            fills.push(fill(&mut world));
+        }
+        assert!(fills.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(fills[0] < 1.0);
+
+        kill(&mut world);
+        run(&mut world, ComboMeterSystem);
+        assert_eq!(world.read_resource::<ComboMeter>().readings[0].fill, 1.0);
+    }
+}
@@ -1,13 +1,21 @@
 use std::time::Duration;
 
-use crate::resources::LabelVariable;
+use crate::{
+    enemy::{
+        BossTimeBonusConfig, ContactDamage, DamageOverTimeConfig, DroneConfig, EmitterPattern,
+    },
+    hero::{RiskZoneConfig, WeaponModeConfig},
+    power_up::{DropEntry, PowerUpKind},
+    resources::LabelVariable,
+};
 
 use quicksilver::{
     geom::{Rectangle, Vector},
-    graphics::FontStyle,
+    graphics::{Color, FontStyle},
 };
 
-use specs::{world::Index, Component, NullStorage, VecStorage};
+use serde::{Deserialize, Serialize};
+use specs::{world::Index, Component, Entity, NullStorage, VecStorage};
 
 #[derive(Component, Debug, Copy, Clone)]
 #[storage(VecStorage)]
@@ -35,6 +43,8 @@ pub struct Shooter {
     pub maximum_fireballs: i32,
     pub fireball_amount: i32,
     pub coefficient: (f32, f32),
+    pub cooldown: Duration,
+    pub timer: Duration,
 }
 
 #[derive(Component, Debug, Copy, Clone)]
@@ -42,6 +52,23 @@ pub struct Shooter {
 pub struct Label {
     pub bind_variable: LabelVariable,
     pub font_style: FontStyle,
+    pub visible: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Anchor {
+    pub corner: Corner,
+    pub offset: Vector,
 }
 
 #[derive(Component, Debug, Copy, Clone)]
@@ -53,13 +80,86 @@ pub struct Hero {
     pub render: bool,
     pub reset_position: bool,
     pub blink_timer: Duration,
+    pub movement_margin: f32,
+    pub risk_zone: Option<RiskZoneConfig>,
+    pub base_score_multiplier: f32,
+    pub score_multiplier: f32,
+    pub pickup_magnet_radius: f32,
+    pub max_score: i32,
+    pub allow_extra_lives: bool,
+    pub allow_shields: bool,
+    pub bombs: u32,
+    pub bomb_held: bool,
+    pub weapon_drain_cooldown: Duration,
 }
 
 #[derive(Component, Debug, Copy, Clone)]
 #[storage(VecStorage)]
 pub struct Boss {
     pub lives: i32,
+    pub maximum_lives: i32,
     pub normal_lives: i32,
+    pub phase_heal_fraction: f32,
+    pub enraged: bool,
+    pub phase_transition_invuln: Duration,
+    pub invulnerable: Duration,
+    pub spawned_at: Duration,
+    pub time_bonus: Option<BossTimeBonusConfig>,
+    pub parts_alive: u32,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct FollowParent {
+    pub parent: Entity,
+    pub offset: Vector,
+    pub orbit_speed: f32,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct BossHealer {
+    pub amount: i32,
+    pub speed: f32,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Dive {
+    pub hover: Duration,
+    pub speed: f32,
+    pub acceleration: f32,
+    pub max_speed: Option<f32>,
+    pub target: Option<Vector>,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct DashEntrance {
+    pub telegraph: Duration,
+    pub dash_speed: f32,
+    pub deceleration: f32,
+    pub pattern_velocity: Vector,
+    pub pattern_acceleration: Option<Acceleration>,
+    pub dashing: bool,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Acceleration {
+    pub acceleration: f32,
+    pub max_speed: Option<f32>,
+}
+
+#[derive(Component, Debug, Clone)]
+#[storage(VecStorage)]
+pub struct DroneHost {
+    pub drone: DroneConfig,
+    pub max_drones: u32,
+    pub interval: Duration,
+    pub timer: Duration,
+    pub spawned: u32,
+    pub drones_alive: u32,
 }
 
 #[derive(Component, Debug, Clone)]
@@ -73,6 +173,10 @@ pub struct ChangeSprite {
 #[storage(VecStorage)]
 pub struct Enemy {
     pub score: i32,
+    pub despawn_ms: u64,
+    pub armor: Option<Vector>,
+    pub reflects: bool,
+    pub weapon_drain: Option<Duration>,
 }
 
 #[derive(Component, Debug, Default, Copy, Clone)]
@@ -89,8 +193,253 @@ pub struct Background;
 #[storage(NullStorage)]
 pub struct CalculateOutOfBounds;
 
-#[derive(Component, Debug, Default, Copy, Clone)]
+#[derive(Component, Debug, Copy, Clone)]
 #[storage(VecStorage)]
 pub struct Fireball {
     pub owner_id: Option<Index>,
+    pub faction: Faction,
+    pub damage_over_time: Option<DamageOverTimeConfig>,
+    pub contact_damage: Option<ContactDamage>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Faction {
+    Hero,
+    Enemy,
+}
+
+#[derive(Component, Debug, Clone)]
+#[storage(VecStorage)]
+pub struct HeroWeapon {
+    pub modes: Vec<WeaponModeConfig>,
+    pub current_mode: usize,
+    pub level: Option<usize>,
+    pub cooldown: Duration,
+    pub aim: Vector,
+    pub cycle_held: bool,
+    pub fireball_trail: bool,
+}
+
+#[derive(Component, Debug, Clone)]
+#[storage(VecStorage)]
+pub struct Overdrive {
+    pub duration: Duration,
+    pub cooldown_factor: f32,
+    pub sound: Option<String>,
+    pub remaining: Duration,
+    pub active: bool,
+    pub at_max: bool,
+}
+
+#[derive(Component, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[storage(VecStorage)]
+#[serde(default)]
+pub struct EntitySounds {
+    pub spawn: Option<String>,
+    pub death: Option<String>,
+    pub hit: Option<String>,
+}
+
+#[derive(Component, Debug, Clone)]
+#[storage(VecStorage)]
+pub struct SecondaryWeapon {
+    pub mode: WeaponModeConfig,
+    pub ammo: u32,
+    pub cooldown: Duration,
+    pub held: bool,
+}
+
+#[derive(Component, Debug, Clone)]
+#[storage(VecStorage)]
+pub struct Emitter {
+    pub pattern: EmitterPattern,
+    pub projectile_sprite: String,
+    pub projectile_speed: f32,
+    pub interval: Duration,
+    pub timer: Duration,
+    pub delay: Duration,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+}
+
+#[derive(Component, Debug, Clone)]
+#[storage(VecStorage)]
+pub struct DropTable {
+    pub entries: Vec<DropEntry>,
+}
+
+#[derive(Component, Debug, Clone)]
+#[storage(VecStorage)]
+pub struct BombDrop {
+    pub sprite: String,
+    pub velocity: Vector,
+}
+
+#[derive(Component, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[storage(VecStorage)]
+pub enum PlayerId {
+    One,
+    Two,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct ShieldAura {
+    pub radius: f32,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct MirrorHeroX {
+    pub inverted: bool,
+    pub speed: f32,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Dash {
+    pub speed: f32,
+    pub duration: Duration,
+    pub cooldown: Duration,
+    pub remaining: Duration,
+    pub cooldown_timer: Duration,
+    pub direction: f32,
+    pub held: bool,
+    pub afterimages: bool,
+    pub afterimage_timer: Duration,
+}
+
+#[derive(Component, Debug, Default, Copy, Clone)]
+#[storage(NullStorage)]
+pub struct ShieldDonor;
+
+#[derive(Component, Debug, Default, Copy, Clone)]
+#[storage(NullStorage)]
+pub struct OneHitShield;
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct SelfShield {
+    pub up: Duration,
+    pub down: Duration,
+    pub timer: Duration,
+    pub active: bool,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Ambient {
+    pub color: Color,
+    pub radius: f32,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Focus {
+    pub speed_factor: f32,
+    pub show_hitbox: bool,
+    pub active: bool,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Combo {
+    pub multiplier: u32,
+    pub max_multiplier: u32,
+    pub window: Duration,
+    pub timer: Duration,
+    pub break_penalty: i32,
+    pub break_flash: Duration,
+    pub flash_remaining: Duration,
+    pub last_lives: i32,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Lifetime {
+    pub remaining: Duration,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Health {
+    pub current: i32,
+    pub maximum: i32,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct DamageOverTime {
+    pub source_id: Option<Index>,
+    pub per_tick: i32,
+    pub remaining_ms: u64,
+    pub interval_ms: u64,
+    pub timer_ms: u64,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Particle {
+    pub color: Color,
+    pub radius: f32,
+    pub duration: Duration,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct Trail {
+    pub color: Color,
+    pub interval: Duration,
+    pub timer: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct MimicShot {
+    pub remaining: Duration,
+    pub sprite: String,
+    pub speed: f32,
+}
+
+#[derive(Component, Debug, Clone)]
+#[storage(VecStorage)]
+pub struct Mimic {
+    pub delay: Duration,
+    pub pending: Vec<MimicShot>,
+}
+
+#[derive(Component, Debug, Clone)]
+#[storage(VecStorage)]
+pub struct ChargeAttack {
+    pub cooldown_ms: u64,
+    pub charge_ms: u64,
+    pub elapsed_ms: u64,
+    pub charging: bool,
+    pub pattern: EmitterPattern,
+    pub projectile_sprite: String,
+    pub projectile_speed: f32,
+    pub saved_velocity: Option<Vector>,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct FreezeFor {
+    pub remaining_ms: u64,
+    pub saved_velocity: Option<Vector>,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct HitFlash {
+    pub remaining: Duration,
+}
+
+#[derive(Component, Debug, Copy, Clone)]
+#[storage(VecStorage)]
+pub struct DespawnAnim {
+    pub remaining_ms: u64,
+    pub duration_ms: u64,
 }
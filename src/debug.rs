@@ -0,0 +1,105 @@
+use quicksilver::geom::Vector;
+use std::{collections::VecDeque, time::Duration};
+
+const PROFILE_OVERLAY_WIDTH: f32 = 250.0;
+const PROFILE_OVERLAY_TOP: f32 = 100.0;
+const PROFILE_OVERLAY_ROW_HEIGHT: f32 = 14.0;
+
+#[derive(Debug, Clone)]
+pub struct InputRecord {
+    pub timestamp: Duration,
+    pub description: String,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct InputHistory {
+    pub enabled: bool,
+    pub capacity: usize,
+    pub entries: VecDeque<InputRecord>,
+}
+
+impl InputHistory {
+    pub fn push(&mut self, timestamp: Duration, description: String) {
+        if !self.enabled || self.capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(InputRecord {
+            timestamp,
+            description,
+        });
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SystemProfile {
+    pub enabled: bool,
+    pub show_overlay: bool,
+    pub timings: Vec<(&'static str, Duration)>,
+}
+
+impl SystemProfile {
+    pub fn clear(&mut self) {
+        self.timings.clear();
+    }
+
+    pub fn record(&mut self, name: &'static str, elapsed: Duration) {
+        match self.timings.iter_mut().find(|(system, _)| *system == name) {
+            Some(timing) => timing.1 = elapsed,
+            None => self.timings.push((name, elapsed)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Duration> {
+        self.timings
+            .iter()
+            .find(|(system, _)| *system == name)
+            .map(|&(_, elapsed)| elapsed)
+    }
+}
+
+pub fn get_profile_overlay_position(field_size: Vector, row: usize) -> Vector {
+    Vector::new(
+        field_size.x - PROFILE_OVERLAY_WIDTH,
+        PROFILE_OVERLAY_TOP + row as f32 * PROFILE_OVERLAY_ROW_HEIGHT,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_history_keeps_only_the_newest_entries() {
+        let mut history = InputHistory {
+            enabled: true,
+            capacity: 3,
+            ..InputHistory::default()
+        };
+        for i in 0..5 {
+            history.push(Duration::from_millis(i * 100), format!("Key {}", i));
+        }
+
+        let entries: Vec<&str> = history
+            .entries
+            .iter()
+            .map(|entry| entry.description.as_str())
+            .collect();
+        assert_eq!(entries, ["Key 2", "Key 3", "Key 4"]);
+        assert_eq!(history.entries[0].timestamp, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn profile_overlay_tracks_the_play_field_width() {
+        assert_eq!(
+            get_profile_overlay_position(Vector::new(800, 600), 2),
+            Vector::new(550, 128)
+        );
+        assert_eq!(
+            get_profile_overlay_position(Vector::new(1280, 720), 0),
+            Vector::new(1030, 100)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{component::Hero, entity_factory::SpawnPacingConfig, resources::GameMetrics};
+use serde::{Deserialize, Serialize};
+use specs::{Join, Read, ReadStorage, System, Write};
+use std::{collections::VecDeque, time::Duration};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct RubberBandConfig {
+    pub min_factor: f32,
+    pub max_factor: f32,
+    pub expected_lives: i32,
+    pub expected_score_rate: f32,
+}
+
+impl Default for RubberBandConfig {
+    fn default() -> RubberBandConfig {
+        RubberBandConfig {
+            min_factor: 0.8,
+            max_factor: 1.2,
+            expected_lives: 3,
+            expected_score_rate: 20.0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct RubberBand {
+    pub config: Option<RubberBandConfig>,
+    pub factor: f32,
+}
+
+impl RubberBand {
+    pub fn new(config: Option<RubberBandConfig>) -> RubberBand {
+        RubberBand {
+            config,
+            factor: 1.0,
+        }
+    }
+}
+
+impl Default for RubberBand {
+    fn default() -> RubberBand {
+        RubberBand::new(None)
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SpawnPacing {
+    pub enabled: bool,
+    pub min_cycle: f32,
+    pub max_cycle: f32,
+    pub window: Duration,
+    pub target_kills: usize,
+    pub step: f32,
+    pub cycle: f32,
+    pub last_kills: u32,
+    pub kill_times: VecDeque<Duration>,
+}
+
+impl SpawnPacing {
+    pub fn new(config: Option<SpawnPacingConfig>, initial_cycle: u64) -> SpawnPacing {
+        match config {
+            Some(pacing) => {
+                let min_cycle = pacing.min_cycle.max(1) as f32;
+                let max_cycle = (pacing.max_cycle as f32).max(min_cycle);
+                SpawnPacing {
+                    enabled: true,
+                    min_cycle,
+                    max_cycle,
+                    window: Duration::from_millis(pacing.window_ms),
+                    target_kills: pacing.target_kills,
+                    step: pacing.step,
+                    cycle: (initial_cycle as f32).max(min_cycle).min(max_cycle),
+                    ..SpawnPacing::default()
+                }
+            }
+            None => SpawnPacing::default(),
+        }
+    }
+}
+
+pub struct SpawnPacingSystem;
+
+impl<'a> System<'a> for SpawnPacingSystem {
+    type SystemData = (Read<'a, GameMetrics>, Write<'a, SpawnPacing>);
+
+    fn run(&mut self, (metrics, mut pacing): Self::SystemData) {
+        if !pacing.enabled {
+            return;
+        }
+        for _ in pacing.last_kills..metrics.kills {
+            pacing.kill_times.push_back(metrics.elapsed);
+        }
+        pacing.last_kills = metrics.kills;
+        while let Some(&kill_time) = pacing.kill_times.front() {
+            if metrics.elapsed - kill_time > pacing.window {
+                pacing.kill_times.pop_front();
+            } else {
+                break;
+            }
+        }
+        pacing.cycle = get_paced_spawn_cycle(&pacing, pacing.kill_times.len());
+    }
+}
+
+pub struct RubberBandSystem;
+
+impl<'a> System<'a> for RubberBandSystem {
+    type SystemData = (
+        ReadStorage<'a, Hero>,
+        Read<'a, GameMetrics>,
+        Write<'a, RubberBand>,
+    );
+
+    fn run(&mut self, (hero, metrics, mut rubber_band): Self::SystemData) {
+        let config = match rubber_band.config {
+            Some(config) => config,
+            None => {
+                rubber_band.factor = 1.0;
+                return;
+            }
+        };
+        let (lives, score) = (&hero).join().fold((0, 0), |(lives, score), hero| {
+            (lives.max(hero.lives), score + hero.score)
+        });
+        rubber_band.factor = get_rubber_band_factor(lives, score, metrics.elapsed, &config);
+    }
+}
+
+pub fn get_rubber_band_factor(
+    lives: i32,
+    score: i32,
+    elapsed: Duration,
+    config: &RubberBandConfig,
+) -> f32 {
+    let expected_lives = config.expected_lives.max(1) as f32;
+    let lives_term = (lives as f32 - expected_lives) / expected_lives;
+    let score_term = if config.expected_score_rate > 0.0 && elapsed.as_secs_f32() > 0.0 {
+        let score_rate = score as f32 / elapsed.as_secs_f32();
+        (score_rate - config.expected_score_rate) / config.expected_score_rate
+    } else {
+        0.0
+    };
+    let performance = ((lives_term + score_term) / 2.0).clamp(-1.0, 1.0);
+    let factor = if performance > 0.0 {
+        1.0 + performance * (config.max_factor - 1.0)
+    } else {
+        1.0 + performance * (1.0 - config.min_factor)
+    };
+    factor.max(config.min_factor).min(config.max_factor)
+}
+
+pub fn get_paced_spawn_cycle(pacing: &SpawnPacing, recent_kills: usize) -> f32 {
+    let target = pacing.target_kills.max(1) as f32;
+    let error = ((recent_kills as f32 - target) / target).clamp(-1.0, 1.0);
+    (pacing.cycle - pacing.step * error)
+        .max(pacing.min_cycle)
+        .min(pacing.max_cycle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_spawn_pacing(recent_kills: usize, frames: u32) -> f32 {
+        let mut pacing = SpawnPacing {
+            enabled: true,
+            min_cycle: 120.0,
+            max_cycle: 300.0,
+            target_kills: 4,
+            step: 2.0,
+            cycle: 210.0,
+            ..SpawnPacing::default()
+        };
+        for _ in 0..frames {
+            pacing.cycle = get_paced_spawn_cycle(&pacing, recent_kills);
+        }
+        pacing.cycle
+    }
+
+    #[test]
+    fn spawn_pacing_moves_proportionally_within_bounds() {
+        assert_eq!(run_spawn_pacing(4, 100), 210.0);
+        assert_eq!(run_spawn_pacing(6, 1), 209.0);
+        assert_eq!(run_spawn_pacing(2, 1), 211.0);
+        assert_eq!(run_spawn_pacing(20, 1000), 120.0);
+        assert_eq!(run_spawn_pacing(0, 1000), 300.0);
+    }
+
+    #[test]
+    fn spawn_pacing_starts_from_the_clamped_scene_cycle() {
+        let config = SpawnPacingConfig {
+            min_cycle: 120,
+            max_cycle: 300,
+            ..SpawnPacingConfig::default()
+        };
+        assert_eq!(SpawnPacing::new(Some(config), 500).cycle, 300.0);
+        assert_eq!(SpawnPacing::new(Some(config), 60).cycle, 120.0);
+        assert!(!SpawnPacing::new(None, 60).enabled);
+    }
+}
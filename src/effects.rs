@@ -0,0 +1,235 @@
+use crate::{
+    component::{FreezeFor, HitFlash, Velocity},
+    resources::RandomGenerator,
+    system::FRAME_DURATION,
+};
+use quicksilver::{geom::Vector, graphics::Color};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use specs::{Entities, Entity, Join, System, Write, WriteStorage};
+use std::time::Duration;
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct HitFlashSettings {
+    pub color: Option<Color>,
+    pub duration: Duration,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct HitFreeze {
+    pub freeze_ms: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+pub enum ShakeCurve {
+    #[default]
+    Linear,
+    EaseOut,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct ScreenShake {
+    pub curve: ShakeCurve,
+    pub intensity: f32,
+    pub duration: Duration,
+    pub remaining: Duration,
+    pub offset: Vector,
+}
+
+impl ScreenShake {
+    pub fn start(&mut self) {
+        self.remaining = self.duration;
+    }
+
+    pub fn current_intensity(&self) -> f32 {
+        if self.duration == Duration::from_millis(0) {
+            return 0.0;
+        }
+        let progress = self.remaining.as_secs_f32() / self.duration.as_secs_f32();
+        match self.curve {
+            ShakeCurve::Linear => self.intensity * progress,
+            ShakeCurve::EaseOut => self.intensity * progress * progress,
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct ScreenFlash {
+    pub color: Option<Color>,
+    pub duration: Duration,
+    pub remaining: Duration,
+}
+
+impl ScreenFlash {
+    pub fn new(color: Option<Color>, duration_ms: u64) -> ScreenFlash {
+        ScreenFlash {
+            color,
+            duration: Duration::from_millis(duration_ms),
+            ..ScreenFlash::default()
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.remaining = self.duration;
+    }
+
+    pub fn current_intensity(&self) -> f32 {
+        if self.duration == Duration::from_millis(0) {
+            return 0.0;
+        }
+        self.remaining.as_secs_f32() / self.duration.as_secs_f32()
+    }
+}
+
+pub struct ScreenFlashSystem;
+
+impl<'a> System<'a> for ScreenFlashSystem {
+    type SystemData = Write<'a, ScreenFlash>;
+
+    fn run(&mut self, mut screen_flash: Self::SystemData) {
+        if screen_flash.remaining > FRAME_DURATION {
+            screen_flash.remaining -= FRAME_DURATION;
+        } else {
+            screen_flash.remaining = Duration::from_millis(0);
+        }
+    }
+}
+
+pub struct ScreenShakeSystem;
+
+impl<'a> System<'a> for ScreenShakeSystem {
+    type SystemData = (Write<'a, ScreenShake>, Write<'a, RandomGenerator>);
+
+    fn run(&mut self, (mut screen_shake, mut rng): Self::SystemData) {
+        if screen_shake.remaining > FRAME_DURATION {
+            screen_shake.remaining -= FRAME_DURATION;
+            let intensity = screen_shake.current_intensity();
+            screen_shake.offset = Vector::from_angle(rng.rng.gen::<f32>() * 360.0)
+                * (intensity * rng.rng.gen::<f32>());
+        } else {
+            screen_shake.remaining = Duration::from_millis(0);
+            screen_shake.offset = Vector::ZERO;
+        }
+    }
+}
+
+pub struct FreezeSystem;
+
+impl<'a> System<'a> for FreezeSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, FreezeFor>,
+        WriteStorage<'a, Velocity>,
+    );
+
+    fn run(&mut self, (entities, mut freeze, mut vel): Self::SystemData) {
+        let frame_ms = FRAME_DURATION.as_millis() as u64;
+        let mut thawed: Vec<Entity> = Vec::new();
+        for (e, freeze, vel) in (&entities, &mut freeze, &mut vel).join() {
+            if freeze.saved_velocity.is_none() {
+                freeze.saved_velocity = Some(vel.velocity);
+                vel.velocity = Vector::ZERO;
+            }
+            if freeze.remaining_ms > frame_ms {
+                freeze.remaining_ms -= frame_ms;
+            } else {
+                if let Some(saved_velocity) = freeze.saved_velocity {
+                    vel.velocity = saved_velocity;
+                }
+                thawed.push(e);
+            }
+        }
+        for e in thawed {
+            freeze.remove(e);
+        }
+    }
+}
+
+pub struct HitFlashSystem;
+
+impl<'a> System<'a> for HitFlashSystem {
+    type SystemData = (Entities<'a>, WriteStorage<'a, HitFlash>);
+
+    fn run(&mut self, (entities, mut hit_flash): Self::SystemData) {
+        let mut expired: Vec<Entity> = Vec::new();
+        for (e, hit_flash) in (&entities, &mut hit_flash).join() {
+            if hit_flash.remaining > FRAME_DURATION {
+                hit_flash.remaining -= FRAME_DURATION;
+            } else {
+                expired.push(e);
+            }
+        }
+        for e in expired {
+            hit_flash.remove(e);
+        }
+    }
+}
+
+pub fn get_hit_flash_tint(
+    hit_flash: Option<&HitFlash>,
+    hit_flash_settings: &HitFlashSettings,
+) -> Option<Color> {
+    hit_flash.and(hit_flash_settings.color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::PlayerId,
+        hero::HeroConfig,
+        scene::{create_world, SceneConfig},
+        system::CollisionSystem,
+        test_support::{create_test_enemy, fire_hero_fireball, run},
+    };
+    use specs::World;
+
+    fn get_midpoint_intensity(curve: ShakeCurve) -> f32 {
+        let mut screen_shake = ScreenShake {
+            curve,
+            intensity: 6.0,
+            duration: Duration::from_millis(300),
+            ..ScreenShake::default()
+        };
+        screen_shake.start();
+        screen_shake.remaining = Duration::from_millis(150);
+        screen_shake.current_intensity()
+    }
+
+    #[test]
+    fn ease_out_shake_is_weaker_than_linear_at_midpoint() {
+        let linear = get_midpoint_intensity(ShakeCurve::Linear);
+        let ease_out = get_midpoint_intensity(ShakeCurve::EaseOut);
+        assert_eq!(linear, 3.0);
+        assert!(ease_out < linear);
+    }
+
+    #[test]
+    fn hit_enemy_flashes_for_the_configured_duration() {
+        let mut world = create_world(&SceneConfig {
+            hit_flash_color: Some([1.0, 0.0, 0.0, 1.0]),
+            hit_flash_ms: 50,
+            ..SceneConfig::default()
+        });
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let enemy = create_test_enemy(&mut world, Vector::new(400, 300), 2);
+        let tint = |world: &World| {
+            let hit_flash = world.read_storage::<HitFlash>();
+            get_hit_flash_tint(
+                hit_flash.get(enemy),
+                &world.read_resource::<HitFlashSettings>(),
+            )
+        };
+        assert_eq!(tint(&world), None);
+
+        fire_hero_fireball(&mut world, hero, Vector::new(400, 300));
+        run(&mut world, CollisionSystem);
+        for _ in 0..2 {
+            assert_eq!(tint(&world), Some(Color::RED));
+            run(&mut world, HitFlashSystem);
+        }
+        assert_eq!(tint(&world), Some(Color::RED));
+        run(&mut world, HitFlashSystem);
+        assert_eq!(tint(&world), None);
+    }
+}
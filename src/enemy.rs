@@ -1,18 +1,24 @@
 use crate::component::{
-    Boss, CalculateOutOfBounds, ChangeSprite, Enemy, Fireball, Position, Render, Shooter, Velocity,
+    Acceleration, Boss, BossHealer, CalculateOutOfBounds, ChangeSprite, ChargeAttack,
+    DamageOverTime, DashEntrance, Dive, DroneHost, Emitter, Enemy, EntitySounds, Faction, Fireball,
+    FollowParent, Health, Lifetime, Mimic, MirrorHeroX, Position, Render, SelfShield, ShieldAura,
+    ShieldDonor, Shooter, Velocity,
 };
+use crate::resources::{GameMetrics, RandomGenerator};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 use specs::{
     world::{Builder, Index},
-    World,
+    Entity, World,
 };
 
 use quicksilver::geom::{Rectangle, Shape, Vector};
 
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 const ENEMY_HEAD_HEIGHT: f32 = 10.;
+const ENEMY_DESPAWN_MS: u64 = 0;
 
 pub fn get_enemy_head_body_area(self_area: Rectangle, position: Vector) -> (Rectangle, Rectangle) {
     let self_area = self_area.with_center(position);
@@ -35,17 +41,41 @@ pub struct EnemyConfig {
     pub velocity: Vector,
     pub score: i32,
     pub shooter_config: Option<ShooterConfig>,
+    pub emitter_config: Option<EmitterConfig>,
+    #[serde(default)]
+    pub despawn_ms: u64,
+    #[serde(default = "get_default_enemy_health")]
+    pub health: i32,
+    #[serde(default)]
+    pub armor: Option<Vector>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+fn get_default_enemy_health() -> i32 {
+    1
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
 pub enum EnemyType {
     Walker,
     Shooter,
     Flyer,
     FireballShower,
+    Shielder,
+    Mirror,
+    Armored,
+    Turtle,
+    Benefactor,
+    RippleLine,
+    Mimic,
+    Charger,
+    Drainer,
+    Reflector,
+    DroneHost,
+    Diver,
+    BossHealer,
 }
 
-pub fn create_enemy(world: &mut World, config: EnemyConfig) {
+pub fn create_enemy(world: &mut World, config: EnemyConfig) -> Entity {
     let mut builder = world
         .create_entity()
         .with(CalculateOutOfBounds)
@@ -61,6 +91,14 @@ pub fn create_enemy(world: &mut World, config: EnemyConfig) {
         })
         .with(Enemy {
             score: config.score,
+            despawn_ms: config.despawn_ms,
+            armor: config.armor,
+            reflects: false,
+            weapon_drain: None,
+        })
+        .with(Health {
+            current: config.health,
+            maximum: config.health,
         });
     if let Some(shooter_config) = config.shooter_config {
         builder = builder.with(Shooter {
@@ -68,19 +106,29 @@ pub fn create_enemy(world: &mut World, config: EnemyConfig) {
             maximum_fireballs: shooter_config.maximum_projectiles,
             fireball_amount: 0,
             coefficient: shooter_config.projectile_coefficient,
+            cooldown: Duration::from_millis(shooter_config.cooldown_ms),
+            timer: Duration::from_millis(0),
         });
     }
-    builder.build();
+    if let Some(emitter_config) = config.emitter_config {
+        builder = builder.with(create_emitter(emitter_config));
+    }
+    builder.build()
 }
 
-pub fn create_walker(world: &mut World) {
-    let config = if rand::random() {
+pub fn create_walker(world: &mut World) -> Entity {
+    let flipped: bool = world.write_resource::<RandomGenerator>().rng.gen();
+    let config = if !flipped {
         EnemyConfig {
             sprite: "andador".to_string(),
             position: Vector::new(850.0, 432.0),
             velocity: Vector::new(-125.0, 0.0),
             score: 100,
             shooter_config: None,
+            emitter_config: None,
+            despawn_ms: ENEMY_DESPAWN_MS,
+            health: 1,
+            armor: None,
         }
     } else {
         EnemyConfig {
@@ -89,12 +137,16 @@ pub fn create_walker(world: &mut World) {
             velocity: Vector::new(125.0, 0.0),
             score: 100,
             shooter_config: None,
+            emitter_config: None,
+            despawn_ms: ENEMY_DESPAWN_MS,
+            health: 1,
+            armor: None,
         }
     };
-    create_enemy(world, config);
+    create_enemy(world, config)
 }
 
-pub fn create_shooter(world: &mut World) {
+pub fn create_shooter(world: &mut World) -> Entity {
     let config = EnemyConfig {
         sprite: "atirador".to_string(),
         position: Vector::new(850.0, 433.5),
@@ -104,12 +156,17 @@ pub fn create_shooter(world: &mut World) {
             projectile_sprite: "tiro".to_string(),
             maximum_projectiles: 2,
             projectile_coefficient: (0.175, 0.0),
+            cooldown_ms: 0,
         }),
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: 1,
+        armor: None,
     };
-    create_enemy(world, config);
+    create_enemy(world, config)
 }
 
-pub fn create_flyer(world: &mut World) {
+pub fn create_flyer(world: &mut World) -> Entity {
     let config = EnemyConfig {
         sprite: "alma".to_string(),
         position: Vector::new(850.0, 400.0),
@@ -119,9 +176,902 @@ pub fn create_flyer(world: &mut World) {
             projectile_sprite: "tiro".to_string(),
             maximum_projectiles: 1,
             projectile_coefficient: (0.250, 0.0),
+            cooldown_ms: 0,
         }),
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: 1,
+        armor: None,
+    };
+    create_enemy(world, config)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ShielderConfig {
+    pub sprite: String,
+    pub velocity: Vector,
+    pub score: i32,
+    pub radius: f32,
+}
+
+impl Default for ShielderConfig {
+    fn default() -> ShielderConfig {
+        ShielderConfig {
+            sprite: "alma".to_string(),
+            velocity: Vector::new(-75.0, 0.0),
+            score: 300,
+            radius: 150.0,
+        }
+    }
+}
+
+pub fn create_shielder(world: &mut World, config: ShielderConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: Vector::new(850.0, 400.0),
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: 1,
+        armor: None,
+    };
+    let shielder = create_enemy(world, enemy_config);
+    world
+        .write_storage::<ShieldAura>()
+        .insert(
+            shielder,
+            ShieldAura {
+                radius: config.radius,
+            },
+        )
+        .unwrap();
+    shielder
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MirrorConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub score: i32,
+    pub speed: f32,
+    pub inverted: bool,
+}
+
+impl Default for MirrorConfig {
+    fn default() -> MirrorConfig {
+        MirrorConfig {
+            sprite: "andador".to_string(),
+            position: Vector::new(400.0, 432.0),
+            score: 250,
+            speed: 100.0,
+            inverted: false,
+        }
+    }
+}
+
+pub fn create_mirror(world: &mut World, config: MirrorConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: Vector::ZERO,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: 1,
+        armor: None,
+    };
+    let mirror = create_enemy(world, enemy_config);
+    world
+        .write_storage::<MirrorHeroX>()
+        .insert(
+            mirror,
+            MirrorHeroX {
+                inverted: config.inverted,
+                speed: config.speed,
+            },
+        )
+        .unwrap();
+    mirror
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ArmoredConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+    pub health: i32,
+    pub facing: Vector,
+}
+
+impl Default for ArmoredConfig {
+    fn default() -> ArmoredConfig {
+        ArmoredConfig {
+            sprite: "atirador".to_string(),
+            position: Vector::new(850.0, 433.5),
+            velocity: Vector::new(-100.0, 0.0),
+            score: 250,
+            health: 2,
+            facing: Vector::new(-1.0, 0.0),
+        }
+    }
+}
+
+pub fn create_armored(world: &mut World, config: ArmoredConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: config.health,
+        armor: Some(config.facing),
+    };
+    create_enemy(world, enemy_config)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DroneConfig {
+    pub sprite: String,
+    pub health: i32,
+    pub score: i32,
+    pub orbit_radius: f32,
+    pub orbit_speed: f32,
+}
+
+impl Default for DroneConfig {
+    fn default() -> DroneConfig {
+        DroneConfig {
+            sprite: "fireball".to_string(),
+            health: 1,
+            score: 50,
+            orbit_radius: 60.0,
+            orbit_speed: 2.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DroneHostConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+    pub health: i32,
+    pub drone: DroneConfig,
+    pub max_drones: u32,
+    pub spawn_interval_ms: u64,
+}
+
+impl Default for DroneHostConfig {
+    fn default() -> DroneHostConfig {
+        DroneHostConfig {
+            sprite: "atirador".to_string(),
+            position: Vector::new(850.0, 380.0),
+            velocity: Vector::new(-60.0, 0.0),
+            score: 500,
+            health: 3,
+            drone: DroneConfig::default(),
+            max_drones: 3,
+            spawn_interval_ms: 1500,
+        }
+    }
+}
+
+pub fn create_drone_host(world: &mut World, config: DroneHostConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: config.health,
+        armor: None,
+    };
+    let host = create_enemy(world, enemy_config);
+    world
+        .write_storage::<DroneHost>()
+        .insert(
+            host,
+            DroneHost {
+                drone: config.drone,
+                max_drones: config.max_drones,
+                interval: Duration::from_millis(config.spawn_interval_ms),
+                timer: Duration::from_millis(0),
+                spawned: 0,
+                drones_alive: 0,
+            },
+        )
+        .unwrap();
+    host
+}
+
+pub fn create_drone(world: &mut World, host: Entity, angle: f32, config: DroneConfig) -> Entity {
+    let center = match world.read_storage::<Position>().get(host) {
+        Some(pos) => pos.position,
+        None => Vector::ZERO,
+    };
+    let offset = get_orbit_offset(config.orbit_radius, angle);
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: center + offset,
+        velocity: Vector::ZERO,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: config.health,
+        armor: None,
+    };
+    let drone = create_enemy(world, enemy_config);
+    world
+        .write_storage::<FollowParent>()
+        .insert(
+            drone,
+            FollowParent {
+                parent: host,
+                offset,
+                orbit_speed: config.orbit_speed,
+            },
+        )
+        .unwrap();
+    drone
+}
+
+pub fn get_rotated_offset(offset: Vector, angle: f32) -> Vector {
+    if angle == 0.0 {
+        return offset;
+    }
+    let (sin, cos) = angle.sin_cos();
+    Vector::new(
+        offset.x * cos - offset.y * sin,
+        offset.x * sin + offset.y * cos,
+    )
+}
+
+pub fn get_orbit_offset(radius: f32, angle: f32) -> Vector {
+    Vector::new(angle.cos(), angle.sin()) * radius
+}
+
+pub fn get_drone_angle(spawned: u32, max_drones: u32) -> f32 {
+    if max_drones == 0 {
+        return 0.0;
+    }
+    (spawned % max_drones) as f32 * std::f32::consts::PI * 2.0 / max_drones as f32
+}
+
+pub fn is_drone_shielded(drone_host: Option<&DroneHost>) -> bool {
+    drone_host.is_some_and(|drone_host| drone_host.drones_alive > 0)
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct AccelerationConfig {
+    pub enemy_type: EnemyType,
+    pub acceleration: f32,
+    #[serde(default)]
+    pub max_speed: Option<f32>,
+}
+
+pub fn get_accelerated_velocity(
+    velocity: Vector,
+    acceleration: &Acceleration,
+    elapsed: f32,
+) -> Vector {
+    let speed = velocity.len();
+    if speed == 0.0 {
+        return velocity;
+    }
+    let mut new_speed = (speed + acceleration.acceleration * elapsed).max(0.0);
+    if let Some(max_speed) = acceleration.max_speed {
+        new_speed = new_speed.min(max_speed);
+    }
+    velocity.normalize() * new_speed
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct DashEntranceConfig {
+    pub enemy_type: EnemyType,
+    pub telegraph_ms: u64,
+    pub dash_speed: f32,
+    pub deceleration: f32,
+}
+
+impl Default for DashEntranceConfig {
+    fn default() -> DashEntranceConfig {
+        DashEntranceConfig {
+            enemy_type: EnemyType::Walker,
+            telegraph_ms: 800,
+            dash_speed: 600.0,
+            deceleration: 900.0,
+        }
+    }
+}
+
+pub fn create_dash_entrance(config: &DashEntranceConfig, velocity: Vector) -> DashEntrance {
+    DashEntrance {
+        telegraph: Duration::from_millis(config.telegraph_ms),
+        dash_speed: config.dash_speed.max(velocity.len()),
+        deceleration: config.deceleration,
+        pattern_velocity: velocity,
+        pattern_acceleration: None,
+        dashing: false,
+    }
+}
+
+pub fn get_dash_marker_position(position: Vector, size: Vector, margin: f32) -> Vector {
+    Vector::new(
+        position.x.max(margin).min(size.x - margin),
+        position.y.max(margin).min(size.y - margin),
+    )
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnemySoundsConfig {
+    pub enemy_type: EnemyType,
+    pub sounds: EntitySounds,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ReflectorConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+    pub health: i32,
+    pub facing: Vector,
+}
+
+impl Default for ReflectorConfig {
+    fn default() -> ReflectorConfig {
+        ReflectorConfig {
+            sprite: "atirador".to_string(),
+            position: Vector::new(850.0, 433.5),
+            velocity: Vector::new(-80.0, 0.0),
+            score: 300,
+            health: 2,
+            facing: Vector::new(-1.0, 0.0),
+        }
+    }
+}
+
+pub fn create_reflector(world: &mut World, config: ReflectorConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: config.health,
+        armor: Some(config.facing),
+    };
+    let reflector = create_enemy(world, enemy_config);
+    if let Some(enemy) = world.write_storage::<Enemy>().get_mut(reflector) {
+        enemy.reflects = true;
+    }
+    reflector
+}
+
+pub fn get_reflected_velocity(velocity: Vector, facing: Vector) -> Vector {
+    let normal = facing.normalize();
+    velocity - normal * (2.0 * velocity.dot(normal))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DiverConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub score: i32,
+    pub health: i32,
+    pub hover_ms: u64,
+    pub dive_speed: f32,
+    pub dive_acceleration: f32,
+    pub max_dive_speed: Option<f32>,
+}
+
+impl Default for DiverConfig {
+    fn default() -> DiverConfig {
+        DiverConfig {
+            sprite: "andador".to_string(),
+            position: Vector::new(700.0, 150.0),
+            score: 250,
+            health: 1,
+            hover_ms: 1200,
+            dive_speed: 250.0,
+            dive_acceleration: 600.0,
+            max_dive_speed: Some(900.0),
+        }
+    }
+}
+
+pub fn create_diver(world: &mut World, config: DiverConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: Vector::ZERO,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: config.health,
+        armor: None,
+    };
+    let diver = create_enemy(world, enemy_config);
+    world
+        .write_storage::<Dive>()
+        .insert(
+            diver,
+            Dive {
+                hover: Duration::from_millis(config.hover_ms),
+                speed: config.dive_speed,
+                acceleration: config.dive_acceleration,
+                max_speed: config.max_dive_speed,
+                target: None,
+            },
+        )
+        .unwrap();
+    diver
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BossHealerConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+    pub health: i32,
+    pub heal_amount: i32,
+    pub speed: f32,
+}
+
+impl Default for BossHealerConfig {
+    fn default() -> BossHealerConfig {
+        BossHealerConfig {
+            sprite: "andador".to_string(),
+            position: Vector::new(-50.0, 432.0),
+            velocity: Vector::new(100.0, 0.0),
+            score: 200,
+            health: 2,
+            heal_amount: 1,
+            speed: 100.0,
+        }
+    }
+}
+
+pub fn create_boss_healer(world: &mut World, config: BossHealerConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: config.health,
+        armor: None,
+    };
+    let healer = create_enemy(world, enemy_config);
+    world
+        .write_storage::<BossHealer>()
+        .insert(
+            healer,
+            BossHealer {
+                amount: config.heal_amount,
+                speed: config.speed,
+            },
+        )
+        .unwrap();
+    healer
+}
+
+pub fn heal_boss(boss: &mut Boss, amount: i32) {
+    boss.lives = (boss.lives + amount.max(0)).min(boss.maximum_lives);
+}
+
+pub fn get_dive_velocity(position: Vector, target: Vector, speed: f32) -> Vector {
+    let direction = target - position;
+    if direction.len2() == 0.0 {
+        return Vector::ZERO;
+    }
+    direction.normalize() * speed
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TurtleConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+    pub up_ms: u64,
+    pub down_ms: u64,
+}
+
+impl Default for TurtleConfig {
+    fn default() -> TurtleConfig {
+        TurtleConfig {
+            sprite: "andador".to_string(),
+            position: Vector::new(850.0, 432.0),
+            velocity: Vector::new(-90.0, 0.0),
+            score: 200,
+            up_ms: 1500,
+            down_ms: 1500,
+        }
+    }
+}
+
+pub fn create_turtle(world: &mut World, config: TurtleConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: 1,
+        armor: None,
+    };
+    let turtle = create_enemy(world, enemy_config);
+    world
+        .write_storage::<SelfShield>()
+        .insert(
+            turtle,
+            SelfShield {
+                up: Duration::from_millis(config.up_ms),
+                down: Duration::from_millis(config.down_ms),
+                timer: Duration::from_millis(0),
+                active: false,
+            },
+        )
+        .unwrap();
+    turtle
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BenefactorConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+}
+
+impl Default for BenefactorConfig {
+    fn default() -> BenefactorConfig {
+        BenefactorConfig {
+            sprite: "andador".to_string(),
+            position: Vector::new(850.0, 432.0),
+            velocity: Vector::new(-110.0, 0.0),
+            score: 150,
+        }
+    }
+}
+
+pub fn create_benefactor(world: &mut World, config: BenefactorConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: 1,
+        armor: None,
+    };
+    let benefactor = create_enemy(world, enemy_config);
+    world
+        .write_storage::<ShieldDonor>()
+        .insert(benefactor, ShieldDonor)
+        .unwrap();
+    benefactor
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RippleLineConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+    pub count: u32,
+    pub spacing: f32,
+    pub ripple_delay_ms: u64,
+    pub emitter: EmitterConfig,
+}
+
+impl Default for RippleLineConfig {
+    fn default() -> RippleLineConfig {
+        RippleLineConfig {
+            sprite: "alma".to_string(),
+            position: Vector::new(850.0, 150.0),
+            velocity: Vector::new(-60.0, 0.0),
+            score: 100,
+            count: 5,
+            spacing: 60.0,
+            ripple_delay_ms: 150,
+            emitter: EmitterConfig {
+                pattern: EmitterPattern::Aimed,
+                projectile_sprite: "tiro".to_string(),
+                projectile_speed: 250.0,
+                interval_ms: 2000,
+            },
+        }
+    }
+}
+
+pub fn create_ripple_line(world: &mut World, config: RippleLineConfig) -> Vec<Entity> {
+    (0..config.count)
+        .map(|index| {
+            let enemy_config = EnemyConfig {
+                sprite: config.sprite.clone(),
+                position: config.position + Vector::new(index as f32 * config.spacing, 0.0),
+                velocity: config.velocity,
+                score: config.score,
+                shooter_config: None,
+                emitter_config: None,
+                despawn_ms: ENEMY_DESPAWN_MS,
+                health: 1,
+                armor: None,
+            };
+            let enemy = create_enemy(world, enemy_config);
+            let mut emitter = create_emitter(config.emitter.clone());
+            emitter.delay = get_ripple_delay(index, config.ripple_delay_ms);
+            world
+                .write_storage::<Emitter>()
+                .insert(enemy, emitter)
+                .unwrap();
+            enemy
+        })
+        .collect()
+}
+
+pub fn get_ripple_delay(index: u32, ripple_delay_ms: u64) -> Duration {
+    Duration::from_millis(u64::from(index) * ripple_delay_ms)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MimicConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+    pub health: i32,
+    pub delay_ms: u64,
+}
+
+impl Default for MimicConfig {
+    fn default() -> MimicConfig {
+        MimicConfig {
+            sprite: "atirador".to_string(),
+            position: Vector::new(850.0, 433.5),
+            velocity: Vector::new(-70.0, 0.0),
+            score: 250,
+            health: 2,
+            delay_ms: 600,
+        }
+    }
+}
+
+pub fn create_mimic(world: &mut World, config: MimicConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: config.health,
+        armor: None,
+    };
+    let mimic = create_enemy(world, enemy_config);
+    world
+        .write_storage::<Mimic>()
+        .insert(
+            mimic,
+            Mimic {
+                delay: Duration::from_millis(config.delay_ms),
+                pending: Vec::new(),
+            },
+        )
+        .unwrap();
+    mimic
+}
+
+pub fn get_mimic_aim(position: Vector, hero_position: Option<Vector>) -> Vector {
+    match hero_position.map(|hero_position| hero_position - position) {
+        Some(direction) if direction.len2() > 0.0 => direction.normalize(),
+        _ => Vector::new(-1.0, 0.0),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ChargerConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+    pub health: i32,
+    pub cooldown_ms: u64,
+    pub charge_ms: u64,
+    pub pattern: EmitterPattern,
+    pub projectile_sprite: String,
+    pub projectile_speed: f32,
+}
+
+impl Default for ChargerConfig {
+    fn default() -> ChargerConfig {
+        ChargerConfig {
+            sprite: "atirador".to_string(),
+            position: Vector::new(850.0, 300.0),
+            velocity: Vector::new(-50.0, 0.0),
+            score: 500,
+            health: 6,
+            cooldown_ms: 3000,
+            charge_ms: 1500,
+            pattern: EmitterPattern::Ring(16),
+            projectile_sprite: "tiro".to_string(),
+            projectile_speed: 200.0,
+        }
+    }
+}
+
+pub fn create_charger(world: &mut World, config: ChargerConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: config.health,
+        armor: None,
+    };
+    let charger = create_enemy(world, enemy_config);
+    world
+        .write_storage::<ChargeAttack>()
+        .insert(
+            charger,
+            ChargeAttack {
+                cooldown_ms: config.cooldown_ms,
+                charge_ms: config.charge_ms,
+                elapsed_ms: 0,
+                charging: false,
+                pattern: config.pattern,
+                projectile_sprite: config.projectile_sprite,
+                projectile_speed: config.projectile_speed,
+                saved_velocity: None,
+            },
+        )
+        .unwrap();
+    charger
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DrainerConfig {
+    pub sprite: String,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub score: i32,
+    pub health: i32,
+    pub cooldown_ms: u64,
+}
+
+impl Default for DrainerConfig {
+    fn default() -> DrainerConfig {
+        DrainerConfig {
+            sprite: "alma".to_string(),
+            position: Vector::new(850.0, 433.5),
+            velocity: Vector::new(-90.0, 0.0),
+            score: 150,
+            health: 1,
+            cooldown_ms: 1000,
+        }
+    }
+}
+
+pub fn create_drainer(world: &mut World, config: DrainerConfig) -> Entity {
+    let enemy_config = EnemyConfig {
+        sprite: config.sprite,
+        position: config.position,
+        velocity: config.velocity,
+        score: config.score,
+        shooter_config: None,
+        emitter_config: None,
+        despawn_ms: ENEMY_DESPAWN_MS,
+        health: config.health,
+        armor: None,
     };
-    create_enemy(world, config);
+    let drainer = create_enemy(world, enemy_config);
+    if let Some(enemy) = world.write_storage::<Enemy>().get_mut(drainer) {
+        enemy.weapon_drain = Some(Duration::from_millis(config.cooldown_ms));
+    }
+    drainer
+}
+
+pub fn advance_charge_attack(charge_attack: &mut ChargeAttack, elapsed_ms: u64) -> bool {
+    charge_attack.elapsed_ms += elapsed_ms;
+    if !charge_attack.charging {
+        if charge_attack.elapsed_ms >= charge_attack.cooldown_ms {
+            charge_attack.charging = true;
+            charge_attack.elapsed_ms = 0;
+        }
+        false
+    } else if charge_attack.elapsed_ms >= charge_attack.charge_ms {
+        charge_attack.charging = false;
+        charge_attack.elapsed_ms = 0;
+        true
+    } else {
+        false
+    }
+}
+
+pub fn get_charge_progress(charge_attack: &ChargeAttack) -> Option<f32> {
+    if !charge_attack.charging || charge_attack.charge_ms == 0 {
+        return None;
+    }
+    Some((charge_attack.elapsed_ms as f32 / charge_attack.charge_ms as f32).min(1.0))
+}
+
+pub fn find_nearest_enemy(position: Vector, candidates: &[(Entity, Vector)]) -> Option<Entity> {
+    candidates
+        .iter()
+        .min_by(|(_, a), (_, b)| {
+            a.distance(position)
+                .partial_cmp(&b.distance(position))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|&(e, _)| e)
+}
+
+pub fn is_self_shielded(self_shield: Option<&SelfShield>) -> bool {
+    self_shield.is_some_and(|self_shield| self_shield.active)
+}
+
+pub fn is_armor_blocked(enemy: &Enemy, approach: Vector) -> bool {
+    match enemy.armor {
+        Some(facing) => facing.dot(approach) < 0.0,
+        None => false,
+    }
+}
+
+pub fn get_mirror_target_x(hero_x: f32, inverted: bool, field_width: f32) -> f32 {
+    if inverted {
+        field_width - hero_x
+    } else {
+        hero_x
+    }
+}
+
+pub fn is_shielded(e: Entity, position: Vector, shielders: &[(Entity, Vector, f32)]) -> bool {
+    shielders
+        .iter()
+        .any(|&(shielder, shielder_position, radius)| {
+            shielder != e && shielder_position.distance(position) <= radius
+        })
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -132,7 +1082,44 @@ pub struct BossConfig {
     pub position: Vector,
     pub lives: i32,
     pub normal_lives: i32,
+    pub phase_heal_fraction: f32,
+    pub phase_transition_invuln_ms: u64,
     pub shooter_config: ShooterConfig,
+    pub emitter_config: Option<EmitterConfig>,
+    pub time_bonus: Option<BossTimeBonusConfig>,
+    pub parts: Vec<BossPartConfig>,
+    pub final_phase_tint: Option<[f32; 4]>,
+    pub final_phase_shake: bool,
+    pub boss_intro_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BossPartConfig {
+    pub sprite: String,
+    pub offset: Vector,
+    pub health: i32,
+    pub score: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct BossTimeBonusConfig {
+    pub max_bonus: i32,
+    pub decay_per_second: f32,
+}
+
+impl Default for BossTimeBonusConfig {
+    fn default() -> BossTimeBonusConfig {
+        BossTimeBonusConfig {
+            max_bonus: 1000,
+            decay_per_second: 20.0,
+        }
+    }
+}
+
+pub fn get_boss_time_bonus(config: &BossTimeBonusConfig, time_to_kill: Duration) -> i32 {
+    let decay = config.decay_per_second * time_to_kill.as_secs_f32();
+    (config.max_bonus as f32 - decay).round().max(0.0) as i32
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -140,6 +1127,8 @@ pub struct ShooterConfig {
     pub projectile_sprite: String,
     pub maximum_projectiles: i32,
     pub projectile_coefficient: (f32, f32),
+    #[serde(default)]
+    pub cooldown_ms: u64,
 }
 
 impl Default for BossConfig {
@@ -150,21 +1139,39 @@ impl Default for BossConfig {
             position: Vector::new(748.5, 428.0),
             lives: 10,
             normal_lives: 5,
+            phase_heal_fraction: 0.0,
+            phase_transition_invuln_ms: 0,
             shooter_config: ShooterConfig {
                 projectile_sprite: "tiro".to_string(),
                 maximum_projectiles: 2,
                 projectile_coefficient: (0.075, -0.05),
+                cooldown_ms: 0,
             },
+            emitter_config: None,
+            time_bonus: None,
+            parts: Vec::new(),
+            final_phase_tint: None,
+            final_phase_shake: false,
+            boss_intro_ms: 0,
         }
     }
 }
 
 pub fn create_boss(world: &mut World, config: BossConfig) {
-    world
+    let spawned_at = world.read_resource::<GameMetrics>().elapsed;
+    let mut builder = world
         .create_entity()
         .with(Boss {
             lives: config.lives,
+            maximum_lives: config.lives,
             normal_lives: config.normal_lives,
+            phase_heal_fraction: config.phase_heal_fraction,
+            enraged: false,
+            phase_transition_invuln: Duration::from_millis(config.phase_transition_invuln_ms),
+            invulnerable: Duration::from_millis(0),
+            spawned_at,
+            time_bonus: config.time_bonus,
+            parts_alive: config.parts.len() as u32,
         })
         .with(Position {
             position: config.position,
@@ -173,7 +1180,13 @@ pub fn create_boss(world: &mut World, config: BossConfig) {
             sprite: config.sprite.clone(),
             bounding_box: None,
         })
-        .with(Enemy { score: 300 })
+        .with(Enemy {
+            score: 300,
+            despawn_ms: 0,
+            armor: None,
+            reflects: false,
+            weapon_drain: None,
+        })
         .with(ChangeSprite {
             new_sprite: config.angry_sprite.clone(),
             do_change: false,
@@ -183,8 +1196,85 @@ pub fn create_boss(world: &mut World, config: BossConfig) {
             maximum_fireballs: config.shooter_config.maximum_projectiles,
             fireball_amount: 0,
             coefficient: config.shooter_config.projectile_coefficient,
-        })
-        .build();
+            cooldown: Duration::from_millis(config.shooter_config.cooldown_ms),
+            timer: Duration::from_millis(0),
+        });
+    if let Some(emitter_config) = config.emitter_config {
+        builder = builder.with(create_emitter(emitter_config));
+    }
+    let boss = builder.build();
+
+    for part_config in config.parts {
+        let enemy_config = EnemyConfig {
+            sprite: part_config.sprite,
+            position: config.position + part_config.offset,
+            velocity: Vector::ZERO,
+            score: part_config.score,
+            shooter_config: None,
+            emitter_config: None,
+            despawn_ms: ENEMY_DESPAWN_MS,
+            health: part_config.health,
+            armor: None,
+        };
+        let part = create_enemy(world, enemy_config);
+        world
+            .write_storage::<FollowParent>()
+            .insert(
+                part,
+                FollowParent {
+                    parent: boss,
+                    offset: part_config.offset,
+                    orbit_speed: 0.0,
+                },
+            )
+            .unwrap();
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub enum EmitterPattern {
+    Ring(u32),
+    Arc(u32, f32),
+    Aimed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmitterConfig {
+    pub pattern: EmitterPattern,
+    pub projectile_sprite: String,
+    pub projectile_speed: f32,
+    pub interval_ms: u64,
+}
+
+pub fn create_emitter(config: EmitterConfig) -> Emitter {
+    Emitter {
+        pattern: config.pattern,
+        projectile_sprite: config.projectile_sprite,
+        projectile_speed: config.projectile_speed,
+        interval: Duration::from_millis(config.interval_ms),
+        timer: Duration::from_millis(0),
+        delay: Duration::from_millis(0),
+    }
+}
+
+pub fn get_emitter_directions(pattern: EmitterPattern, aim: Vector) -> Vec<Vector> {
+    match pattern {
+        EmitterPattern::Ring(amount) => (0..amount)
+            .map(|i| Vector::from_angle(360.0 * i as f32 / amount as f32))
+            .collect(),
+        EmitterPattern::Arc(amount, spread) => {
+            if amount <= 1 {
+                vec![Vector::from_angle(aim.angle())]
+            } else {
+                let first = aim.angle() - spread / 2.0;
+                let step = spread / (amount - 1) as f32;
+                (0..amount)
+                    .map(|i| Vector::from_angle(first + step * i as f32))
+                    .collect()
+            }
+        }
+        EmitterPattern::Aimed => vec![Vector::from_angle(aim.angle())],
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -192,6 +1282,65 @@ pub struct FireballConfig {
     pub sprite: String,
     pub position: Vector,
     pub velocity: Vector,
+    #[serde(default)]
+    pub damage_over_time: Option<DamageOverTimeConfig>,
+    #[serde(default)]
+    pub contact_damage: Option<ContactDamage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct ContactDamage {
+    pub per_tick: i32,
+    pub lifetime_ms: u64,
+}
+
+impl Default for ContactDamage {
+    fn default() -> Self {
+        ContactDamage {
+            per_tick: 1,
+            lifetime_ms: 300,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum DotStacking {
+    Refresh,
+    Stack,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct DamageOverTimeConfig {
+    pub per_tick: i32,
+    pub interval_ms: u64,
+    pub duration_ms: u64,
+    pub stacking: DotStacking,
+}
+
+pub fn create_damage_over_time(
+    source_id: Option<Index>,
+    config: DamageOverTimeConfig,
+) -> DamageOverTime {
+    DamageOverTime {
+        source_id,
+        per_tick: config.per_tick,
+        remaining_ms: config.duration_ms,
+        interval_ms: config.interval_ms,
+        timer_ms: 0,
+    }
+}
+
+pub fn stack_damage_over_time(damage_over_time: &mut DamageOverTime, config: DamageOverTimeConfig) {
+    match config.stacking {
+        DotStacking::Refresh => {
+            damage_over_time.remaining_ms = config.duration_ms;
+        }
+        DotStacking::Stack => {
+            damage_over_time.per_tick += config.per_tick;
+            damage_over_time.remaining_ms = damage_over_time.remaining_ms.max(config.duration_ms);
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -211,23 +1360,46 @@ impl Default for FireballShowerConfig {
 }
 
 pub fn create_fireball_shower(world: &mut World, config: FireballShowerConfig) {
-    let mut rng = thread_rng();
-    let x_init: i32 = rng.gen_range(0, 100);
-    let x_end: i32 = rng.gen_range(810, 900);
-    let step: usize = rng.gen_range(90, 120);
-    for x in (x_init..x_end).step_by(step) {
-        let fireball_config = FireballConfig {
-            sprite: config.sprite.clone(),
-            position: Vector::new(x as f32, config.y_velocity),
-            velocity: Vector::new(0.0, 250.0 + rng.gen_range(-10.0, 10.0)),
-        };
-        create_fireball(world.create_entity(), None, fireball_config);
+    let fireball_configs: Vec<FireballConfig> = {
+        let rng = &mut world.write_resource::<RandomGenerator>().rng;
+        let x_init: i32 = rng.gen_range(0, 100);
+        let x_end: i32 = rng.gen_range(810, 900);
+        let step: usize = rng.gen_range(90, 120);
+        (x_init..x_end)
+            .step_by(step)
+            .map(|x| FireballConfig {
+                sprite: config.sprite.clone(),
+                position: Vector::new(x as f32, config.y_velocity),
+                velocity: Vector::new(0.0, 250.0 + rng.gen_range(-10.0, 10.0)),
+                damage_over_time: None,
+                contact_damage: None,
+            })
+            .collect()
+    };
+    for fireball_config in fireball_configs {
+        create_fireball(world.create_entity(), None, Faction::Enemy, fireball_config);
     }
 }
 
-pub fn create_fireball<T: Builder>(builder: T, owner_id: Option<Index>, config: FireballConfig) {
+pub fn create_fireball<T: Builder>(
+    builder: T,
+    owner_id: Option<Index>,
+    faction: Faction,
+    config: FireballConfig,
+) {
+    let builder = match config.contact_damage {
+        Some(contact_damage) => builder.with(Lifetime {
+            remaining: Duration::from_millis(contact_damage.lifetime_ms),
+        }),
+        None => builder,
+    };
     builder
-        .with(Fireball { owner_id })
+        .with(Fireball {
+            owner_id,
+            faction,
+            damage_over_time: config.damage_over_time,
+            contact_damage: config.contact_damage,
+        })
         .with(CalculateOutOfBounds)
         .with(Render {
             sprite: config.sprite,
@@ -241,3 +1413,23 @@ pub fn create_fireball<T: Builder>(builder: T, owner_id: Option<Index>, config:
         })
         .build();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enemy_config_without_despawn_or_health_uses_the_defaults() {
+        let json = r#"{
+            "sprite": "andador",
+            "position": { "x": 850.0, "y": 432.0 },
+            "velocity": { "x": -125.0, "y": 0.0 },
+            "score": 100,
+            "shooter_config": null
+        }"#;
+        let config: EnemyConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.despawn_ms, ENEMY_DESPAWN_MS);
+        assert_eq!(config.health, 1);
+        assert!(config.emitter_config.is_none());
+    }
+}
@@ -1,11 +1,22 @@
 use crate::{
-    enemy::{EnemyType, FireballShowerConfig},
+    component::{
+        Acceleration, BombDrop, DashEntrance, DropTable, Emitter, Enemy, EntitySounds, Health,
+        Shooter, Velocity,
+    },
+    enemy::{
+        AccelerationConfig, ArmoredConfig, BenefactorConfig, BossHealerConfig, ChargerConfig,
+        DashEntranceConfig, DiverConfig, DrainerConfig, DroneHostConfig, EnemySoundsConfig,
+        EnemyType, FireballShowerConfig, MimicConfig, MirrorConfig, ReflectorConfig,
+        RippleLineConfig, ShielderConfig, TurtleConfig,
+    },
     healing::HealingConfig,
+    power_up::{BombDropConfig, DropTableConfig},
+    resources::{GameMetrics, RandomGenerator, SoundPriority, SoundQueue},
 };
-use quicksilver::Result;
-use rand::{thread_rng, Rng};
+use quicksilver::{geom::Vector, Result};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use specs::World;
+use specs::{Entity, World};
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub enum FactoryType {
@@ -13,12 +24,107 @@ pub enum FactoryType {
     Random,
 }
 
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum DifficultyPreset {
+    Easy,
+    Normal,
+    Hard,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpawnEntry {
+    pub weight: u32,
+    pub enemy_type: EnemyType,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpawnTableConfig {
+    pub difficulty: DifficultyPreset,
+    pub entries: Vec<SpawnEntry>,
+}
+
+pub fn get_difficulty_preset(difficulty: f32) -> DifficultyPreset {
+    if difficulty < 1.0 {
+        DifficultyPreset::Easy
+    } else if difficulty > 1.0 {
+        DifficultyPreset::Hard
+    } else {
+        DifficultyPreset::Normal
+    }
+}
+
+pub fn roll_spawn_table<R: Rng>(entries: &[SpawnEntry], rng: &mut R) -> Option<EnemyType> {
+    let total_weight: u32 = entries.iter().map(|entry| entry.weight).sum();
+    if total_weight == 0 {
+        return None;
+    }
+    let mut roll = rng.gen_range(0, total_weight);
+    for entry in entries {
+        if roll < entry.weight {
+            return Some(entry.enemy_type);
+        }
+        roll -= entry.weight;
+    }
+    None
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct SpawnPacingConfig {
+    pub min_cycle: u64,
+    pub max_cycle: u64,
+    pub window_ms: u64,
+    pub target_kills: usize,
+    pub step: f32,
+}
+
+impl Default for SpawnPacingConfig {
+    fn default() -> SpawnPacingConfig {
+        SpawnPacingConfig {
+            min_cycle: 120,
+            max_cycle: 300,
+            window_ms: 10000,
+            target_kills: 3,
+            step: 0.5,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct EntityFactoryConfig {
     pub factory_type: FactoryType,
     pub enemy_types: Vec<EnemyType>,
+    pub spawn_tables: Vec<SpawnTableConfig>,
     pub healing_interval: Option<i32>,
+    pub drop_tables: Vec<DropTableConfig>,
+    pub bomb_drops: Vec<BombDropConfig>,
+    pub enemy_sounds: Vec<EnemySoundsConfig>,
+    pub accelerations: Vec<AccelerationConfig>,
+    pub dash_entrances: Vec<DashEntranceConfig>,
+    pub shielder_config: ShielderConfig,
+    pub mirror_config: MirrorConfig,
+    pub armored_config: ArmoredConfig,
+    pub turtle_config: TurtleConfig,
+    pub benefactor_config: BenefactorConfig,
+    pub ripple_line_config: RippleLineConfig,
+    pub mimic_config: MimicConfig,
+    pub charger_config: ChargerConfig,
+    pub drainer_config: DrainerConfig,
+    pub reflector_config: ReflectorConfig,
+    pub drone_host_config: DroneHostConfig,
+    pub diver_config: DiverConfig,
+    pub boss_healer_config: BossHealerConfig,
+    pub speed_variance: f32,
+    pub timing_variance: f32,
+    pub despawn_ms: u64,
+    pub fire_grace_ms: u64,
+    pub spawn_during_boss: bool,
+    pub boss_spawn_cycle_factor: u64,
+    pub enemy_separation: bool,
+    pub separation_strength: f32,
+    pub spawn_pacing: Option<SpawnPacingConfig>,
+    pub health_curve: Vec<(u64, f32)>,
 }
 
 impl Default for EntityFactoryConfig {
@@ -26,7 +132,36 @@ impl Default for EntityFactoryConfig {
         EntityFactoryConfig {
             factory_type: FactoryType::Fixed,
             enemy_types: vec![EnemyType::Walker, EnemyType::Shooter],
+            spawn_tables: Vec::new(),
             healing_interval: Some(3),
+            drop_tables: Vec::new(),
+            bomb_drops: Vec::new(),
+            enemy_sounds: Vec::new(),
+            accelerations: Vec::new(),
+            dash_entrances: Vec::new(),
+            shielder_config: ShielderConfig::default(),
+            mirror_config: MirrorConfig::default(),
+            armored_config: ArmoredConfig::default(),
+            turtle_config: TurtleConfig::default(),
+            benefactor_config: BenefactorConfig::default(),
+            ripple_line_config: RippleLineConfig::default(),
+            mimic_config: MimicConfig::default(),
+            charger_config: ChargerConfig::default(),
+            drainer_config: DrainerConfig::default(),
+            reflector_config: ReflectorConfig::default(),
+            drone_host_config: DroneHostConfig::default(),
+            diver_config: DiverConfig::default(),
+            boss_healer_config: BossHealerConfig::default(),
+            speed_variance: 0.0,
+            timing_variance: 0.0,
+            despawn_ms: 0,
+            fire_grace_ms: 0,
+            spawn_during_boss: false,
+            boss_spawn_cycle_factor: 2,
+            enemy_separation: false,
+            separation_strength: 60.0,
+            spawn_pacing: None,
+            health_curve: Vec::new(),
         }
     }
 }
@@ -34,37 +169,492 @@ impl Default for EntityFactoryConfig {
 pub struct EntityFactory {
     factory_type: FactoryType,
     enemy_types: Vec<EnemyType>,
+    spawn_tables: Vec<SpawnTableConfig>,
+    spawn_table: Vec<SpawnEntry>,
     healing_interval: Option<i32>,
+    drop_tables: Vec<DropTableConfig>,
+    bomb_drops: Vec<BombDropConfig>,
+    enemy_sounds: Vec<EnemySoundsConfig>,
+    accelerations: Vec<AccelerationConfig>,
+    dash_entrances: Vec<DashEntranceConfig>,
+    shielder_config: ShielderConfig,
+    mirror_config: MirrorConfig,
+    armored_config: ArmoredConfig,
+    turtle_config: TurtleConfig,
+    benefactor_config: BenefactorConfig,
+    ripple_line_config: RippleLineConfig,
+    mimic_config: MimicConfig,
+    charger_config: ChargerConfig,
+    drainer_config: DrainerConfig,
+    reflector_config: ReflectorConfig,
+    drone_host_config: DroneHostConfig,
+    diver_config: DiverConfig,
+    boss_healer_config: BossHealerConfig,
+    speed_variance: f32,
+    timing_variance: f32,
+    despawn_ms: u64,
+    health_curve: Vec<(u64, f32)>,
     counter: i32,
 }
 
 impl EntityFactory {
+    #[allow(clippy::result_large_err)]
     pub fn new(config: EntityFactoryConfig) -> Result<Self> {
+        let mut health_curve = config.health_curve;
+        health_curve.sort_by_key(|&(secs, _)| secs);
         Ok(EntityFactory {
             factory_type: config.factory_type,
             enemy_types: config.enemy_types,
+            spawn_tables: config.spawn_tables,
+            spawn_table: Vec::new(),
             healing_interval: config.healing_interval,
+            drop_tables: config.drop_tables,
+            bomb_drops: config.bomb_drops,
+            enemy_sounds: config.enemy_sounds,
+            accelerations: config.accelerations,
+            dash_entrances: config.dash_entrances,
+            shielder_config: config.shielder_config,
+            mirror_config: config.mirror_config,
+            armored_config: config.armored_config,
+            turtle_config: config.turtle_config,
+            benefactor_config: config.benefactor_config,
+            ripple_line_config: config.ripple_line_config,
+            mimic_config: config.mimic_config,
+            charger_config: config.charger_config,
+            drainer_config: config.drainer_config,
+            reflector_config: config.reflector_config,
+            drone_host_config: config.drone_host_config,
+            diver_config: config.diver_config,
+            boss_healer_config: config.boss_healer_config,
+            speed_variance: config.speed_variance,
+            timing_variance: config.timing_variance,
+            despawn_ms: config.despawn_ms,
+            health_curve,
             counter: 0,
         })
     }
 
+    pub fn select_spawn_table(&mut self, difficulty: f32) {
+        let preset = get_difficulty_preset(difficulty);
+        self.spawn_table = self
+            .spawn_tables
+            .iter()
+            .find(|spawn_table| spawn_table.difficulty == preset)
+            .map(|spawn_table| spawn_table.entries.clone())
+            .unwrap_or_default();
+    }
+
+    #[allow(clippy::result_large_err)]
     pub fn create_entity(&mut self, world: &mut World) -> Result<()> {
-        let pos = match self.factory_type {
-            FactoryType::Fixed => self.counter as usize % self.enemy_types.len(),
-            FactoryType::Random => thread_rng().gen_range(0, self.enemy_types.len()),
+        let rolled_type = if self.spawn_table.is_empty() {
+            None
+        } else {
+            let mut rng = world.write_resource::<RandomGenerator>();
+            roll_spawn_table(&self.spawn_table, &mut rng.rng)
         };
-        match self.enemy_types[pos] {
-            EnemyType::Walker => crate::enemy::create_walker(world),
-            EnemyType::Shooter => crate::enemy::create_shooter(world),
-            EnemyType::Flyer => crate::enemy::create_flyer(world),
+        let enemy_type = match rolled_type {
+            Some(enemy_type) => enemy_type,
+            None => {
+                let pos = match self.factory_type {
+                    FactoryType::Fixed => self.counter as usize % self.enemy_types.len(),
+                    FactoryType::Random => world
+                        .write_resource::<RandomGenerator>()
+                        .rng
+                        .gen_range(0, self.enemy_types.len()),
+                };
+                self.enemy_types[pos]
+            }
+        };
+        let enemies = match enemy_type {
+            EnemyType::Walker => vec![crate::enemy::create_walker(world)],
+            EnemyType::Shooter => vec![crate::enemy::create_shooter(world)],
+            EnemyType::Flyer => vec![crate::enemy::create_flyer(world)],
             EnemyType::FireballShower => {
-                crate::enemy::create_fireball_shower(world, FireballShowerConfig::default())
+                crate::enemy::create_fireball_shower(world, FireballShowerConfig::default());
+                Vec::new()
+            }
+            EnemyType::Shielder => vec![crate::enemy::create_shielder(
+                world,
+                self.shielder_config.clone(),
+            )],
+            EnemyType::Mirror => vec![crate::enemy::create_mirror(
+                world,
+                self.mirror_config.clone(),
+            )],
+            EnemyType::Armored => vec![crate::enemy::create_armored(
+                world,
+                self.armored_config.clone(),
+            )],
+            EnemyType::Turtle => vec![crate::enemy::create_turtle(
+                world,
+                self.turtle_config.clone(),
+            )],
+            EnemyType::Benefactor => vec![crate::enemy::create_benefactor(
+                world,
+                self.benefactor_config.clone(),
+            )],
+            EnemyType::Mimic => vec![crate::enemy::create_mimic(world, self.mimic_config.clone())],
+            EnemyType::Charger => vec![crate::enemy::create_charger(
+                world,
+                self.charger_config.clone(),
+            )],
+            EnemyType::Drainer => vec![crate::enemy::create_drainer(
+                world,
+                self.drainer_config.clone(),
+            )],
+            EnemyType::Reflector => vec![crate::enemy::create_reflector(
+                world,
+                self.reflector_config.clone(),
+            )],
+            EnemyType::DroneHost => vec![crate::enemy::create_drone_host(
+                world,
+                self.drone_host_config.clone(),
+            )],
+            EnemyType::Diver => vec![crate::enemy::create_diver(world, self.diver_config.clone())],
+            EnemyType::BossHealer => vec![crate::enemy::create_boss_healer(
+                world,
+                self.boss_healer_config.clone(),
+            )],
+            EnemyType::RippleLine => {
+                crate::enemy::create_ripple_line(world, self.ripple_line_config.clone())
             }
         };
+        for &enemy in enemies.iter() {
+            self.setup_enemy(world, enemy, enemy_type);
+        }
+        let enemy_sounds = self
+            .enemy_sounds
+            .iter()
+            .find(|enemy_sounds| enemy_sounds.enemy_type == enemy_type)
+            .filter(|_| !enemies.is_empty());
+        if let Some(enemy_sounds) = enemy_sounds {
+            world
+                .write_resource::<SoundQueue>()
+                .push(&enemy_sounds.sounds.spawn, SoundPriority::Low);
+        }
         if self.healing_interval.is_some() && self.counter % self.healing_interval.unwrap() == 0 {
-            crate::healing::create_healing_potion(world, HealingConfig::default());
+            let x = world
+                .write_resource::<RandomGenerator>()
+                .rng
+                .gen_range(50.0, 700.0);
+            let config = HealingConfig {
+                position: Vector::new(x, -100.0),
+                ..HealingConfig::default()
+            };
+            crate::healing::create_healing_potion(world, config);
         }
         self.counter += 1;
         Ok(())
     }
+
+    fn setup_enemy(&self, world: &mut World, enemy: Entity, enemy_type: EnemyType) {
+        if let Some(enemy) = world.write_storage::<Enemy>().get_mut(enemy) {
+            enemy.despawn_ms = self.despawn_ms;
+        }
+        self.apply_variation(world, enemy);
+        self.apply_health_scaling(world, enemy);
+        let drop_table = self
+            .drop_tables
+            .iter()
+            .find(|drop_table| drop_table.enemy_type == enemy_type);
+        if let Some(drop_table) = drop_table {
+            world
+                .write_storage::<DropTable>()
+                .insert(
+                    enemy,
+                    DropTable {
+                        entries: drop_table.entries.clone(),
+                    },
+                )
+                .unwrap();
+        }
+        let bomb_drop = self
+            .bomb_drops
+            .iter()
+            .find(|bomb_drop| bomb_drop.enemy_type == enemy_type);
+        if let Some(bomb_drop) = bomb_drop {
+            world
+                .write_storage::<BombDrop>()
+                .insert(
+                    enemy,
+                    BombDrop {
+                        sprite: bomb_drop.sprite.clone(),
+                        velocity: bomb_drop.velocity,
+                    },
+                )
+                .unwrap();
+        }
+        let enemy_sounds = self
+            .enemy_sounds
+            .iter()
+            .find(|enemy_sounds| enemy_sounds.enemy_type == enemy_type);
+        if let Some(enemy_sounds) = enemy_sounds {
+            world
+                .write_storage::<EntitySounds>()
+                .insert(enemy, enemy_sounds.sounds.clone())
+                .unwrap();
+        }
+        let acceleration = self
+            .accelerations
+            .iter()
+            .find(|acceleration| acceleration.enemy_type == enemy_type);
+        if let Some(acceleration) = acceleration {
+            world
+                .write_storage::<Acceleration>()
+                .insert(
+                    enemy,
+                    Acceleration {
+                        acceleration: acceleration.acceleration,
+                        max_speed: acceleration.max_speed,
+                    },
+                )
+                .unwrap();
+        }
+        let dash_entrance = self
+            .dash_entrances
+            .iter()
+            .find(|dash_entrance| dash_entrance.enemy_type == enemy_type);
+        let velocity = world
+            .read_storage::<Velocity>()
+            .get(enemy)
+            .map(|vel| vel.velocity)
+            .filter(|velocity| velocity.len() > 0.0);
+        if let (Some(dash_entrance), Some(velocity)) = (dash_entrance, velocity) {
+            world
+                .write_storage::<DashEntrance>()
+                .insert(
+                    enemy,
+                    crate::enemy::create_dash_entrance(dash_entrance, velocity),
+                )
+                .unwrap();
+        }
+    }
+
+    fn apply_health_scaling(&self, world: &mut World, enemy: Entity) {
+        if self.health_curve.is_empty() {
+            return;
+        }
+        let elapsed = world.read_resource::<GameMetrics>().elapsed;
+        let multiplier = get_health_multiplier(&self.health_curve, elapsed.as_secs_f32());
+        if let Some(health) = world.write_storage::<Health>().get_mut(enemy) {
+            health.maximum = ((health.maximum as f32 * multiplier).round() as i32).max(1);
+            health.current = health.maximum;
+        }
+    }
+
+    fn apply_variation(&self, world: &mut World, enemy: Entity) {
+        let mut rng = world.write_resource::<RandomGenerator>();
+        let speed_factor = get_variation_factor(&mut rng.rng, self.speed_variance);
+        let timing_factor = get_variation_factor(&mut rng.rng, self.timing_variance);
+
+        if let Some(velocity) = world.write_storage::<Velocity>().get_mut(enemy) {
+            velocity.velocity *= speed_factor;
+        }
+        if let Some(shooter) = world.write_storage::<Shooter>().get_mut(enemy) {
+            shooter.cooldown = shooter.cooldown.mul_f32(timing_factor);
+        }
+        if let Some(emitter) = world.write_storage::<Emitter>().get_mut(enemy) {
+            emitter.interval = emitter.interval.mul_f32(timing_factor);
+        }
+    }
+}
+
+pub fn get_health_multiplier(curve: &[(u64, f32)], elapsed_secs: f32) -> f32 {
+    let mut previous: Option<(u64, f32)> = None;
+    for &(secs, multiplier) in curve {
+        if elapsed_secs < secs as f32 {
+            return match previous {
+                Some((previous_secs, previous_multiplier)) => {
+                    let t = (elapsed_secs - previous_secs as f32) / (secs - previous_secs) as f32;
+                    previous_multiplier + (multiplier - previous_multiplier) * t
+                }
+                None => multiplier,
+            };
+        }
+        previous = Some((secs, multiplier));
+    }
+    previous.map_or(1.0, |(_, multiplier)| multiplier)
+}
+
+fn get_variation_factor<R: Rng>(rng: &mut R, variance: f32) -> f32 {
+    if variance > 0.0 {
+        (1.0 + rng.gen_range(-variance, variance)).max(0.0)
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::Render,
+        enemy::{EnemyConfig, ShooterConfig},
+        power_up::DropEntry,
+        scene::{create_world, SceneConfig},
+    };
+    use quicksilver::geom::Vector;
+    use specs::Join;
+    use std::time::Duration;
+
+    fn create_varied_enemies(seed: u64) -> Vec<(f32, Duration)> {
+        let mut world = create_world(&SceneConfig {
+            seed: Some(seed),
+            ..SceneConfig::default()
+        });
+        let factory = EntityFactory::new(EntityFactoryConfig {
+            speed_variance: 0.2,
+            timing_variance: 0.5,
+            ..EntityFactoryConfig::default()
+        })
+        .unwrap();
+        (0..2)
+            .map(|_| {
+                let enemy = crate::enemy::create_enemy(
+                    &mut world,
+                    EnemyConfig {
+                        sprite: "atirador".to_string(),
+                        position: Vector::new(850, 433),
+                        velocity: Vector::new(-125, 0),
+                        score: 200,
+                        shooter_config: Some(ShooterConfig {
+                            projectile_sprite: "tiro".to_string(),
+                            maximum_projectiles: 2,
+                            projectile_coefficient: (0.175, 0.0),
+                            cooldown_ms: 1000,
+                        }),
+                        emitter_config: None,
+                        despawn_ms: 0,
+                        health: 1,
+                        armor: None,
+                    },
+                );
+                factory.apply_variation(&mut world, enemy);
+                let speed = world
+                    .read_storage::<Velocity>()
+                    .get(enemy)
+                    .unwrap()
+                    .velocity
+                    .x;
+                let cooldown = world.read_storage::<Shooter>().get(enemy).unwrap().cooldown;
+                (speed, cooldown)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn seeded_variation_differs_per_enemy_within_band() {
+        let enemies = create_varied_enemies(7);
+        assert_eq!(enemies, create_varied_enemies(7));
+        assert_ne!(enemies[0], enemies[1]);
+        for &(speed, cooldown) in enemies.iter() {
+            assert!((-150.0..=-100.0).contains(&speed));
+            assert!(cooldown >= Duration::from_millis(500));
+            assert!(cooldown <= Duration::from_millis(1500));
+        }
+    }
+
+    #[test]
+    fn ripple_line_members_get_the_shared_enemy_setup() {
+        let mut world = create_world(&SceneConfig::default());
+        let mut factory = EntityFactory::new(EntityFactoryConfig {
+            enemy_types: vec![EnemyType::RippleLine],
+            drop_tables: vec![DropTableConfig {
+                enemy_type: EnemyType::RippleLine,
+                entries: vec![DropEntry {
+                    weight: 1,
+                    power_up: None,
+                }],
+            }],
+            despawn_ms: 250,
+            ..EntityFactoryConfig::default()
+        })
+        .unwrap();
+
+        factory.create_entity(&mut world).unwrap();
+
+        let enemy = world.read_storage::<Enemy>();
+        let drop_table = world.read_storage::<DropTable>();
+        let members: Vec<u64> = (&enemy, &drop_table)
+            .join()
+            .map(|(enemy, _)| enemy.despawn_ms)
+            .collect();
+        assert_eq!(
+            members,
+            vec![250; RippleLineConfig::default().count as usize]
+        );
+    }
+
+    fn spawn_sprites(difficulty: f32) -> Vec<String> {
+        let mut world = create_world(&SceneConfig {
+            seed: Some(11),
+            ..SceneConfig::default()
+        });
+        let spawn_table = |difficulty, entries: &[(u32, EnemyType)]| SpawnTableConfig {
+            difficulty,
+            entries: entries
+                .iter()
+                .map(|&(weight, enemy_type)| SpawnEntry { weight, enemy_type })
+                .collect(),
+        };
+        let mut factory = EntityFactory::new(EntityFactoryConfig {
+            spawn_tables: vec![
+                spawn_table(
+                    DifficultyPreset::Normal,
+                    &[(3, EnemyType::Walker), (1, EnemyType::Shooter)],
+                ),
+                spawn_table(
+                    DifficultyPreset::Hard,
+                    &[(1, EnemyType::Walker), (3, EnemyType::Shooter)],
+                ),
+            ],
+            ..EntityFactoryConfig::default()
+        })
+        .unwrap();
+        factory.select_spawn_table(difficulty);
+        for _ in 0..40 {
+            factory.create_entity(&mut world).unwrap();
+        }
+        let enemy = world.read_storage::<Enemy>();
+        let render = world.read_storage::<Render>();
+        (&enemy, &render)
+            .join()
+            .map(|(_, render)| render.sprite.clone())
+            .collect()
+    }
+
+    #[test]
+    fn hard_difficulty_spawns_from_its_own_table() {
+        let count = |sprites: &[String], sprite: &str| {
+            sprites.iter().filter(|s| s.starts_with(sprite)).count()
+        };
+        let normal = spawn_sprites(1.0);
+        let hard = spawn_sprites(1.5);
+
+        assert_eq!(normal, spawn_sprites(1.0));
+        assert!(count(&normal, "andador") > count(&normal, "atirador"));
+        assert!(count(&hard, "atirador") > count(&hard, "andador"));
+    }
+
+    fn spawn_health(elapsed_secs: u64) -> i32 {
+        let mut world = create_world(&SceneConfig::default());
+        world.write_resource::<GameMetrics>().elapsed = Duration::from_secs(elapsed_secs);
+        let mut factory = EntityFactory::new(EntityFactoryConfig {
+            enemy_types: vec![EnemyType::Walker],
+            health_curve: vec![(60, 3.0), (0, 1.0)],
+            ..EntityFactoryConfig::default()
+        })
+        .unwrap();
+        factory.create_entity(&mut world).unwrap();
+        let enemy = world.read_storage::<Enemy>();
+        let health = world.read_storage::<Health>();
+        (&enemy, &health).join().next().unwrap().1.maximum
+    }
+
+    #[test]
+    fn enemies_spawned_later_start_with_more_health_along_the_sorted_curve() {
+        assert_eq!(spawn_health(10), 1);
+        assert_eq!(spawn_health(60), 3);
+        assert!(spawn_health(60) > spawn_health(10));
+    }
 }
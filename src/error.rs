@@ -0,0 +1,47 @@
+use quicksilver::Error as QuicksilverError;
+use std::{error, fmt};
+
+#[derive(Debug)]
+pub enum EngineError {
+    Asset(Box<QuicksilverError>),
+    Audio(Box<QuicksilverError>),
+    System(Box<QuicksilverError>),
+}
+
+impl EngineError {
+    pub fn asset(error: QuicksilverError) -> EngineError {
+        EngineError::Asset(Box::new(error))
+    }
+
+    pub fn audio(error: QuicksilverError) -> EngineError {
+        EngineError::Audio(Box::new(error))
+    }
+
+    pub fn system(error: QuicksilverError) -> EngineError {
+        EngineError::System(Box::new(error))
+    }
+}
+
+pub type EngineResult<T> = std::result::Result<T, EngineError>;
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EngineError::Asset(error) => write!(f, "asset error: {}", error),
+            EngineError::Audio(error) => write!(f, "audio error: {}", error),
+            EngineError::System(error) => write!(f, "system error: {}", error),
+        }
+    }
+}
+
+impl error::Error for EngineError {}
+
+impl From<EngineError> for QuicksilverError {
+    fn from(error: EngineError) -> QuicksilverError {
+        match error {
+            EngineError::Asset(error) | EngineError::Audio(error) | EngineError::System(error) => {
+                *error
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::scene::{Scene, SceneConfig};
 use log::Level;
-use quicksilver::prelude::*;
+use quicksilver::{graphics::View, prelude::*};
 use std::path::{Path, PathBuf};
 
 struct Game {
@@ -14,16 +14,36 @@ impl State for Game {
         })
     }
 
+    #[allow(clippy::result_large_err)]
     fn update(&mut self, window: &mut Window) -> Result<()> {
-        self.scene.execute(|s| s.update(window))
+        self.scene.execute(|s| {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                if let Some(min_size) = s.min_window_size() {
+                    let size = window.screen_size();
+                    let clamped = clamp_window_size(size, min_size);
+                    if clamped != size {
+                        window.set_size(clamped);
+                    }
+                }
+            }
+            let size = window.screen_size();
+            if s.resize(size) {
+                window.set_view(View::new(Rectangle::new_sized(size)));
+            }
+            s.update(window).map_err(Error::from)
+        })
     }
 
+    #[allow(clippy::result_large_err)]
     fn draw(&mut self, window: &mut Window) -> Result<()> {
-        self.scene.execute(|s| s.draw(window))
+        self.scene.execute(|s| s.draw(window).map_err(Error::from))
     }
 
+    #[allow(clippy::result_large_err)]
     fn event(&mut self, event: &Event, window: &mut Window) -> Result<()> {
-        self.scene.execute(|s| s.event(event, window))
+        self.scene
+            .execute(|s| s.event(event, window).map_err(Error::from))
     }
 }
 
@@ -37,6 +57,10 @@ pub fn run() {
     quicksilver::lifecycle::run::<Game>("Evil Alligator", Vector::new(800, 600), settings);
 }
 
+pub fn clamp_window_size(size: Vector, min_size: (u32, u32)) -> Vector {
+    Vector::new(size.x.max(min_size.0 as f32), size.y.max(min_size.1 as f32))
+}
+
 fn init_logger(level: Level) {
     #[cfg(not(target_arch = "wasm32"))]
     {
@@ -56,3 +80,21 @@ fn create_scene(path: impl AsRef<Path>) -> impl Future<Item = Scene, Error = Err
         Scene::new(cfg).unwrap()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_is_clamped_to_the_minimum_size() {
+        let min_size = (640, 480);
+        assert_eq!(
+            clamp_window_size(Vector::new(320, 200), min_size),
+            Vector::new(640, 480)
+        );
+        assert_eq!(
+            clamp_window_size(Vector::new(1024, 300), min_size),
+            Vector::new(1024, 480)
+        );
+    }
+}
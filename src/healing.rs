@@ -1,6 +1,5 @@
 use crate::component::{CalculateOutOfBounds, Healing, Position, Render, Velocity};
 use quicksilver::geom::Vector;
-use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 use specs::{Builder, Entity, World};
 
@@ -17,7 +16,7 @@ impl Default for HealingConfig {
     fn default() -> HealingConfig {
         HealingConfig {
             sprite: "potion".to_string(),
-            position: Vector::new(thread_rng().gen_range(50.0, 700.0), -100.0),
+            position: Vector::new(375.0, -100.0),
             velocity: Vector::new(0.0, 250.0),
             score: 50,
         }
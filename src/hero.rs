@@ -1,4 +1,10 @@
-use crate::component::{CalculateOutOfBounds, Hero, Position, Render, Velocity};
+use crate::{
+    component::{
+        CalculateOutOfBounds, Combo, Dash, Focus, Hero, HeroWeapon, Overdrive, PlayerId, Position,
+        Render, SecondaryWeapon, Velocity,
+    },
+    enemy::{ContactDamage, DamageOverTimeConfig, EmitterPattern},
+};
 use quicksilver::geom::{Rectangle, Shape, Vector};
 use serde::{Deserialize, Serialize};
 use specs::{Builder, Entity, World};
@@ -20,12 +26,221 @@ pub fn get_hero_body_feet_area(self_area: Rectangle, position: Vector) -> (Recta
     )
 }
 
+pub fn get_kill_score(hero: &Hero, score: i32) -> i32 {
+    (score as f32 * hero.score_multiplier).round() as i32
+}
+
+pub fn add_score(hero: &mut Hero, amount: i32) {
+    hero.score = hero.score.saturating_add(amount).min(hero.max_score);
+}
+
+pub fn add_kill_score(hero: &mut Hero, score: i32) {
+    let kill_score = get_kill_score(hero, score);
+    add_score(hero, kill_score);
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct RiskZoneConfig {
+    pub distance: f32,
+    pub multiplier: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct NoFireZoneConfig {
+    pub position: Vector,
+    pub size: Vector,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct DashConfig {
+    pub speed: f32,
+    pub duration_ms: u64,
+    pub cooldown_ms: u64,
+}
+
+impl Default for DashConfig {
+    fn default() -> DashConfig {
+        DashConfig {
+            speed: 700.0,
+            duration_ms: 150,
+            cooldown_ms: 800,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct FocusConfig {
+    pub speed_factor: f32,
+    pub show_hitbox: bool,
+}
+
+impl Default for FocusConfig {
+    fn default() -> FocusConfig {
+        FocusConfig {
+            speed_factor: 0.5,
+            show_hitbox: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct OverdriveConfig {
+    pub duration_ms: u64,
+    pub cooldown_factor: f32,
+    pub sound: Option<String>,
+}
+
+impl Default for OverdriveConfig {
+    fn default() -> OverdriveConfig {
+        OverdriveConfig {
+            duration_ms: 5000,
+            cooldown_factor: 0.5,
+            sound: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct ComboConfig {
+    pub window_ms: u64,
+    pub max_multiplier: u32,
+    pub break_penalty: i32,
+    pub break_flash_ms: u64,
+}
+
+impl Default for ComboConfig {
+    fn default() -> ComboConfig {
+        ComboConfig {
+            window_ms: 2000,
+            max_multiplier: 4,
+            break_penalty: 0,
+            break_flash_ms: 0,
+        }
+    }
+}
+
+pub fn register_combo_kill(combo: &mut Combo) {
+    combo.multiplier = (combo.multiplier + 1).min(combo.max_multiplier.max(1));
+    combo.timer = combo.window;
+}
+
+pub fn get_combo_meter_level(combo: &Combo) -> f32 {
+    (combo.multiplier as f32 / combo.max_multiplier.max(1) as f32).min(1.0)
+}
+
+pub fn get_combo_meter_fill(combo: &Combo) -> f32 {
+    if combo.multiplier <= 1 || combo.window == Duration::from_millis(0) {
+        return 0.0;
+    }
+    (combo.timer.as_secs_f32() / combo.window.as_secs_f32()).min(1.0)
+}
+
+pub fn update_overdrive(overdrive: &mut Overdrive, combo: &Combo, elapsed: Duration) -> bool {
+    if overdrive.active {
+        if overdrive.remaining > elapsed {
+            overdrive.remaining -= elapsed;
+        } else {
+            overdrive.remaining = Duration::from_millis(0);
+            overdrive.active = false;
+        }
+    }
+    let at_max = combo.multiplier >= combo.max_multiplier.max(2);
+    let triggered = at_max && !overdrive.at_max && !overdrive.active;
+    overdrive.at_max = at_max;
+    if triggered {
+        overdrive.active = true;
+        overdrive.remaining = overdrive.duration;
+    }
+    triggered
+}
+
+pub fn get_overdrive_cooldown(cooldown: Duration, overdrive: Option<&Overdrive>) -> Duration {
+    match overdrive {
+        Some(overdrive) if overdrive.active => {
+            cooldown.mul_f32(overdrive.cooldown_factor.clamp(0.0, 1.0))
+        }
+        _ => cooldown,
+    }
+}
+
+pub fn break_combo(hero: &mut Hero, combo: &mut Combo) {
+    if combo.multiplier <= 1 {
+        return;
+    }
+    combo.multiplier = 1;
+    combo.timer = Duration::from_millis(0);
+    combo.flash_remaining = combo.break_flash;
+    if combo.break_penalty > 0 {
+        hero.score = (hero.score - combo.break_penalty).max(0);
+    }
+}
+
+pub fn get_next_weapon_mode(current_mode: usize, amount: usize) -> usize {
+    (current_mode + 1) % amount
+}
+
+pub fn is_in_no_fire_zone(zones: &[Rectangle], position: Vector) -> bool {
+    zones.iter().any(|zone| zone.contains(position))
+}
+
+pub fn get_unlocked_weapon_modes(weapon: &HeroWeapon) -> usize {
+    match weapon.level {
+        Some(level) => level.max(1).min(weapon.modes.len()),
+        None => weapon.modes.len(),
+    }
+}
+
+pub fn downgrade_weapon(weapon: &mut HeroWeapon) {
+    if let Some(level) = weapon.level {
+        let level = level.saturating_sub(1).max(1);
+        weapon.level = Some(level);
+        weapon.current_mode = weapon.current_mode.min(level - 1);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WeaponModeConfig {
+    pub projectile_sprite: String,
+    pub pattern: EmitterPattern,
+    pub projectile_speed: f32,
+    pub cooldown_ms: u64,
+    #[serde(default)]
+    pub damage_over_time: Option<DamageOverTimeConfig>,
+    #[serde(default)]
+    pub contact_damage: Option<ContactDamage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SecondaryWeaponConfig {
+    pub mode: WeaponModeConfig,
+    pub ammo: u32,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct HeroConfig {
     pub sprite: String,
     pub position: Vector,
     pub lives: i32,
+    pub movement_margin: f32,
+    pub risk_zone: Option<RiskZoneConfig>,
+    pub weapon_modes: Vec<WeaponModeConfig>,
+    pub secondary_weapon: Option<SecondaryWeaponConfig>,
+    pub fireball_trail: bool,
+    pub weapon_levels: bool,
+    pub pickup_magnet_radius: f32,
+    pub max_score: i32,
+    pub combo: Option<ComboConfig>,
+    pub overdrive: Option<OverdriveConfig>,
+    pub allow_extra_lives: bool,
+    pub allow_shields: bool,
+    pub dash: Option<DashConfig>,
+    pub dash_afterimages: bool,
+    pub focus: Option<FocusConfig>,
 }
 
 impl Default for HeroConfig {
@@ -34,13 +249,90 @@ impl Default for HeroConfig {
             sprite: "heroi".to_string(),
             position: Vector::new(425.0, 425.0),
             lives: 5,
+            movement_margin: 15.0,
+            risk_zone: None,
+            weapon_modes: Vec::new(),
+            secondary_weapon: None,
+            fireball_trail: false,
+            weapon_levels: false,
+            pickup_magnet_radius: 0.0,
+            max_score: i32::MAX,
+            combo: None,
+            overdrive: None,
+            allow_extra_lives: true,
+            allow_shields: true,
+            dash: None,
+            dash_afterimages: false,
+            focus: None,
         }
     }
 }
 
-pub fn create_hero(world: &mut World, config: HeroConfig) -> Entity {
-    world
-        .create_entity()
+pub fn create_hero(world: &mut World, config: HeroConfig, player: PlayerId) -> Entity {
+    let mut builder = world.create_entity();
+    if !config.weapon_modes.is_empty() {
+        builder = builder.with(HeroWeapon {
+            level: Some(config.weapon_modes.len()).filter(|_| config.weapon_levels),
+            modes: config.weapon_modes,
+            current_mode: 0,
+            cooldown: Duration::from_millis(0),
+            aim: Vector::new(1.0, 0.0),
+            cycle_held: false,
+            fireball_trail: config.fireball_trail,
+        });
+    }
+    if let Some(secondary_weapon) = config.secondary_weapon {
+        builder = builder.with(SecondaryWeapon {
+            mode: secondary_weapon.mode,
+            ammo: secondary_weapon.ammo,
+            cooldown: Duration::from_millis(0),
+            held: false,
+        });
+    }
+    if let Some(dash) = config.dash {
+        builder = builder.with(Dash {
+            speed: dash.speed,
+            duration: Duration::from_millis(dash.duration_ms),
+            cooldown: Duration::from_millis(dash.cooldown_ms),
+            remaining: Duration::from_millis(0),
+            cooldown_timer: Duration::from_millis(0),
+            direction: 1.0,
+            held: false,
+            afterimages: config.dash_afterimages,
+            afterimage_timer: Duration::from_millis(0),
+        });
+    }
+    if let Some(focus) = config.focus {
+        builder = builder.with(Focus {
+            speed_factor: focus.speed_factor.clamp(0.0, 1.0),
+            show_hitbox: focus.show_hitbox,
+            active: false,
+        });
+    }
+    if let Some(combo) = config.combo {
+        builder = builder.with(Combo {
+            multiplier: 1,
+            max_multiplier: combo.max_multiplier,
+            window: Duration::from_millis(combo.window_ms),
+            timer: Duration::from_millis(0),
+            break_penalty: combo.break_penalty,
+            break_flash: Duration::from_millis(combo.break_flash_ms),
+            flash_remaining: Duration::from_millis(0),
+            last_lives: config.lives,
+        });
+    }
+    if let Some(overdrive) = config.overdrive {
+        builder = builder.with(Overdrive {
+            duration: Duration::from_millis(overdrive.duration_ms),
+            cooldown_factor: overdrive.cooldown_factor,
+            sound: overdrive.sound,
+            remaining: Duration::from_millis(0),
+            active: false,
+            at_max: false,
+        });
+    }
+    builder
+        .with(player)
         .with(Hero {
             lives: config.lives,
             score: 0,
@@ -48,6 +340,17 @@ pub fn create_hero(world: &mut World, config: HeroConfig) -> Entity {
             render: true,
             reset_position: false,
             blink_timer: Duration::from_millis(0),
+            movement_margin: config.movement_margin,
+            risk_zone: config.risk_zone,
+            base_score_multiplier: 1.0,
+            score_multiplier: 1.0,
+            pickup_magnet_radius: config.pickup_magnet_radius,
+            max_score: config.max_score,
+            allow_extra_lives: config.allow_extra_lives,
+            allow_shields: config.allow_shields,
+            bombs: 0,
+            bomb_held: false,
+            weapon_drain_cooldown: Duration::from_millis(0),
         })
         .with(CalculateOutOfBounds)
         .with(Position {
@@ -62,3 +365,30 @@ pub fn create_hero(world: &mut World, config: HeroConfig) -> Entity {
         })
         .build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::{create_world, SceneConfig};
+
+    #[test]
+    fn score_saturates_at_the_cap_instead_of_wrapping() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            max_score: 1_000,
+            ..HeroConfig::default()
+        };
+        let e = create_hero(&mut world, config, PlayerId::One);
+        let mut storage = world.write_storage::<Hero>();
+        let hero = storage.get_mut(e).unwrap();
+
+        hero.score = 990;
+        add_score(hero, 50);
+        assert_eq!(hero.score, 1_000);
+
+        hero.max_score = i32::MAX;
+        hero.score = i32::MAX - 10;
+        add_score(hero, 50);
+        assert_eq!(hero.score, i32::MAX);
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+const NAME_LENGTH: usize = 3;
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const ANONYMOUS_NAME: &str = "---";
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ScoreEntry {
+    pub name: String,
+    pub score: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct HighScoreTable {
+    pub entries: Vec<ScoreEntry>,
+}
+
+impl HighScoreTable {
+    pub fn qualifies(&self, score: i32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self
+                    .entries
+                    .last()
+                    .is_none_or(|lowest| score > lowest.score))
+    }
+
+    pub fn insert(&mut self, entry: ScoreEntry) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        let index = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(index)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NameEntry {
+    letters: [u8; NAME_LENGTH],
+    cursor: usize,
+}
+
+impl NameEntry {
+    pub fn new() -> NameEntry {
+        NameEntry {
+            letters: [b'A'; NAME_LENGTH],
+            cursor: 0,
+        }
+    }
+
+    pub fn next_letter(&mut self) {
+        let letter = &mut self.letters[self.cursor];
+        *letter = if *letter == b'Z' { b'A' } else { *letter + 1 };
+    }
+
+    pub fn previous_letter(&mut self) {
+        let letter = &mut self.letters[self.cursor];
+        *letter = if *letter == b'A' { b'Z' } else { *letter - 1 };
+    }
+
+    pub fn confirm(&mut self) -> bool {
+        self.cursor += 1;
+        if self.cursor >= NAME_LENGTH {
+            self.cursor = NAME_LENGTH - 1;
+            return true;
+        }
+        false
+    }
+
+    pub fn name(&self) -> String {
+        self.letters.iter().map(|&letter| letter as char).collect()
+    }
+
+    pub fn display(&self) -> String {
+        self.letters
+            .iter()
+            .enumerate()
+            .map(|(i, &letter)| {
+                if i == self.cursor {
+                    format!("[{}]", letter as char)
+                } else {
+                    format!(" {} ", letter as char)
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for NameEntry {
+    fn default() -> NameEntry {
+        NameEntry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: i32) -> ScoreEntry {
+        ScoreEntry {
+            name: ANONYMOUS_NAME.to_string(),
+            score,
+        }
+    }
+
+    #[test]
+    fn qualifying_score_is_sorted_in_and_evicts_the_lowest() {
+        let mut table = HighScoreTable {
+            entries: (1..=MAX_HIGH_SCORES as i32)
+                .rev()
+                .map(|i| entry(i * 100))
+                .collect(),
+        };
+        assert_eq!(table.insert(entry(50)), None);
+
+        assert_eq!(table.insert(entry(550)), Some(5));
+        assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+        assert_eq!(table.entries[5].score, 550);
+        assert_eq!(table.entries.last().unwrap().score, 200);
+    }
+}
@@ -10,17 +10,38 @@ extern crate log;
 #[cfg(not(target_arch = "wasm32"))]
 extern crate simple_logger;
 
+pub mod background;
+pub mod behavior;
+pub mod boss;
+pub mod combo;
 pub mod component;
+pub mod debug;
+pub mod difficulty;
+pub mod effects;
 pub mod enemy;
 pub mod entity_factory;
+pub mod error;
 pub mod game;
 pub mod healing;
 pub mod hero;
+pub mod high_score;
 pub mod instant;
 pub mod music;
+pub mod particle;
+pub mod persistence;
+pub mod power_up;
+pub mod radar;
+pub mod replay;
 pub mod resources;
 pub mod scene;
+pub mod score_decay;
+pub mod settings;
+pub mod snapshot;
 pub mod system;
+#[cfg(test)]
+mod test_support;
+pub mod timestep;
+pub mod wave_clear;
 
 #[cfg(test)]
 mod tests {
@@ -1,53 +1,518 @@
+use crate::{
+    component::{Boss, DespawnAnim, Enemy},
+    resources::SoundRequest,
+};
 use quicksilver::{
     lifecycle::Asset,
     sound::{Sound, StopHandle},
     Result,
 };
+use serde::{Deserialize, Serialize};
+use specs::{Join, ReadStorage, System, Write};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TrackConfig {
+    pub path: String,
+    pub start_offset_ms: u64,
+    pub volume: f32,
+}
+
+impl TrackConfig {
+    pub fn new(path: &str) -> TrackConfig {
+        TrackConfig {
+            path: path.to_string(),
+            start_offset_ms: 0,
+            volume: 1.0,
+        }
+    }
+}
+
+impl Default for TrackConfig {
+    fn default() -> TrackConfig {
+        TrackConfig::new("")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MusicLayerConfig {
+    pub track: TrackConfig,
+    pub threshold: u32,
+}
+
+impl Default for MusicLayerConfig {
+    fn default() -> MusicLayerConfig {
+        MusicLayerConfig {
+            track: TrackConfig::default(),
+            threshold: 5,
+        }
+    }
+}
+
+struct Layer {
+    sound: Asset<Sound>,
+    stop_handle: Option<StopHandle>,
+    track_volume: f32,
+    level: f32,
+    active: bool,
+}
+
+struct Fade {
+    elapsed: Duration,
+    duration: Duration,
+    next_music: TrackConfig,
+}
 
 pub struct MusicPlayer {
     current_music: Option<Asset<Sound>>,
+    current_path: Option<String>,
     stop_handle: Option<StopHandle>,
     volume: f32,
+    asset_base: String,
+    fade: Option<Fade>,
+    start_offset: Duration,
+    played: Duration,
+    track_volume: f32,
+    layers: Vec<Layer>,
+    layer_fade: Duration,
 }
 
 impl MusicPlayer {
-    pub fn new() -> Result<Self> {
+    #[allow(clippy::result_large_err)]
+    pub fn new(asset_base: String) -> Result<Self> {
         Ok(MusicPlayer {
             current_music: None,
+            current_path: None,
             stop_handle: None,
             volume: 0.75,
+            asset_base,
+            fade: None,
+            start_offset: Duration::from_millis(0),
+            played: Duration::from_millis(0),
+            track_volume: 1.0,
+            layers: Vec::new(),
+            layer_fade: Duration::from_millis(0),
         })
     }
 
-    pub fn update(&mut self) -> Result<()> {
+    pub fn set_layers(&mut self, layers: Vec<TrackConfig>, fade: Duration) {
+        self.layer_fade = fade;
+        self.layers = layers
+            .into_iter()
+            .map(|track| Layer {
+                sound: Asset::new(Sound::load(crate::scene::get_asset_path(
+                    &self.asset_base,
+                    &track.path,
+                ))),
+                stop_handle: None,
+                track_volume: track.volume,
+                level: 0.0,
+                active: false,
+            })
+            .collect();
+    }
+
+    pub fn set_active_layers(&mut self, count: usize) {
+        for (i, layer) in self.layers.iter_mut().enumerate() {
+            layer.active = i < count;
+        }
+    }
+
+    pub fn layer_levels(&self) -> Vec<f32> {
+        self.layers.iter().map(|layer| layer.level).collect()
+    }
+
+    fn fade_layers(&mut self, delta: Duration) {
+        let step = if self.layer_fade > Duration::from_millis(0) {
+            delta.as_secs_f32() / self.layer_fade.as_secs_f32()
+        } else {
+            1.0
+        };
+        for layer in self.layers.iter_mut() {
+            layer.level = get_layer_level(layer.level, layer.active, step);
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn update_layers(&mut self, delta: Duration) -> Result<()> {
+        self.fade_layers(delta);
+        let volume = self.volume;
+        let position = self.position();
+        for layer in self.layers.iter_mut() {
+            let vol = get_effective_volume(volume, layer.track_volume) * layer.level;
+            let Layer {
+                sound,
+                stop_handle,
+                level,
+                ..
+            } = layer;
+            if *level <= 0.0 {
+                if let Some(handle) = stop_handle.take() {
+                    handle.stop()?;
+                }
+                continue;
+            }
+            sound.execute(|sound| {
+                sound.set_volume(vol);
+                if stop_handle.is_none() {
+                    *stop_handle = Some(sound.play_from(position)?);
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn update(&mut self, delta: Duration) -> Result<()> {
+        self.update_layers(delta)?;
+        let volume = self.effective_volume();
+        if let Some(fade) = &mut self.fade {
+            fade.elapsed += delta;
+            if fade.elapsed >= fade.duration {
+                let next_music = fade.next_music.clone();
+                return self.play_music(next_music);
+            }
+            if let Some(handle) = &self.stop_handle {
+                handle.set_volume(get_fade_volume(volume, fade.elapsed, fade.duration));
+            }
+            return Ok(());
+        }
         if self.stop_handle.is_none() {
+            let vol = self.effective_volume();
             if let Some(music) = &mut self.current_music {
                 let mut handle: Option<StopHandle> = None;
-                let vol = self.volume;
+                let offset = self.start_offset;
                 music.execute(|music| {
                     music.set_volume(vol);
-                    handle = Some(music.play()?);
+                    handle = Some(music.play_from(offset)?);
                     Ok(())
                 })?;
                 self.stop_handle = handle;
             }
+        } else {
+            self.played += delta;
         }
         Ok(())
     }
 
-    pub fn play_music(&mut self, music: String) -> Result<()> {
+    #[allow(clippy::result_large_err)]
+    pub fn fade_to_music(&mut self, music: TrackConfig, duration: Duration) -> Result<()> {
+        if duration == Duration::from_millis(0) || self.current_music.is_none() {
+            return self.play_music(music);
+        }
+        self.fade = Some(Fade {
+            elapsed: Duration::from_millis(0),
+            duration,
+            next_music: music,
+        });
+        Ok(())
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    pub fn current_track(&self) -> Option<&str> {
+        self.current_path.as_deref()
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn set_volume(&mut self, volume: f32) -> Result<()> {
+        self.volume = volume.clamp(0.0, 1.0);
+        if self.fade.is_none() {
+            if let Some(handle) = &self.stop_handle {
+                handle.set_volume(self.effective_volume());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn effective_volume(&self) -> f32 {
+        get_effective_volume(self.volume, self.track_volume)
+    }
+
+    pub fn position(&self) -> Duration {
+        self.start_offset + self.played
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn play_music(&mut self, music: TrackConfig) -> Result<()> {
+        self.fade = None;
         self.stop_music()?;
-        self.current_music = Some(Asset::new(Sound::load(music)));
+        self.start_offset = Duration::from_millis(music.start_offset_ms);
+        self.played = Duration::from_millis(0);
+        self.track_volume = music.volume;
+        self.current_path = Some(music.path.clone());
+        self.current_music = Some(Asset::new(Sound::load(crate::scene::get_asset_path(
+            &self.asset_base,
+            &music.path,
+        ))));
         Ok(())
     }
 
+    #[allow(clippy::result_large_err)]
     fn stop_music(&mut self) -> Result<()> {
         match self.stop_handle.take() {
             Some(x) => {
                 self.current_music = None;
+                self.current_path = None;
                 x.stop()
             }
             None => Ok(()),
         }
     }
 }
+
+pub struct SfxPlayer {
+    sounds: HashMap<String, Asset<Sound>>,
+    volume: f32,
+    asset_base: String,
+    max_concurrent: Option<usize>,
+    playing: VecDeque<StopHandle>,
+}
+
+impl SfxPlayer {
+    pub fn new(asset_base: String, volume: f32, max_concurrent: Option<usize>) -> Self {
+        SfxPlayer {
+            sounds: HashMap::new(),
+            volume: volume.clamp(0.0, 1.0),
+            asset_base,
+            max_concurrent,
+            playing: VecDeque::new(),
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn play_requests(&mut self, requests: Vec<SoundRequest>) -> Result<()> {
+        for request in select_sound_requests(requests, self.max_concurrent) {
+            self.play(&request.path)?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn play(&mut self, path: &str) -> Result<()> {
+        if let Some(max_concurrent) = self.max_concurrent {
+            if max_concurrent == 0 {
+                return Ok(());
+            }
+            self.playing.retain(|handle| !handle.is_finished());
+            while self.playing.len() >= max_concurrent {
+                if let Some(handle) = self.playing.pop_front() {
+                    handle.stop()?;
+                }
+            }
+        }
+        let asset_base = &self.asset_base;
+        let vol = self.volume;
+        let mut handle: Option<StopHandle> = None;
+        self.sounds
+            .entry(path.to_string())
+            .or_insert_with(|| {
+                Asset::new(Sound::load(crate::scene::get_asset_path(asset_base, path)))
+            })
+            .execute(|sound| {
+                sound.set_volume(vol);
+                handle = Some(sound.play_from(Duration::from_millis(0))?);
+                Ok(())
+            })?;
+        if let Some(handle) = handle.filter(|_| self.max_concurrent.is_some()) {
+            self.playing.push_back(handle);
+        }
+        Ok(())
+    }
+}
+
+pub fn select_sound_requests(
+    mut requests: Vec<SoundRequest>,
+    max_concurrent: Option<usize>,
+) -> Vec<SoundRequest> {
+    if let Some(max_concurrent) = max_concurrent {
+        requests.sort_by_key(|request| Reverse(request.priority));
+        requests.truncate(max_concurrent);
+    }
+    requests
+}
+
+pub fn get_layer_level(level: f32, active: bool, step: f32) -> f32 {
+    if active {
+        (level + step).min(1.0)
+    } else {
+        (level - step).max(0.0)
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct Threat {
+    pub level: u32,
+    pub boss_weight: u32,
+}
+
+pub struct ThreatSystem;
+
+impl<'a> System<'a> for ThreatSystem {
+    type SystemData = (
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, Boss>,
+        ReadStorage<'a, DespawnAnim>,
+        Write<'a, Threat>,
+    );
+
+    fn run(&mut self, (enemy, boss, despawn_anim, mut threat): Self::SystemData) {
+        let enemies = (&enemy, !&boss, !&despawn_anim).join().count() as u32;
+        let bosses = (&boss, !&despawn_anim).join().count() as u32;
+        threat.level = enemies + bosses * threat.boss_weight;
+    }
+}
+
+pub fn get_active_layers(threat: u32, layers: &[MusicLayerConfig]) -> usize {
+    layers
+        .iter()
+        .take_while(|layer| threat >= layer.threshold)
+        .count()
+}
+
+pub fn get_effective_volume(global_volume: f32, track_volume: f32) -> f32 {
+    (global_volume * track_volume).clamp(0.0, 1.0)
+}
+
+pub fn get_fade_volume(volume: f32, elapsed: Duration, duration: Duration) -> f32 {
+    volume * (1.0 - elapsed.as_secs_f32() / duration.as_secs_f32()).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enemy::BossConfig,
+        resources::SoundPriority,
+        scene::{create_world, SceneConfig},
+        test_support::{create_test_enemy, run},
+    };
+    use quicksilver::geom::Vector;
+
+    #[test]
+    fn fade_lowers_volume_then_switches_to_next_track() {
+        let mut music_player = MusicPlayer::new(String::new()).unwrap();
+        music_player
+            .play_music(TrackConfig::new("music/boss.ogg"))
+            .unwrap();
+        music_player
+            .fade_to_music(
+                TrackConfig::new("music/gameover.ogg"),
+                Duration::from_millis(100),
+            )
+            .unwrap();
+
+        music_player.update(Duration::from_millis(50)).unwrap();
+        assert!(music_player.is_fading());
+        assert_eq!(music_player.current_track(), Some("music/boss.ogg"));
+
+        music_player.update(Duration::from_millis(50)).unwrap();
+        assert!(!music_player.is_fading());
+        assert_eq!(music_player.current_track(), Some("music/gameover.ogg"));
+
+        let half = Duration::from_millis(50);
+        let full = Duration::from_millis(100);
+        assert_eq!(get_fade_volume(0.8, half, full), 0.4);
+    }
+
+    #[test]
+    fn track_starts_at_its_offset() {
+        let mut music_player = MusicPlayer::new(String::new()).unwrap();
+        music_player
+            .play_music(TrackConfig {
+                start_offset_ms: 5000,
+                ..TrackConfig::new("music/normal.ogg")
+            })
+            .unwrap();
+        assert_eq!(music_player.position(), Duration::from_millis(5000));
+    }
+
+    #[test]
+    fn track_volume_scales_the_global_volume() {
+        let mut music_player = MusicPlayer::new(String::new()).unwrap();
+        music_player.set_volume(0.8).unwrap();
+        music_player
+            .play_music(TrackConfig {
+                volume: 0.5,
+                ..TrackConfig::new("music/normal.ogg")
+            })
+            .unwrap();
+        assert_eq!(music_player.effective_volume(), 0.4);
+    }
+
+    #[test]
+    fn layers_fade_with_the_frame_delta() {
+        let mut music_player = MusicPlayer::new(String::new()).unwrap();
+        music_player.set_layers(
+            vec![
+                TrackConfig::new("music/drums.ogg"),
+                TrackConfig::new("music/brass.ogg"),
+            ],
+            Duration::from_millis(100),
+        );
+        let step = Duration::from_millis(50);
+
+        music_player.set_active_layers(1);
+        music_player.fade_layers(step);
+        assert_eq!(music_player.layer_levels(), vec![0.5, 0.0]);
+        music_player.fade_layers(step);
+        assert_eq!(music_player.layer_levels(), vec![1.0, 0.0]);
+
+        music_player.set_active_layers(0);
+        music_player.fade_layers(step);
+        assert_eq!(music_player.layer_levels(), vec![0.5, 0.0]);
+    }
+
+    #[test]
+    fn sfx_limit_keeps_the_highest_priority_requests() {
+        let request = |path: &str, priority| SoundRequest {
+            path: path.to_string(),
+            priority,
+        };
+        let requests = vec![
+            request("hit.ogg", SoundPriority::Normal),
+            request("spawn.ogg", SoundPriority::Low),
+            request("death.ogg", SoundPriority::High),
+            request("pickup.ogg", SoundPriority::Normal),
+        ];
+
+        let selected = select_sound_requests(requests.clone(), Some(2));
+        assert_eq!(
+            selected,
+            vec![
+                request("death.ogg", SoundPriority::High),
+                request("hit.ogg", SoundPriority::Normal),
+            ]
+        );
+        assert_eq!(select_sound_requests(requests.clone(), None), requests);
+    }
+
+    #[test]
+    fn threat_counts_enemies_and_weights_the_boss() {
+        let mut world = create_world(&SceneConfig {
+            boss_threat: 5,
+            ..SceneConfig::default()
+        });
+        create_test_enemy(&mut world, Vector::new(400, 300), 1);
+        create_test_enemy(&mut world, Vector::new(500, 300), 1);
+        run(&mut world, ThreatSystem);
+        assert_eq!(world.read_resource::<Threat>().level, 2);
+
+        crate::enemy::create_boss(&mut world, BossConfig::default());
+        run(&mut world, ThreatSystem);
+        assert_eq!(world.read_resource::<Threat>().level, 7);
+    }
+}
@@ -0,0 +1,428 @@
+use crate::{
+    component::{
+        Ambient, Dash, DespawnAnim, Lifetime, Particle, Position, Render, Trail, Velocity,
+    },
+    resources::{AdaptiveQuality, PlayField, RandomGenerator, SystemErrors},
+    system::FRAME_DURATION,
+};
+use quicksilver::{geom::Vector, graphics::Color};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use specs::{
+    Builder, Entities, Entity, Join, LazyUpdate, Read, ReadStorage, System, World, Write,
+    WriteStorage,
+};
+use std::time::Duration;
+
+pub const TRAIL_INTERVAL: Duration = Duration::from_millis(50);
+const TRAIL_OFFSET: f32 = 12.0;
+const AFTERIMAGE_INTERVAL: Duration = Duration::from_millis(30);
+const AFTERIMAGE_MS: u64 = 200;
+
+#[derive(Debug, Copy, Clone)]
+pub struct ParticleConfig {
+    pub color: Color,
+    pub radius: f32,
+    pub velocity: Vector,
+    pub lifetime_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct AmbientConfig {
+    pub count: usize,
+    pub speed: f32,
+    pub color: [f32; 4],
+    pub radius: f32,
+}
+
+impl Default for AmbientConfig {
+    fn default() -> AmbientConfig {
+        AmbientConfig {
+            count: 40,
+            speed: 30.0,
+            color: [1.0, 1.0, 1.0, 0.6],
+            radius: 2.0,
+        }
+    }
+}
+
+pub fn create_ambient_particles(world: &mut World, config: AmbientConfig) {
+    let size = world.read_resource::<PlayField>().size;
+    let color = Color {
+        r: config.color[0],
+        g: config.color[1],
+        b: config.color[2],
+        a: config.color[3],
+    };
+    for _ in 0..config.count {
+        let (position, speed_factor) = {
+            let mut rng = world.write_resource::<RandomGenerator>();
+            (
+                Vector::new(
+                    rng.rng.gen_range(0.0, size.x),
+                    rng.rng.gen_range(0.0, size.y),
+                ),
+                rng.rng.gen_range(0.5, 1.5),
+            )
+        };
+        world
+            .create_entity()
+            .with(Position { position })
+            .with(Velocity {
+                velocity: Vector::new(-config.speed * speed_factor, 0.0),
+            })
+            .with(Ambient {
+                color,
+                radius: config.radius,
+            })
+            .build();
+    }
+}
+
+pub fn get_particle_alpha(particle: &Particle, lifetime: &Lifetime) -> f32 {
+    if particle.duration == Duration::from_millis(0) {
+        return 0.0;
+    }
+    particle.color.a * lifetime.remaining.as_secs_f32() / particle.duration.as_secs_f32()
+}
+
+pub fn create_particle<T: Builder>(builder: T, position: Vector, config: ParticleConfig) -> Entity {
+    builder
+        .with(Position { position })
+        .with(Velocity {
+            velocity: config.velocity,
+        })
+        .with(Particle {
+            color: config.color,
+            radius: config.radius,
+            duration: Duration::from_millis(config.lifetime_ms),
+        })
+        .with(Lifetime {
+            remaining: Duration::from_millis(config.lifetime_ms),
+        })
+        .build()
+}
+
+pub fn get_excess_particles(mut particles: Vec<(Entity, Duration)>, max: usize) -> Vec<Entity> {
+    if particles.len() <= max {
+        return Vec::new();
+    }
+    particles.sort_by(|(_, a), (_, b)| b.cmp(a));
+    let excess = particles.len() - max;
+    particles.into_iter().take(excess).map(|(e, _)| e).collect()
+}
+
+pub struct TrailSystem;
+
+impl<'a> System<'a> for TrailSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Velocity>,
+        WriteStorage<'a, Trail>,
+        Read<'a, LazyUpdate>,
+        Read<'a, AdaptiveQuality>,
+    );
+
+    fn run(&mut self, (entities, pos, vel, mut trail, lazy, quality): Self::SystemData) {
+        for (pos, vel, trail) in (&pos, &vel, &mut trail).join() {
+            trail.timer += FRAME_DURATION;
+            if trail.timer < trail.interval || vel.velocity.len2() == 0.0 {
+                continue;
+            }
+            trail.timer = Duration::from_millis(0);
+            if quality.reduced {
+                continue;
+            }
+
+            let behind = pos.position - vel.velocity.normalize() * TRAIL_OFFSET;
+            create_particle(
+                lazy.create_entity(&entities),
+                behind,
+                ParticleConfig {
+                    color: trail.color,
+                    radius: 4.0,
+                    velocity: Vector::ZERO,
+                    lifetime_ms: 200,
+                },
+            );
+        }
+    }
+}
+
+pub struct AfterimageSystem;
+
+impl<'a> System<'a> for AfterimageSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Render>,
+        WriteStorage<'a, Dash>,
+        Read<'a, LazyUpdate>,
+    );
+
+    fn run(&mut self, (entities, pos, render, mut dash, lazy): Self::SystemData) {
+        for (pos, render, dash) in (&pos, &render, &mut dash).join() {
+            if !dash.afterimages || dash.remaining == Duration::from_millis(0) {
+                dash.afterimage_timer = Duration::from_millis(0);
+                continue;
+            }
+            if dash.afterimage_timer > FRAME_DURATION {
+                dash.afterimage_timer -= FRAME_DURATION;
+                continue;
+            }
+            dash.afterimage_timer = AFTERIMAGE_INTERVAL;
+
+            lazy.create_entity(&entities)
+                .with(Position {
+                    position: pos.position,
+                })
+                .with(Render {
+                    sprite: render.sprite.clone(),
+                    bounding_box: None,
+                })
+                .with(DespawnAnim {
+                    remaining_ms: AFTERIMAGE_MS,
+                    duration_ms: AFTERIMAGE_MS,
+                })
+                .build();
+        }
+    }
+}
+
+pub struct AmbientSystem;
+
+impl<'a> System<'a> for AmbientSystem {
+    type SystemData = (
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Ambient>,
+        Write<'a, RandomGenerator>,
+        Read<'a, PlayField>,
+    );
+
+    fn run(&mut self, (mut pos, ambient, mut rng, play_field): Self::SystemData) {
+        let size = play_field.size;
+        for (pos, ambient) in (&mut pos, &ambient).join() {
+            if pos.position.x < -ambient.radius {
+                pos.position = Vector::new(size.x + ambient.radius, rng.rng.gen_range(0.0, size.y));
+            }
+        }
+    }
+}
+
+pub struct ParticleCapSystem {
+    pub max: usize,
+}
+
+impl<'a> System<'a> for ParticleCapSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Particle>,
+        ReadStorage<'a, Lifetime>,
+        Write<'a, SystemErrors>,
+    );
+
+    fn run(&mut self, (entities, particle, lifetime, mut errors): Self::SystemData) {
+        let particles: Vec<(Entity, Duration)> = (&entities, &particle, &lifetime)
+            .join()
+            .map(|(e, particle, lifetime)| {
+                let age = if particle.duration > lifetime.remaining {
+                    particle.duration - lifetime.remaining
+                } else {
+                    Duration::from_millis(0)
+                };
+                (e, age)
+            })
+            .collect();
+        for e in get_excess_particles(particles, self.max) {
+            crate::system::delete_entity(&entities, e, &mut errors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::PlayerId,
+        hero::{DashConfig, HeroConfig},
+        scene::{create_world, SceneConfig},
+        system::{LifetimeSystem, WalkSystem},
+        test_support::run,
+    };
+
+    #[test]
+    fn moving_fireball_leaves_trail_particles_behind_it() {
+        let mut world = create_world(&SceneConfig::default());
+        for y in [100.0, 200.0] {
+            let builder = world
+                .create_entity()
+                .with(Position {
+                    position: Vector::new(400.0, y),
+                })
+                .with(Velocity {
+                    velocity: Vector::new(5, 0),
+                });
+            if y == 100.0 {
+                builder
+                    .with(Trail {
+                        color: Color::ORANGE,
+                        interval: TRAIL_INTERVAL,
+                        timer: Duration::from_millis(0),
+                    })
+                    .build();
+            } else {
+                builder.build();
+            }
+        }
+
+        for _ in 0..10 {
+            run(&mut world, TrailSystem);
+        }
+
+        let particle = world.read_storage::<Particle>();
+        let pos = world.read_storage::<Position>();
+        let trail: Vec<Vector> = (&particle, &pos).join().map(|(_, p)| p.position).collect();
+        assert_eq!(trail.len(), 3);
+        for position in trail {
+            assert_eq!(position, Vector::new(400.0 - TRAIL_OFFSET, 100.0));
+        }
+    }
+
+    #[test]
+    fn dashing_hero_leaves_afterimages_until_the_dash_ends() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            dash: Some(DashConfig::default()),
+            dash_afterimages: true,
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        let count_afterimages =
+            |world: &World| (&world.read_storage::<DespawnAnim>()).join().count();
+        world
+            .write_storage::<Dash>()
+            .get_mut(hero)
+            .unwrap()
+            .remaining = Duration::from_millis(100);
+
+        for _ in 0..6 {
+            run(&mut world, AfterimageSystem);
+        }
+        let spawned = count_afterimages(&world);
+        assert!(spawned > 0);
+
+        world
+            .write_storage::<Dash>()
+            .get_mut(hero)
+            .unwrap()
+            .remaining = Duration::from_millis(0);
+        for _ in 0..6 {
+            run(&mut world, AfterimageSystem);
+        }
+        assert_eq!(count_afterimages(&world), spawned);
+    }
+
+    #[test]
+    fn drifting_ambient_particles_respawn_and_keep_their_count() {
+        let mut world = create_world(&SceneConfig::default());
+        create_ambient_particles(
+            &mut world,
+            AmbientConfig {
+                count: 12,
+                speed: 600.0,
+                ..AmbientConfig::default()
+            },
+        );
+        let field_size = world.read_resource::<PlayField>().size;
+        let mut respawned = false;
+        for _ in 0..200 {
+            let before: Vec<f32> = (
+                &world.read_storage::<Position>(),
+                &world.read_storage::<Ambient>(),
+            )
+                .join()
+                .map(|(pos, _)| pos.position.x)
+                .collect();
+            run(&mut world, WalkSystem);
+            run(&mut world, AmbientSystem);
+            let pos = world.read_storage::<Position>();
+            let ambient = world.read_storage::<Ambient>();
+            let after: Vec<f32> = (&pos, &ambient)
+                .join()
+                .map(|(pos, _)| pos.position.x)
+                .collect();
+            assert_eq!(after.len(), 12);
+            respawned |= before
+                .iter()
+                .zip(after.iter())
+                .any(|(before, after)| after > before);
+            assert!(after.iter().all(|x| *x <= field_size.x + 2.0));
+        }
+        assert!(respawned);
+    }
+
+    fn count_trail_particles(fps: f32) -> usize {
+        let mut world = create_world(&SceneConfig {
+            adaptive_quality: true,
+            ..SceneConfig::default()
+        });
+        world.write_resource::<AdaptiveQuality>().update(fps);
+        world
+            .create_entity()
+            .with(Position {
+                position: Vector::new(400, 100),
+            })
+            .with(Velocity {
+                velocity: Vector::new(5, 0),
+            })
+            .with(Trail {
+                color: Color::ORANGE,
+                interval: TRAIL_INTERVAL,
+                timer: Duration::from_millis(0),
+            })
+            .build();
+        for _ in 0..10 {
+            run(&mut world, TrailSystem);
+        }
+        let particles = world.read_storage::<Particle>().join().count();
+        particles
+    }
+
+    #[test]
+    fn reduced_quality_stops_spawning_trail_particles() {
+        assert_eq!(count_trail_particles(20.0), 0);
+        assert_eq!(count_trail_particles(60.0), 3);
+    }
+
+    #[test]
+    fn particle_cap_recycles_the_oldest_particles() {
+        let mut world = create_world(&SceneConfig::default());
+        let spawn = |world: &mut World, count: usize| -> Vec<Entity> {
+            (0..count)
+                .map(|_| {
+                    create_particle(
+                        world.create_entity(),
+                        Vector::new(400, 300),
+                        ParticleConfig {
+                            color: Color::ORANGE,
+                            radius: 4.0,
+                            velocity: Vector::ZERO,
+                            lifetime_ms: 500,
+                        },
+                    )
+                })
+                .collect()
+        };
+        let old = spawn(&mut world, 100);
+        run(&mut world, LifetimeSystem);
+        let burst = spawn(&mut world, 50);
+
+        run(&mut world, ParticleCapSystem { max: 100 });
+
+        assert_eq!(world.read_storage::<Particle>().join().count(), 100);
+        assert!(burst.iter().all(|&e| world.is_alive(e)));
+        assert_eq!(old.iter().filter(|&&e| world.is_alive(e)).count(), 50);
+    }
+}
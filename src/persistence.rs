@@ -0,0 +1,36 @@
+use quicksilver::{Error, Result};
+use serde::{de::DeserializeOwned, Serialize};
+
+#[allow(clippy::result_large_err)]
+pub fn serialize<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|error| Error::ContextError(error.to_string()))
+}
+
+#[allow(clippy::result_large_err)]
+pub fn deserialize<T: DeserializeOwned>(data: &str) -> Result<T> {
+    serde_json::from_str(data).map_err(|error| Error::ContextError(error.to_string()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::result_large_err)]
+pub fn save<T: Serialize>(value: &T, path: impl AsRef<std::path::Path>) -> Result<()> {
+    std::fs::write(path, serialize(value)?)?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::result_large_err)]
+pub fn load<T: DeserializeOwned>(path: impl AsRef<std::path::Path>) -> Result<T> {
+    deserialize(&std::fs::read_to_string(path)?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::result_large_err)]
+pub fn load_if_present<T: DeserializeOwned>(
+    path: impl AsRef<std::path::Path>,
+) -> Result<Option<T>> {
+    if !path.as_ref().exists() {
+        return Ok(None);
+    }
+    load(path).map(Some)
+}
@@ -0,0 +1,356 @@
+use crate::{
+    component::{
+        BombDrop, Boss, CalculateOutOfBounds, DespawnAnim, Enemy, Faction, Fireball, Healing, Hero,
+        PlayerId, Position, PowerUp, Render, Velocity,
+    },
+    effects::ScreenFlash,
+    enemy::EnemyType,
+    resources::{KeyboardKeys, Kill, Kills, PressedKeys, SystemErrors},
+};
+use quicksilver::geom::Vector;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use specs::{Builder, Entities, Entity, Join, Read, ReadStorage, System, Write, WriteStorage};
+
+const MAGNET_SPEED: f32 = 300.0;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum PowerUpKind {
+    ExtraLife,
+    Invincibility,
+    Bomb,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PowerUpConfig {
+    pub kind: PowerUpKind,
+    pub sprite: String,
+    pub velocity: Vector,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DropEntry {
+    pub weight: u32,
+    pub power_up: Option<PowerUpConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DropTableConfig {
+    pub enemy_type: EnemyType,
+    pub entries: Vec<DropEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BombDropConfig {
+    pub enemy_type: EnemyType,
+    pub sprite: String,
+    pub velocity: Vector,
+}
+
+pub fn roll_drop_table<R: Rng>(entries: &[DropEntry], rng: &mut R) -> Option<PowerUpConfig> {
+    let total_weight: u32 = entries.iter().map(|entry| entry.weight).sum();
+    if total_weight == 0 {
+        return None;
+    }
+    let mut roll = rng.gen_range(0, total_weight);
+    for entry in entries {
+        if roll < entry.weight {
+            return entry.power_up.clone();
+        }
+        roll -= entry.weight;
+    }
+    None
+}
+
+pub fn create_bomb_drop<T: Builder>(builder: T, position: Vector, bomb_drop: &BombDrop) -> Entity {
+    create_power_up(
+        builder,
+        position,
+        PowerUpConfig {
+            kind: PowerUpKind::Bomb,
+            sprite: bomb_drop.sprite.clone(),
+            velocity: bomb_drop.velocity,
+        },
+    )
+}
+
+pub fn create_power_up<T: Builder>(builder: T, position: Vector, config: PowerUpConfig) -> Entity {
+    builder
+        .with(CalculateOutOfBounds)
+        .with(Position { position })
+        .with(Velocity {
+            velocity: config.velocity,
+        })
+        .with(Render {
+            sprite: config.sprite,
+            bounding_box: None,
+        })
+        .with(PowerUp { kind: config.kind })
+        .build()
+}
+
+pub struct BombSystem;
+
+impl<'a> System<'a> for BombSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, PressedKeys>,
+        WriteStorage<'a, Hero>,
+        ReadStorage<'a, PlayerId>,
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, Boss>,
+        ReadStorage<'a, Fireball>,
+        ReadStorage<'a, DespawnAnim>,
+        ReadStorage<'a, Position>,
+        Write<'a, Kills>,
+        Write<'a, ScreenFlash>,
+        Write<'a, SystemErrors>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            pressed_keys,
+            mut hero,
+            player,
+            enemy,
+            boss,
+            fireball,
+            despawn_anim,
+            pos,
+            mut kills,
+            mut screen_flash,
+            mut errors,
+        ): Self::SystemData,
+    ) {
+        for (e_hero, hero, player) in (&entities, &mut hero, &player).join() {
+            let pressed =
+                pressed_keys.pressed_keys[*player as usize].contains(KeyboardKeys::KeyBomb as u32);
+            let triggered = pressed && !hero.bomb_held && hero.bombs > 0;
+            hero.bomb_held = pressed;
+            if !triggered {
+                continue;
+            }
+            hero.bombs -= 1;
+            screen_flash.start();
+
+            for (e, pos, _, _, _) in (&entities, &pos, &enemy, !&boss, !&despawn_anim).join() {
+                if kills.kills.iter().any(|kill| kill.enemy == e) {
+                    continue;
+                }
+                kills.kills.push(Kill {
+                    enemy: e,
+                    position: pos.position,
+                    hero_id: Some(e_hero.id()),
+                });
+            }
+            for (e, fireball) in (&entities, &fireball).join() {
+                if fireball.faction == Faction::Enemy {
+                    crate::system::delete_entity(&entities, e, &mut errors);
+                }
+            }
+        }
+    }
+}
+
+pub struct PickupMagnetSystem;
+
+impl<'a> System<'a> for PickupMagnetSystem {
+    type SystemData = (
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Hero>,
+        ReadStorage<'a, Healing>,
+        ReadStorage<'a, PowerUp>,
+    );
+
+    fn run(&mut self, (mut pos, hero, healing, power_up): Self::SystemData) {
+        let time_step = 0.0167;
+        let magnets: Vec<(Vector, f32)> = (&pos, &hero)
+            .join()
+            .filter(|(_, hero)| hero.pickup_magnet_radius > 0.0)
+            .map(|(pos, hero)| (pos.position, hero.pickup_magnet_radius))
+            .collect();
+        if magnets.is_empty() {
+            return;
+        }
+
+        for (pos, _) in (&mut pos, &healing).join() {
+            pos.position += get_magnet_pull(pos.position, &magnets) * MAGNET_SPEED * time_step;
+        }
+        for (pos, _) in (&mut pos, &power_up).join() {
+            pos.position += get_magnet_pull(pos.position, &magnets) * MAGNET_SPEED * time_step;
+        }
+    }
+}
+
+fn get_magnet_pull(position: Vector, magnets: &[(Vector, f32)]) -> Vector {
+    magnets
+        .iter()
+        .filter(|&&(magnet, radius)| {
+            let distance = magnet.distance(position);
+            distance > 0.0 && distance <= radius
+        })
+        .min_by(|&&(a, _), &&(b, _)| {
+            a.distance(position)
+                .partial_cmp(&b.distance(position))
+                .unwrap()
+        })
+        .map(|&(magnet, _)| (magnet - position).normalize())
+        .unwrap_or(Vector::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hero::HeroConfig,
+        resources::GameMetrics,
+        scene::{create_world, SceneConfig},
+        system::{CollisionSystem, KillSystem},
+        test_support::{create_test_enemy, run, set_bounding_box, set_key},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+    use specs::Builder;
+
+    fn entry(weight: u32, kind: Option<PowerUpKind>) -> DropEntry {
+        DropEntry {
+            weight,
+            power_up: kind.map(|kind| PowerUpConfig {
+                kind,
+                sprite: "power_up".to_string(),
+                velocity: Vector::ZERO,
+            }),
+        }
+    }
+
+    fn roll_kinds(entries: &[DropEntry], seed: u64) -> Vec<Option<PowerUpKind>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..10_000)
+            .map(|_| roll_drop_table(entries, &mut rng).map(|power_up| power_up.kind))
+            .collect()
+    }
+
+    #[test]
+    fn seeded_drop_table_matches_weights() {
+        let entries = vec![
+            entry(70, None),
+            entry(20, Some(PowerUpKind::Invincibility)),
+            entry(10, Some(PowerUpKind::ExtraLife)),
+        ];
+        let drops = roll_kinds(&entries, 42);
+        assert_eq!(drops, roll_kinds(&entries, 42));
+
+        let count = |kind| drops.iter().filter(|drop| **drop == kind).count();
+        assert!((6_700..7_300).contains(&count(None)));
+        assert!((1_700..2_300).contains(&count(Some(PowerUpKind::Invincibility))));
+        assert!((800..1_200).contains(&count(Some(PowerUpKind::ExtraLife))));
+    }
+
+    #[test]
+    fn magnet_pulls_only_pickups_within_radius() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            position: Vector::new(400, 500),
+            pickup_magnet_radius: 100.0,
+            ..HeroConfig::default()
+        };
+        crate::hero::create_hero(&mut world, config, PlayerId::One);
+        let mut create_potion = |y: f32| {
+            world
+                .create_entity()
+                .with(Position {
+                    position: Vector::new(400.0, y),
+                })
+                .with(Healing { score: 50 })
+                .build()
+        };
+        let near = create_potion(450.0);
+        let far = create_potion(200.0);
+
+        run(&mut world, PickupMagnetSystem);
+
+        let pos = world.read_storage::<Position>();
+        assert!(pos.get(near).unwrap().position.y > 450.0);
+        assert_eq!(pos.get(far).unwrap().position, Vector::new(400, 200));
+    }
+
+    #[test]
+    fn bomb_dropper_kill_gives_a_bomb_that_is_consumed_on_use() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            position: Vector::new(400, 500),
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        set_bounding_box(&mut world, hero, Vector::new(40, 40));
+        let enemy = create_test_enemy(&mut world, Vector::new(100, 100), 1);
+        world
+            .write_storage::<BombDrop>()
+            .insert(
+                enemy,
+                BombDrop {
+                    sprite: "bomb".to_string(),
+                    velocity: Vector::ZERO,
+                },
+            )
+            .unwrap();
+        world.write_resource::<Kills>().kills.push(Kill {
+            enemy,
+            position: Vector::new(400, 500),
+            hero_id: Some(hero.id()),
+        });
+
+        run(&mut world, KillSystem);
+        let bomb = (&world.entities(), &world.read_storage::<PowerUp>())
+            .join()
+            .map(|(e, power_up)| {
+                assert_eq!(power_up.kind, PowerUpKind::Bomb);
+                e
+            })
+            .next()
+            .unwrap();
+        set_bounding_box(&mut world, bomb, Vector::new(20, 20));
+        run(&mut world, CollisionSystem);
+        assert_eq!(world.read_storage::<Hero>().get(hero).unwrap().bombs, 1);
+        assert!(world.read_storage::<PowerUp>().is_empty());
+
+        set_key(&mut world, KeyboardKeys::KeyBomb, true);
+        run(&mut world, BombSystem);
+        assert_eq!(world.read_storage::<Hero>().get(hero).unwrap().bombs, 0);
+    }
+
+    #[test]
+    fn simultaneous_bombs_score_each_enemy_once_through_the_kill_system() {
+        let mut world = create_world(&SceneConfig {
+            player_two_config: Some(HeroConfig::default()),
+            ..SceneConfig::default()
+        });
+        let one = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let two = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::Two);
+        for hero in (&mut world.write_storage::<Hero>()).join() {
+            hero.bombs = 1;
+        }
+        let enemy = create_test_enemy(&mut world, Vector::new(100, 100), 3);
+        {
+            let mut pressed_keys = world.write_resource::<PressedKeys>();
+            for player in [PlayerId::One, PlayerId::Two].iter() {
+                pressed_keys.pressed_keys[*player as usize].add(KeyboardKeys::KeyBomb as u32);
+            }
+        }
+
+        run(&mut world, BombSystem);
+        assert_eq!(world.read_resource::<Kills>().kills.len(), 1);
+        run(&mut world, KillSystem);
+
+        assert!(!world.is_alive(enemy));
+        assert_eq!(world.read_resource::<GameMetrics>().kills, 1);
+        let hero = world.read_storage::<Hero>();
+        let scores = [hero.get(one).unwrap().score, hero.get(two).unwrap().score];
+        assert_eq!(scores.iter().sum::<i32>(), 100);
+        assert_eq!(
+            [hero.get(one).unwrap().bombs, hero.get(two).unwrap().bombs],
+            [0, 0]
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    component::{DespawnAnim, Enemy, Hero, Position},
+    resources::PlayField,
+};
+use quicksilver::geom::Vector;
+use specs::{Join, Read, ReadStorage, System, Write};
+
+const RADAR_MARGIN: f32 = 10.0;
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct EdgeWarning {
+    pub distance: f32,
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RadarSide {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct RadarIndicator {
+    pub side: RadarSide,
+    pub position: Vector,
+}
+
+#[derive(Default)]
+pub struct Radar {
+    pub indicators: Vec<RadarIndicator>,
+}
+
+pub struct RadarSystem;
+
+impl<'a> System<'a> for RadarSystem {
+    type SystemData = (
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, DespawnAnim>,
+        Write<'a, Radar>,
+        Read<'a, PlayField>,
+    );
+
+    fn run(&mut self, (pos, enemy, despawn_anim, mut radar, play_field): Self::SystemData) {
+        radar.indicators = (&pos, &enemy, !&despawn_anim)
+            .join()
+            .filter_map(|(pos, _, _)| get_radar_indicator(pos.position, play_field.size))
+            .collect();
+    }
+}
+
+pub struct EdgeWarningSystem;
+
+impl<'a> System<'a> for EdgeWarningSystem {
+    type SystemData = (
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Hero>,
+        Read<'a, PlayField>,
+        Write<'a, EdgeWarning>,
+    );
+
+    fn run(&mut self, (pos, hero, play_field, mut edge_warning): Self::SystemData) {
+        let distance = edge_warning.distance;
+        let size = play_field.size;
+        edge_warning.left = 0.0;
+        edge_warning.right = 0.0;
+        edge_warning.top = 0.0;
+        edge_warning.bottom = 0.0;
+        for (pos, _) in (&pos, &hero).join() {
+            let position = pos.position;
+            edge_warning.left = edge_warning
+                .left
+                .max(get_edge_proximity(position.x, distance));
+            edge_warning.right = edge_warning
+                .right
+                .max(get_edge_proximity(size.x - position.x, distance));
+            edge_warning.top = edge_warning
+                .top
+                .max(get_edge_proximity(position.y, distance));
+            edge_warning.bottom = edge_warning
+                .bottom
+                .max(get_edge_proximity(size.y - position.y, distance));
+        }
+    }
+}
+
+pub fn get_edge_proximity(gap: f32, distance: f32) -> f32 {
+    if distance <= 0.0 {
+        return 0.0;
+    }
+    ((distance - gap) / distance).clamp(0.0, 1.0)
+}
+
+pub fn get_radar_indicator(position: Vector, field_size: Vector) -> Option<RadarIndicator> {
+    let side = if position.x > field_size.x {
+        RadarSide::Right
+    } else if position.x < 0.0 {
+        RadarSide::Left
+    } else if position.y < 0.0 {
+        RadarSide::Top
+    } else if position.y > field_size.y {
+        RadarSide::Bottom
+    } else {
+        return None;
+    };
+    Some(RadarIndicator {
+        side,
+        position: Vector::new(
+            position
+                .x
+                .max(RADAR_MARGIN)
+                .min(field_size.x - RADAR_MARGIN),
+            position
+                .y
+                .max(RADAR_MARGIN)
+                .min(field_size.y - RADAR_MARGIN),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::PlayerId,
+        hero::HeroConfig,
+        scene::{create_world, SceneConfig},
+        test_support::{create_test_enemy, run},
+    };
+
+    #[test]
+    fn enemy_entering_from_right_shows_right_radar_indicator() {
+        let mut world = create_world(&SceneConfig::default());
+        create_test_enemy(&mut world, Vector::new(850, 432), 1);
+
+        run(&mut world, RadarSystem);
+
+        let radar = world.read_resource::<Radar>();
+        assert_eq!(radar.indicators.len(), 1);
+        assert_eq!(radar.indicators[0].side, RadarSide::Right);
+        assert_eq!(
+            radar.indicators[0].position,
+            Vector::new(800.0 - RADAR_MARGIN, 432.0)
+        );
+    }
+
+    #[test]
+    fn radar_indicator_survives_a_play_field_narrower_than_its_margins() {
+        let field_size = Vector::new(RADAR_MARGIN, RADAR_MARGIN);
+        let indicator = get_radar_indicator(Vector::new(100, 5), field_size).unwrap();
+        assert_eq!(indicator.side, RadarSide::Right);
+        assert_eq!(indicator.position, Vector::new(0, 0));
+    }
+
+    #[test]
+    fn edge_warning_glows_near_the_left_edge_but_not_at_the_center() {
+        let mut world = create_world(&SceneConfig::default());
+        let size = world.read_resource::<PlayField>().size;
+        let config = HeroConfig {
+            position: Vector::new(10, size.y / 2.0),
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+
+        run(&mut world, EdgeWarningSystem);
+        let near_edge = *world.read_resource::<EdgeWarning>();
+        assert!(near_edge.left > 0.5);
+        assert_eq!(near_edge.right, 0.0);
+
+        world
+            .write_storage::<Position>()
+            .get_mut(hero)
+            .unwrap()
+            .position = size / 2.0;
+        run(&mut world, EdgeWarningSystem);
+        let centered = *world.read_resource::<EdgeWarning>();
+        assert_eq!(centered.left, 0.0);
+        assert_eq!(centered.right, 0.0);
+        assert_eq!(centered.top, 0.0);
+        assert_eq!(centered.bottom, 0.0);
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct ReplayInput {
+    pub elapsed_ms: u64,
+    pub player: u32,
+    pub key: u32,
+    pub pressed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: Option<u64>,
+    pub inputs: Vec<ReplayInput>,
+}
+
+impl Replay {
+    pub fn new(seed: Option<u64>) -> Replay {
+        Replay {
+            seed,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, elapsed_ms: u64, player: u32, key: u32, pressed: bool) {
+        self.inputs.push(ReplayInput {
+            elapsed_ms,
+            player,
+            key,
+            pressed,
+        });
+    }
+}
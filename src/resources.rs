@@ -1,6 +1,14 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
-use specs::BitSet;
+use crate::component::PlayerId;
+
+use quicksilver::{
+    geom::{Rectangle, Vector},
+    graphics::Color,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use specs::{world::Index, BitSet, Entity};
 
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub enum LabelVariable {
@@ -8,6 +16,21 @@ pub enum LabelVariable {
     FramesPerSecond,
     Score,
     EngineVersion,
+    PlayerTwoLives,
+    PlayerTwoScore,
+    ControlHints,
+    StatsScore,
+    StatsTime,
+    StatsKills,
+    Announcement,
+    QuitPrompt,
+    NameEntry,
+    Rebind,
+    Seed,
+    WaveIntro,
+    SecondaryAmmo,
+    PlayerTwoAmmo,
+    BossIntro,
 }
 
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
@@ -26,13 +49,340 @@ pub struct VariableDictionary {
     pub dictionary: HashMap<LabelVariable, String>,
 }
 
+#[derive(Copy, Clone)]
 pub enum KeyboardKeys {
     KeyUp = 1,
     KeyLeft = 2,
     KeyRight = 4,
+    KeyFire = 8,
+    KeyCycleWeapon = 16,
+    KeyDash = 32,
+    KeyFocus = 64,
+    KeyBomb = 128,
+    KeySecondary = 256,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    CycleWeapon,
+    Dash,
+    Focus,
+    Bomb,
+    Secondary,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::MoveUp,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::CycleWeapon,
+        Action::Dash,
+        Action::Focus,
+        Action::Bomb,
+        Action::Secondary,
+    ];
+
+    pub fn keyboard_key(self) -> KeyboardKeys {
+        match self {
+            Action::MoveUp => KeyboardKeys::KeyUp,
+            Action::MoveLeft => KeyboardKeys::KeyLeft,
+            Action::MoveRight => KeyboardKeys::KeyRight,
+            Action::Fire => KeyboardKeys::KeyFire,
+            Action::CycleWeapon => KeyboardKeys::KeyCycleWeapon,
+            Action::Dash => KeyboardKeys::KeyDash,
+            Action::Focus => KeyboardKeys::KeyFocus,
+            Action::Bomb => KeyboardKeys::KeyBomb,
+            Action::Secondary => KeyboardKeys::KeySecondary,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KeyBinding {
+    pub input: String,
+    pub player: PlayerId,
+    pub action: Action,
+}
+
+impl KeyBinding {
+    pub fn new(input: &str, player: PlayerId, action: Action) -> KeyBinding {
+        KeyBinding {
+            input: input.to_string(),
+            player,
+            action,
+        }
+    }
+
+    pub fn is_gamepad(&self) -> bool {
+        is_gamepad_input(&self.input)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub bindings: Vec<KeyBinding>,
+}
+
+impl KeyBindings {
+    pub fn find(&self, input: &str) -> Option<&KeyBinding> {
+        self.bindings.iter().find(|binding| binding.input == input)
+    }
+
+    pub fn get_input(&self, player: PlayerId, action: Action, gamepad: bool) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|binding| {
+                binding.player == player
+                    && binding.action == action
+                    && binding.is_gamepad() == gamepad
+            })
+            .map(|binding| binding.input.as_str())
+    }
+
+    pub fn get_conflict(
+        &self,
+        input: &str,
+        player: PlayerId,
+        action: Action,
+    ) -> Option<&KeyBinding> {
+        self.find(input)
+            .filter(|binding| binding.player != player || binding.action != action)
+    }
+
+    pub fn rebind(
+        &mut self,
+        player: PlayerId,
+        action: Action,
+        input: &str,
+    ) -> Result<(), KeyBinding> {
+        if let Some(conflict) = self.get_conflict(input, player, action) {
+            return Err(conflict.clone());
+        }
+        let gamepad = is_gamepad_input(input);
+        self.bindings.retain(|binding| {
+            binding.player != player || binding.action != action || binding.is_gamepad() != gamepad
+        });
+        self.bindings.push(KeyBinding::new(input, player, action));
+        Ok(())
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            bindings: vec![
+                KeyBinding::new("Key:W", PlayerId::One, Action::MoveUp),
+                KeyBinding::new("Key:A", PlayerId::One, Action::MoveLeft),
+                KeyBinding::new("Key:D", PlayerId::One, Action::MoveRight),
+                KeyBinding::new("Key:Space", PlayerId::One, Action::Fire),
+                KeyBinding::new("Key:Q", PlayerId::One, Action::CycleWeapon),
+                KeyBinding::new("Key:LShift", PlayerId::One, Action::Dash),
+                KeyBinding::new("Key:E", PlayerId::One, Action::Focus),
+                KeyBinding::new("Key:B", PlayerId::One, Action::Bomb),
+                KeyBinding::new("Key:F", PlayerId::One, Action::Secondary),
+                KeyBinding::new("Key:Up", PlayerId::Two, Action::MoveUp),
+                KeyBinding::new("Key:Left", PlayerId::Two, Action::MoveLeft),
+                KeyBinding::new("Key:Right", PlayerId::Two, Action::MoveRight),
+                KeyBinding::new("Key:RControl", PlayerId::Two, Action::Fire),
+                KeyBinding::new("Key:RShift", PlayerId::Two, Action::CycleWeapon),
+                KeyBinding::new("Key:RAlt", PlayerId::Two, Action::Dash),
+                KeyBinding::new("Key:Slash", PlayerId::Two, Action::Focus),
+                KeyBinding::new("Key:Period", PlayerId::Two, Action::Bomb),
+                KeyBinding::new("Key:Comma", PlayerId::Two, Action::Secondary),
+                KeyBinding::new("Pad:DpadUp", PlayerId::One, Action::MoveUp),
+                KeyBinding::new("Pad:DpadLeft", PlayerId::One, Action::MoveLeft),
+                KeyBinding::new("Pad:DpadRight", PlayerId::One, Action::MoveRight),
+                KeyBinding::new("Pad:FaceDown", PlayerId::One, Action::Fire),
+                KeyBinding::new("Pad:FaceLeft", PlayerId::One, Action::CycleWeapon),
+                KeyBinding::new("Pad:FaceRight", PlayerId::One, Action::Dash),
+                KeyBinding::new("Pad:ShoulderLeft", PlayerId::One, Action::Focus),
+                KeyBinding::new("Pad:ShoulderRight", PlayerId::One, Action::Bomb),
+                KeyBinding::new("Pad:FaceUp", PlayerId::One, Action::Secondary),
+            ],
+        }
+    }
+}
+
+pub fn is_gamepad_input(input: &str) -> bool {
+    input.starts_with("Pad:")
 }
 
 #[derive(Default)]
 pub struct PressedKeys {
-    pub pressed_keys: BitSet,
+    pub pressed_keys: [BitSet; 2],
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct GameMetrics {
+    pub elapsed: Duration,
+    pub kills: u32,
+    pub score: i32,
+    pub outcome: Option<GameStateFlag>,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct FireGrace {
+    pub duration: Duration,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct DifficultyRes {
+    pub multiplier: f32,
+}
+
+impl Default for DifficultyRes {
+    fn default() -> DifficultyRes {
+        DifficultyRes { multiplier: 1.0 }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct NoFireZones {
+    pub zones: Vec<Rectangle>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct PlayField {
+    pub size: Vector,
+}
+
+impl Default for PlayField {
+    fn default() -> PlayField {
+        PlayField {
+            size: Vector::new(800.0, 600.0),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SoundQueue {
+    pub sounds: Vec<SoundRequest>,
+}
+
+impl SoundQueue {
+    pub fn push(&mut self, sound: &Option<String>, priority: SoundPriority) {
+        if let Some(sound) = sound {
+            self.sounds.push(SoundRequest {
+                path: sound.clone(),
+                priority,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundRequest {
+    pub path: String,
+    pub priority: SoundPriority,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SoundPriority {
+    Low,
+    Normal,
+    High,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct OutcomeTint {
+    pub defeat: Option<Color>,
+    pub victory: Option<Color>,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct AdaptiveQuality {
+    pub enabled: bool,
+    pub low_fps: f32,
+    pub recover_fps: f32,
+    pub reduced: bool,
+}
+
+impl AdaptiveQuality {
+    pub fn update(&mut self, fps: f32) {
+        if !self.enabled {
+            self.reduced = false;
+        } else if fps < self.low_fps {
+            self.reduced = true;
+        } else if fps >= self.recover_fps {
+            self.reduced = false;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HeroShot {
+    pub sprite: String,
+    pub speed: f32,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Pacifist {
+    pub eligible: bool,
+}
+
+impl Default for Pacifist {
+    fn default() -> Pacifist {
+        Pacifist { eligible: true }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct HeroShots {
+    pub shots: Vec<HeroShot>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Kill {
+    pub enemy: Entity,
+    pub position: Vector,
+    pub hero_id: Option<Index>,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct Kills {
+    pub kills: Vec<Kill>,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct BulletAssist {
+    pub factor: Option<f32>,
+    pub radius: f32,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct AnnouncementFilter {
+    pub suppress_during_boss: bool,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SystemErrors {
+    pub errors: Vec<String>,
+}
+
+pub struct RandomGenerator {
+    pub rng: StdRng,
+}
+
+impl RandomGenerator {
+    pub fn new(seed: Option<u64>) -> Self {
+        RandomGenerator {
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+        }
+    }
+}
+
+impl Default for RandomGenerator {
+    fn default() -> RandomGenerator {
+        RandomGenerator::new(None)
+    }
 }
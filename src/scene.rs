@@ -1,29 +1,82 @@
-use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, rc::Rc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{cell::RefCell, rc::Rc, time::Duration};
 
 use crate::{
+    background::{BackgroundStageSystem, BackgroundStages},
+    behavior::{
+        ChargeAttackSystem, DashEntranceSystem, DiveSystem, DroneSystem, MimicSystem,
+        MirrorHeroXSystem, SelfShieldSystem, SeparationSystem,
+    },
+    boss::{BossHealerSystem, BossPhaseSystem, FinalPhase},
+    combo::{ComboMeter, ComboMeterSystem, ComboSystem, OverdriveSystem},
     component::{
-        Background, Boss, CalculateOutOfBounds, ChangeSprite, Enemy, Fireball, Healing, Hero,
-        Label, Position, Render, Shooter, Velocity,
+        Acceleration, Ambient, Anchor, Background, BombDrop, Boss, BossHealer,
+        CalculateOutOfBounds, ChangeSprite, ChargeAttack, Combo, Corner, DamageOverTime, Dash,
+        DashEntrance, DespawnAnim, Dive, DroneHost, DropTable, Emitter, Enemy, EntitySounds,
+        Faction, Fireball, Focus, FollowParent, FreezeFor, Healing, Health, Hero, HeroWeapon,
+        HitFlash, Label, Lifetime, Mimic, MirrorHeroX, OneHitShield, Overdrive, Particle, PlayerId,
+        Position, PowerUp, Render, SecondaryWeapon, SelfShield, ShieldAura, ShieldDonor, Shooter,
+        Trail, Velocity,
+    },
+    debug::{InputHistory, SystemProfile},
+    difficulty::{RubberBand, RubberBandConfig, RubberBandSystem, SpawnPacing, SpawnPacingSystem},
+    effects::{
+        FreezeSystem, HitFlashSettings, HitFlashSystem, HitFreeze, ScreenFlash, ScreenFlashSystem,
+        ScreenShake, ScreenShakeSystem, ShakeCurve,
     },
     enemy::BossConfig,
     entity_factory::{EntityFactory, EntityFactoryConfig},
-    hero::HeroConfig,
-    music::MusicPlayer,
+    error::{EngineError, EngineResult},
+    hero::{HeroConfig, NoFireZoneConfig},
+    high_score::{HighScoreTable, NameEntry, ScoreEntry},
+    instant::Instant,
+    music::{
+        get_active_layers, MusicLayerConfig, MusicPlayer, SfxPlayer, Threat, ThreatSystem,
+        TrackConfig,
+    },
+    particle::{AfterimageSystem, AmbientConfig, AmbientSystem, ParticleCapSystem, TrailSystem},
+    power_up::{BombSystem, PickupMagnetSystem},
+    radar::{EdgeWarning, EdgeWarningSystem, Radar, RadarSystem},
+    replay::{Replay, ReplayInput},
     resources::{
-        GameStateFlag, GameStateFlagRes, KeyboardKeys, LabelVariable, PressedKeys,
-        VariableDictionary,
+        Action, AdaptiveQuality, AnnouncementFilter, BulletAssist, DifficultyRes, FireGrace,
+        GameMetrics, GameStateFlag, GameStateFlagRes, HeroShots, KeyBindings, KeyboardKeys, Kills,
+        LabelVariable, NoFireZones, OutcomeTint, Pacifist, PlayField, PressedKeys, RandomGenerator,
+        SoundQueue, SystemErrors, VariableDictionary,
     },
+    score_decay::{ScoreDecay, ScoreDecayConfig, ScoreDecaySystem},
+    settings::Settings,
+    snapshot::WorldSnapshot,
     system::{
-        CollisionSystem, FireballSystem, HeroBlinkingSystem, HeroControlSystem, LabelRenderSystem,
-        OutOfBoundsSystem, RenderSystem, WalkSystem,
+        CollisionSystem, DamageOverTimeSystem, DespawnSystem, EmitterSystem, FireballSystem,
+        FollowParentSystem, HeroBlinkingSystem, HeroControlSystem, KillSystem, LabelRenderSystem,
+        LifetimeSystem, MetricsSystem, OutOfBoundsSystem, RenderSystem, RiskZoneSystem, WalkSystem,
+        FRAME_DURATION,
     },
+    timestep::FrameAccumulator,
+    wave_clear::{WaveClear, WaveClearSystem},
 };
 
-use quicksilver::{graphics::Atlas, prelude::*};
+use quicksilver::{
+    graphics::{Atlas, AtlasItem, View},
+    prelude::*,
+};
 
 use specs::prelude::*;
 
+const RESERVED_INPUTS: [&str; 10] = [
+    "Key:P",
+    "Key:Pause",
+    "Key:Escape",
+    "Key:Return",
+    "Key:Y",
+    "Key:N",
+    "Key:F1",
+    "Key:F5",
+    "Key:F9",
+    "Pad:Start",
+];
+
 #[derive(PartialEq, Copy, Clone)]
 enum GameState {
     WaitingInput,
@@ -36,222 +89,650 @@ enum GameState {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct SceneConfig {
+    pub asset_base: String,
     pub atlas: String,
     pub font: String,
     pub main_background: String,
     pub defeat_background: String,
     pub victory_background: String,
     pub hero_config: HeroConfig,
+    pub player_two_config: Option<HeroConfig>,
     pub boss_config: BossConfig,
     pub entity_factory_config: EntityFactoryConfig,
     pub boss_cycle: u32,
+    pub boss_rush: bool,
+    pub boss_kill_requirement: Option<u32>,
+    pub sudden_death: bool,
     pub new_body_cycle: u64,
-    pub normal_music: String,
-    pub boss_music: String,
-    pub game_over_music: String,
-    pub victory_music: String,
+    pub normal_music: TrackConfig,
+    pub boss_music: TrackConfig,
+    pub final_phase_music: Option<TrackConfig>,
+    pub music_layers: Vec<MusicLayerConfig>,
+    pub music_layer_fade_ms: u64,
+    pub boss_threat: u32,
+    pub game_over_music: TrackConfig,
+    pub victory_music: TrackConfig,
+    pub seed: Option<u64>,
+    pub show_seed: bool,
+    pub show_control_hints: bool,
+    pub control_hints_ms: u64,
+    pub show_stats_screen: bool,
+    pub show_radar: bool,
+    pub show_combo_meter: bool,
+    pub edge_warning: bool,
+    pub edge_warning_distance: f32,
+    pub no_fire_zones: Vec<NoFireZoneConfig>,
+    pub shake_curve: ShakeCurve,
+    pub shake_intensity: f32,
+    pub shake_ms: u64,
+    pub bomb_flash_color: Option<[f32; 4]>,
+    pub bomb_flash_ms: u64,
+    pub wave_clear_bonus: i32,
+    pub spawns_per_wave: u32,
+    pub show_wave_intro: bool,
+    pub wave_intro_ms: u64,
+    pub pause_dim_alpha: f32,
+    pub score_thousands_separator: Option<char>,
+    pub debug_mode: bool,
+    pub input_history_size: usize,
+    pub profile_systems: bool,
+    pub outcome_fade_ms: u64,
+    pub suppress_announcements_during_boss: bool,
+    pub difficulty_per_wave: f32,
+    pub min_window_size: Option<(u32, u32)>,
+    pub play_field_size: (u32, u32),
+    pub resize_play_field: bool,
+    pub letterbox_color: [f32; 4],
+    pub letterbox_sprite: Option<String>,
+    pub confirm_quit: bool,
+    pub record_replay: bool,
+    pub max_delta_ms: Option<u64>,
+    pub high_score_path: Option<String>,
+    pub adaptive_quality: bool,
+    pub low_quality_fps: f32,
+    pub recover_quality_fps: f32,
+    pub name_entry: bool,
+    pub key_bindings: KeyBindings,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub max_concurrent_sfx: Option<usize>,
+    pub headless_max_ms: u64,
+    pub difficulty: f32,
+    pub settings_path: Option<String>,
+    pub practice_checkpoints: bool,
+    pub hit_flash_color: Option<[f32; 4]>,
+    pub hit_flash_ms: u64,
+    pub hit_freeze_ms: Option<u64>,
+    pub max_particles: Option<usize>,
+    pub bullet_assist: Option<f32>,
+    pub bullet_assist_radius: f32,
+    pub rubber_band: Option<RubberBandConfig>,
+    pub score_decay: Option<ScoreDecayConfig>,
+    pub pacifist_bonus: Option<i32>,
+    pub background_stages: Vec<(i32, String)>,
+    pub background_fade_color: Option<[f32; 4]>,
+    pub background_fade_ms: u64,
+    pub defeat_tint: Option<[f32; 4]>,
+    pub victory_tint: Option<[f32; 4]>,
+    pub ambient_particles: Option<AmbientConfig>,
 }
 
 impl Default for SceneConfig {
     fn default() -> SceneConfig {
         SceneConfig {
+            asset_base: String::new(),
             atlas: "evil_alligator.atlas".to_string(),
             font: "cmunrm.ttf".to_string(),
             main_background: "cenario".to_string(),
             defeat_background: "inferno".to_string(),
             victory_background: "ceu".to_string(),
             hero_config: HeroConfig::default(),
+            player_two_config: None,
             boss_config: BossConfig::default(),
             entity_factory_config: EntityFactoryConfig::default(),
             boss_cycle: 11,
+            boss_rush: false,
+            boss_kill_requirement: None,
+            sudden_death: false,
             new_body_cycle: 210,
-            normal_music: "music/normal.ogg".to_string(),
-            boss_music: "music/boss.ogg".to_string(),
-            game_over_music: "music/gameover.ogg".to_string(),
-            victory_music: "music/victory.ogg".to_string(),
+            normal_music: TrackConfig::new("music/normal.ogg"),
+            boss_music: TrackConfig::new("music/boss.ogg"),
+            final_phase_music: None,
+            music_layers: Vec::new(),
+            music_layer_fade_ms: 1000,
+            boss_threat: 10,
+            game_over_music: TrackConfig::new("music/gameover.ogg"),
+            victory_music: TrackConfig::new("music/victory.ogg"),
+            seed: None,
+            show_seed: false,
+            show_control_hints: false,
+            control_hints_ms: 4000,
+            show_stats_screen: false,
+            show_radar: false,
+            show_combo_meter: false,
+            edge_warning: false,
+            edge_warning_distance: 80.0,
+            no_fire_zones: Vec::new(),
+            shake_curve: ShakeCurve::EaseOut,
+            shake_intensity: 0.0,
+            shake_ms: 300,
+            bomb_flash_color: Some([1.0, 1.0, 1.0, 0.8]),
+            bomb_flash_ms: 250,
+            wave_clear_bonus: 0,
+            spawns_per_wave: 5,
+            show_wave_intro: false,
+            wave_intro_ms: 2000,
+            pause_dim_alpha: 0.0,
+            score_thousands_separator: None,
+            debug_mode: false,
+            input_history_size: 8,
+            profile_systems: false,
+            outcome_fade_ms: 0,
+            suppress_announcements_during_boss: false,
+            difficulty_per_wave: 0.0,
+            min_window_size: None,
+            play_field_size: (800, 600),
+            resize_play_field: false,
+            letterbox_color: [0.0, 0.0, 0.0, 1.0],
+            letterbox_sprite: None,
+            confirm_quit: false,
+            record_replay: false,
+            max_delta_ms: None,
+            high_score_path: None,
+            adaptive_quality: false,
+            low_quality_fps: 40.0,
+            recover_quality_fps: 55.0,
+            name_entry: false,
+            key_bindings: KeyBindings::default(),
+            music_volume: 0.75,
+            sfx_volume: 1.0,
+            max_concurrent_sfx: None,
+            headless_max_ms: 600_000,
+            difficulty: 1.0,
+            settings_path: None,
+            practice_checkpoints: false,
+            hit_flash_color: None,
+            hit_flash_ms: 100,
+            hit_freeze_ms: None,
+            max_particles: None,
+            bullet_assist: None,
+            bullet_assist_radius: 150.0,
+            rubber_band: None,
+            score_decay: None,
+            pacifist_bonus: None,
+            background_stages: Vec::new(),
+            background_fade_color: Some([0.0, 0.0, 0.0, 1.0]),
+            background_fade_ms: 400,
+            defeat_tint: None,
+            victory_tint: None,
+            ambient_particles: None,
         }
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct RebindMenu {
+    selected: usize,
+    waiting: bool,
+    label: Entity,
+}
+
+#[derive(Debug, Clone)]
+struct Checkpoint {
+    snapshot: WorldSnapshot,
+    cycle_timer: u64,
+    cycle_counter: u32,
+    awaiting_boss_kills: bool,
+    spawn_timer: u64,
+    pacifist: Pacifist,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct EntityStats {
+    pub enemies: usize,
+    pub enemy_fireballs: usize,
+    pub hero_fireballs: usize,
+    pub particles: usize,
+    pub boss_alive: bool,
+}
+
 pub struct Scene {
     world: World,
     atlas: Rc<RefCell<Asset<Atlas>>>,
     font: Rc<RefCell<Asset<Font>>>,
-    hero: Entity,
     state: GameState,
     cycle_timer: u64,
     cycle_counter: u32,
+    awaiting_boss_kills: bool,
+    spawn_timer: u64,
     music_player: MusicPlayer,
+    sfx_player: SfxPlayer,
     entity_factory: EntityFactory,
+    replay: Replay,
+    quit_prompt: Option<(Entity, GameState)>,
+    rebind_menu: Option<RebindMenu>,
+    checkpoint: Option<Checkpoint>,
+    accumulator: Option<FrameAccumulator>,
+    high_scores: HighScoreTable,
+    name_entry: Option<(NameEntry, i32)>,
+    wave_intro: Option<u64>,
+    boss_intro: Option<(u64, Entity)>,
+    playback: Option<(Vec<ReplayInput>, usize)>,
+    headless: bool,
+    seed: u64,
     config: SceneConfig,
 }
 
 impl Scene {
-    pub fn new(config: SceneConfig) -> Result<Self> {
-        let atlas = Rc::new(RefCell::new(Asset::new(Atlas::load(config.atlas.clone()))));
-        let font = Rc::new(RefCell::new(Asset::new(Font::load(config.font.clone()))));
-        let music_player = MusicPlayer::new()?;
+    #[allow(clippy::result_large_err)]
+    pub fn new(mut config: SceneConfig) -> Result<Self> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(settings) = load_persisted::<Settings>(&mut config.settings_path) {
+                settings.apply(&mut config);
+            }
+        }
+        if config.sudden_death {
+            apply_sudden_death(&mut config);
+        }
+        let seed = *config.seed.get_or_insert_with(rand::random);
+        let atlas = Rc::new(RefCell::new(Asset::new(Atlas::load(get_asset_path(
+            &config.asset_base,
+            &config.atlas,
+        )))));
+        let font = Rc::new(RefCell::new(Asset::new(Font::load(get_asset_path(
+            &config.asset_base,
+            &config.font,
+        )))));
+        let mut music_player = MusicPlayer::new(config.asset_base.clone())?;
+        music_player.set_volume(config.music_volume)?;
+        music_player.set_layers(
+            config
+                .music_layers
+                .iter()
+                .map(|layer| layer.track.clone())
+                .collect(),
+            Duration::from_millis(config.music_layer_fade_ms),
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        let high_scores = load_persisted(&mut config.high_score_path).unwrap_or_default();
+        #[cfg(target_arch = "wasm32")]
+        let high_scores = HighScoreTable::default();
 
-        let mut world = World::new();
-        register_components(&mut world);
-        add_resorces(&mut world);
+        let mut world = create_world(&config);
 
         create_background(&mut world, config.main_background.clone());
-        create_label(
+        if let Some(ambient_config) = config.ambient_particles {
+            crate::particle::create_ambient_particles(&mut world, ambient_config);
+        }
+        create_anchored_label(
             &mut world,
             LabelVariable::FramesPerSecond,
             FontStyle::new(48.0, Color::BLACK),
-            Vector::new(20, 587),
+            Corner::BottomLeft,
+            Vector::new(20, 13),
         );
-        create_label(
+        create_anchored_label(
             &mut world,
             LabelVariable::HeroLives,
             FontStyle::new(48.0, Color::BLACK),
+            Corner::TopLeft,
             Vector::new(10, 20),
         );
-        create_label(
+        create_anchored_label(
             &mut world,
             LabelVariable::Score,
             FontStyle::new(48.0, Color::BLACK),
-            Vector::new(730, 20),
+            Corner::TopRight,
+            Vector::new(70, 20),
         );
-        create_label(
+        create_anchored_label(
             &mut world,
             LabelVariable::EngineVersion,
             FontStyle::new(48.0, Color::BLACK),
-            Vector::new(730, 587),
+            Corner::BottomRight,
+            Vector::new(70, 13),
         );
-        let hero = crate::hero::create_hero(&mut world, config.hero_config.clone());
+        if config.show_seed {
+            create_anchored_label(
+                &mut world,
+                LabelVariable::Seed,
+                FontStyle::new(24.0, Color::BLACK),
+                Corner::BottomLeft,
+                Vector::new(20, 50),
+            );
+        }
+        if config.hero_config.secondary_weapon.is_some() {
+            create_anchored_label(
+                &mut world,
+                LabelVariable::SecondaryAmmo,
+                FontStyle::new(36.0, Color::BLACK),
+                Corner::TopLeft,
+                Vector::new(10, 100),
+            );
+        }
+        crate::hero::create_hero(&mut world, config.hero_config.clone(), PlayerId::One);
+        if let Some(player_two_config) = config.player_two_config.clone() {
+            create_anchored_label(
+                &mut world,
+                LabelVariable::PlayerTwoLives,
+                FontStyle::new(48.0, Color::BLACK),
+                Corner::TopLeft,
+                Vector::new(10, 60),
+            );
+            create_anchored_label(
+                &mut world,
+                LabelVariable::PlayerTwoScore,
+                FontStyle::new(48.0, Color::BLACK),
+                Corner::TopRight,
+                Vector::new(70, 60),
+            );
+            if player_two_config.secondary_weapon.is_some() {
+                create_anchored_label(
+                    &mut world,
+                    LabelVariable::PlayerTwoAmmo,
+                    FontStyle::new(36.0, Color::BLACK),
+                    Corner::TopRight,
+                    Vector::new(70, 100),
+                );
+            }
+            crate::hero::create_hero(&mut world, player_two_config, PlayerId::Two);
+        }
+        let mut entity_factory = EntityFactory::new(config.entity_factory_config.clone())?;
+        entity_factory.select_spawn_table(config.difficulty);
 
         Ok(Scene {
             world,
             atlas,
             font,
-            hero,
             state: GameState::WaitingInput,
             cycle_timer: 0,
-            cycle_counter: 0,
+            spawn_timer: 0,
+            cycle_counter: if config.boss_rush {
+                config.boss_cycle
+            } else {
+                0
+            },
+            awaiting_boss_kills: false,
             music_player,
-            entity_factory: EntityFactory::new(config.entity_factory_config.clone())?,
+            sfx_player: SfxPlayer::new(
+                config.asset_base.clone(),
+                config.sfx_volume,
+                config.max_concurrent_sfx,
+            ),
+            entity_factory,
+            replay: Replay::new(config.seed),
+            quit_prompt: None,
+            rebind_menu: None,
+            checkpoint: None,
+            accumulator: config.max_delta_ms.map(|max_delta_ms| {
+                FrameAccumulator::new(FRAME_DURATION, Duration::from_millis(max_delta_ms))
+            }),
+            high_scores,
+            name_entry: None,
+            wave_intro: None,
+            boss_intro: None,
+            playback: None,
+            headless: false,
+            seed,
             config,
         })
     }
 
-    pub fn update(&mut self, _window: &mut Window) -> Result<()> {
+    pub fn update(&mut self, window: &mut Window) -> EngineResult<()> {
         if self.state != GameState::WaitingInput {
             if self.state == GameState::Running {
-                self.entity_factory()?;
-                self.run_update_systems()?;
-                let flag = self.world.read_resource::<GameStateFlagRes>().flag;
-                if let Some(f) = flag {
-                    match f {
-                        GameStateFlag::Victory => self.victory(),
-                        GameStateFlag::Defeat => self.defeat(),
-                    }?;
+                self.world
+                    .write_resource::<AdaptiveQuality>()
+                    .update(window.average_fps() as f32);
+                for _ in 0..self.frame_steps() {
+                    if self.step_frame()? {
+                        break;
+                    }
+                }
+                if self.state == GameState::Running {
+                    let threat = self.world.read_resource::<Threat>().level;
+                    self.music_player
+                        .set_active_layers(get_active_layers(threat, &self.config.music_layers));
                 }
             }
-            self.music_player.update()?;
+            self.update_audio()?;
             self.world.maintain();
+            if let Some(max) = self.config.max_particles {
+                ParticleCapSystem { max }.run_now(&self.world.res);
+                self.take_system_error()?;
+                self.world.maintain();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn step(&mut self) -> EngineResult<()> {
+        if self.state != GameState::Running {
+            return Ok(());
         }
+        self.apply_playback();
+        self.step_frame()?;
+        self.world.write_resource::<SoundQueue>().sounds.clear();
+        self.world.maintain();
         Ok(())
     }
 
-    pub fn draw(&mut self, window: &mut Window) -> Result<()> {
-        window.clear(Color::WHITE)?;
+    pub fn play_replay(&mut self, replay: Replay) {
+        self.playback = Some((replay.inputs, 0));
+        self.state = GameState::Running;
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.state == GameState::GameOver
+    }
+
+    fn step_frame(&mut self) -> EngineResult<bool> {
+        if self.hold_for_boss_intro() {
+            return Ok(false);
+        }
+        self.entity_factory()?;
+        self.run_update_systems().map_err(EngineError::system)?;
+        self.take_system_error()?;
+        self.update_final_phase().map_err(EngineError::audio)?;
+        let flag = self.world.read_resource::<GameStateFlagRes>().flag;
+        if let Some(f) = flag {
+            match f {
+                GameStateFlag::Victory => self.victory(),
+                GameStateFlag::Defeat => self.defeat(),
+            }?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn apply_playback(&mut self) {
+        let (inputs, next) = match &mut self.playback {
+            Some(playback) => playback,
+            None => return,
+        };
+        let elapsed = self
+            .world
+            .read_resource::<GameMetrics>()
+            .elapsed
+            .as_millis() as u64;
+        let mut pressed_keys = self.world.write_resource::<PressedKeys>();
+        while let Some(input) = inputs
+            .get(*next)
+            .filter(|input| input.elapsed_ms <= elapsed)
+        {
+            if let Some(keys) = pressed_keys.pressed_keys.get_mut(input.player as usize) {
+                if input.pressed {
+                    keys.add(input.key);
+                } else {
+                    keys.remove(input.key);
+                }
+            }
+            *next += 1;
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn draw(&mut self, window: &mut Window) -> EngineResult<()> {
+        window
+            .clear_letterbox_color(Color::WHITE, get_tint_color(self.config.letterbox_color))
+            .map_err(EngineError::system)?;
+        if !self.config.resize_play_field {
+            let field_size = self.world.read_resource::<PlayField>().size;
+            window.set_view(View::new(get_letterbox_view(
+                window.screen_size(),
+                field_size,
+            )));
+        }
 
-        let loaded_assets = self.has_loaded_atlas()? && self.has_loaded_font()?;
+        let loaded_assets = self.has_loaded_atlas().map_err(EngineError::asset)?
+            && self.has_loaded_font().map_err(EngineError::asset)?;
         if !loaded_assets {
             return Ok(());
         } else if loaded_assets && self.state == GameState::WaitingInput {
-            return self.font.borrow_mut().execute(|font| {
-                let rendered_label = font.render(
-                    "Press ENTER to start...",
-                    &FontStyle::new(72.0, Color::BLACK),
-                )?;
-                window.draw(
-                    &rendered_label.area().with_center((400, 300)),
-                    Img(&rendered_label),
-                );
-                Ok(())
-            });
+            let field_size = self.world.read_resource::<PlayField>().size;
+            return self
+                .font
+                .borrow_mut()
+                .execute(|font| {
+                    let rendered_label = font.render(
+                        "Press ENTER to start...",
+                        &FontStyle::new(72.0, Color::BLACK),
+                    )?;
+                    window.draw(
+                        &rendered_label.area().with_center(field_size * 0.5),
+                        Img(&rendered_label),
+                    );
+                    Ok(())
+                })
+                .map_err(EngineError::asset);
         } else if loaded_assets && self.state == GameState::Initialiazing {
             log::debug!("Starting game...");
             self.state = GameState::Running;
+            if self.config.show_control_hints {
+                create_control_hints(&mut self.world, self.config.control_hints_ms);
+            }
         }
 
-        RenderSystem::new(window, Rc::clone(&self.atlas))?.run_now(&self.world.res);
+        let mut render_system =
+            RenderSystem::new(window, Rc::clone(&self.atlas)).map_err(EngineError::system)?;
+        render_system.run_now(&self.world.res);
+        if let Some(error) = render_system.take_error() {
+            return Err(EngineError::asset(error));
+        }
+        let dim_alpha = self.dim_alpha();
+        if dim_alpha > 0.0 {
+            window.draw(
+                &Rectangle::new_sized(self.world.read_resource::<PlayField>().size),
+                Col(Color::BLACK.with_alpha(dim_alpha)),
+            );
+        }
         if self.state == GameState::Running {
-            self.update_labels(window)?;
+            self.update_labels(window).map_err(EngineError::system)?;
         }
-        if self.state == GameState::Running || self.state == GameState::Paused {
-            LabelRenderSystem::new(window, Rc::clone(&self.font))?.run_now(&self.world.res);
+        if self.state != GameState::Initialiazing {
+            let mut label_render_system = LabelRenderSystem::new(window, Rc::clone(&self.font))
+                .map_err(EngineError::system)?;
+            label_render_system.run_now(&self.world.res);
+            if let Some(error) = label_render_system.take_error() {
+                return Err(EngineError::asset(error));
+            }
         }
+        self.draw_letterbox(window).map_err(EngineError::asset)?;
         self.world.maintain();
         Ok(())
     }
 
-    pub fn event(&mut self, event: &Event, window: &mut Window) -> Result<()> {
+    #[allow(clippy::result_large_err)]
+    fn draw_letterbox(&self, window: &mut Window) -> Result<()> {
+        let field_size = self.world.read_resource::<PlayField>().size;
+        let bars = get_letterbox_bars(window.screen_size(), field_size);
+        let color = get_tint_color(self.config.letterbox_color);
+        for bar in bars.iter() {
+            window.draw(bar, Col(color));
+        }
+        let sprite = match &self.config.letterbox_sprite {
+            Some(sprite) if !bars.is_empty() => sprite,
+            _ => return Ok(()),
+        };
+        self.atlas.borrow_mut().execute(|atlas| {
+            let image = match atlas.get(sprite) {
+                Some(AtlasItem::Image(image)) => image,
+                _ => return Err(Error::ContextError(format!("Missing sprite {}", sprite))),
+            };
+            for bar in bars.iter() {
+                window.draw(bar, Img(&image));
+            }
+            Ok(())
+        })
+    }
+
+    pub fn event(&mut self, event: &Event, window: &mut Window) -> EngineResult<()> {
+        if let Event::Key(_, _) | Event::GamepadButton(_, _, _) = event {
+            let elapsed = self.world.read_resource::<GameMetrics>().elapsed;
+            self.world
+                .write_resource::<InputHistory>()
+                .push(elapsed, format!("{:?}", event));
+        }
         match self.state {
-            GameState::WaitingInput => match event {
-                Event::Key(Key::Return, ButtonState::Pressed) => {
+            GameState::WaitingInput => {
+                if let Event::Key(Key::Return, ButtonState::Pressed) = event {
                     self.state = GameState::Initialiazing;
                 }
-                _ => {}
-            },
+            }
             GameState::Running | GameState::Paused => {
-                let mut pressed_keys = self.world.write_resource::<PressedKeys>();
-                let pressed_keys = &mut pressed_keys.pressed_keys;
+                if self.rebind_menu.is_some() {
+                    self.rebind_event(event);
+                    return Ok(());
+                }
+                self.key_binding_event(event);
+                if self.quit_prompt.is_some() {
+                    match event {
+                        Event::Key(Key::Y, ButtonState::Pressed) => self.confirm_quit(),
+                        Event::Key(Key::N, ButtonState::Pressed)
+                        | Event::Key(Key::Escape, ButtonState::Pressed) => self.cancel_quit(),
+                        _ => {}
+                    };
+                    return Ok(());
+                }
                 match event {
-                    Event::Key(Key::Up, ButtonState::Pressed)
-                    | Event::Key(Key::W, ButtonState::Pressed)
-                    | Event::GamepadButton(_, GamepadButton::DpadUp, ButtonState::Pressed) => {
-                        pressed_keys.add(KeyboardKeys::KeyUp as u32);
-                    }
-                    Event::Key(Key::Up, ButtonState::Released)
-                    | Event::Key(Key::W, ButtonState::Released)
-                    | Event::GamepadButton(_, GamepadButton::DpadUp, ButtonState::Released) => {
-                        pressed_keys.remove(KeyboardKeys::KeyUp as u32);
-                    }
-                    Event::Key(Key::Left, ButtonState::Pressed)
-                    | Event::Key(Key::A, ButtonState::Pressed)
-                    | Event::GamepadButton(_, GamepadButton::DpadLeft, ButtonState::Pressed) => {
-                        pressed_keys.add(KeyboardKeys::KeyLeft as u32);
-                    }
-                    Event::Key(Key::Left, ButtonState::Released)
-                    | Event::Key(Key::A, ButtonState::Released)
-                    | Event::GamepadButton(_, GamepadButton::DpadLeft, ButtonState::Released) => {
-                        pressed_keys.remove(KeyboardKeys::KeyLeft as u32);
-                    }
-                    Event::Key(Key::Right, ButtonState::Pressed)
-                    | Event::Key(Key::D, ButtonState::Pressed)
-                    | Event::GamepadButton(_, GamepadButton::DpadRight, ButtonState::Pressed) => {
-                        pressed_keys.add(KeyboardKeys::KeyRight as u32);
-                    }
-                    Event::Key(Key::Right, ButtonState::Released)
-                    | Event::Key(Key::D, ButtonState::Released)
-                    | Event::GamepadButton(_, GamepadButton::DpadRight, ButtonState::Released) => {
-                        pressed_keys.remove(KeyboardKeys::KeyRight as u32);
-                    }
                     Event::Key(Key::P, ButtonState::Pressed)
                     | Event::Key(Key::Pause, ButtonState::Pressed)
                     | Event::GamepadButton(_, GamepadButton::Start, ButtonState::Pressed) => {
                         if self.state == GameState::Running {
-                            self.state = GameState::Paused;
-                        } else if self.state == GameState::Paused {
-                            self.state = GameState::Running;
+                            self.pause();
+                        } else {
+                            self.resume();
                         }
                     }
+                    Event::Key(Key::F1, ButtonState::Pressed) if self.is_paused() => {
+                        self.open_rebind_menu();
+                    }
+                    Event::Key(Key::F5, ButtonState::Pressed)
+                        if self.config.practice_checkpoints =>
+                    {
+                        self.save_checkpoint();
+                    }
+                    Event::Key(Key::F9, ButtonState::Pressed)
+                        if self.config.practice_checkpoints =>
+                    {
+                        self.rewind_to_checkpoint();
+                    }
                     _ => {}
                 };
 
                 if let Event::Key(Key::Escape, ButtonState::Pressed) = event {
-                    let mut flag = self.world.write_resource::<GameStateFlagRes>();
-                    *flag = GameStateFlagRes {
-                        flag: Some(GameStateFlag::Defeat),
-                    };
+                    if self.config.confirm_quit {
+                        self.request_quit();
+                    } else {
+                        self.confirm_quit();
+                    }
                 }
             }
             GameState::GameOver => {
+                if self.name_entry.is_some() {
+                    return self.name_entry_event(event).map_err(EngineError::system);
+                }
                 if let Event::Key(Key::Escape, ButtonState::Pressed)
                 | Event::Key(Key::Return, ButtonState::Pressed)
                 | Event::GamepadButton(_, GamepadButton::Start, ButtonState::Pressed) = event
@@ -265,174 +746,2381 @@ impl Scene {
         Ok(())
     }
 
-    fn run_update_systems(&mut self) -> Result<()> {
-        HeroControlSystem.run_now(&self.world.res);
-        WalkSystem.run_now(&self.world.res);
-        FireballSystem.run_now(&self.world.res);
-        CollisionSystem.run_now(&self.world.res);
-        OutOfBoundsSystem.run_now(&self.world.res);
-        HeroBlinkingSystem.run_now(&self.world.res);
+    fn key_binding_event(&mut self, event: &Event) {
+        let two_players = self.config.player_two_config.is_some();
+        let key_binding = get_key_binding(
+            event,
+            &self.world.read_resource::<KeyBindings>(),
+            two_players,
+        );
+        if let Some((player, key, state)) = key_binding {
+            let changed = matches!(state, ButtonState::Pressed | ButtonState::Released);
+            if changed && self.config.record_replay {
+                let elapsed = self.world.read_resource::<GameMetrics>().elapsed;
+                self.replay.record(
+                    elapsed.as_millis() as u64,
+                    player as u32,
+                    key as u32,
+                    state == ButtonState::Pressed,
+                );
+            }
+            let mut pressed_keys = self.world.write_resource::<PressedKeys>();
+            let pressed_keys = &mut pressed_keys.pressed_keys[player as usize];
+            match state {
+                ButtonState::Pressed => {
+                    pressed_keys.add(key as u32);
+                }
+                ButtonState::Released => {
+                    pressed_keys.remove(key as u32);
+                }
+                _ => {}
+            };
+        }
+    }
+
+    pub fn is_final_phase(&self) -> bool {
+        let boss = self.world.read_storage::<Boss>();
+        (&boss).join().any(|boss| boss.enraged)
+    }
+
+    pub fn system_timings(&self) -> Vec<(&'static str, Duration)> {
+        self.world.read_resource::<SystemProfile>().timings.clone()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn export_replay(&self, path: impl AsRef<std::path::Path>) -> EngineResult<()> {
+        crate::persistence::save(&self.replay, path).map_err(EngineError::system)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn import_replay(&mut self, path: impl AsRef<std::path::Path>) -> EngineResult<()> {
+        self.replay = crate::persistence::load(path).map_err(EngineError::system)?;
         Ok(())
     }
 
-    fn entity_factory(&mut self) -> Result<()> {
-        if self.cycle_counter < self.config.boss_cycle {
-            if self.cycle_timer == 0 {
-                self.music_player
-                    .play_music(self.config.normal_music.clone())?;
+    #[cfg(target_arch = "wasm32")]
+    pub fn export_replay(&self) -> EngineResult<String> {
+        crate::persistence::serialize(&self.replay).map_err(EngineError::system)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn import_replay(&mut self, data: &str) -> EngineResult<()> {
+        self.replay = crate::persistence::deserialize(data).map_err(EngineError::system)?;
+        Ok(())
+    }
+
+    pub fn high_scores(&self) -> Vec<ScoreEntry> {
+        self.high_scores.entries.clone()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn export_high_scores(&self) -> EngineResult<String> {
+        crate::persistence::serialize(&self.high_scores).map_err(EngineError::system)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn import_high_scores(&mut self, data: &str) -> EngineResult<()> {
+        self.high_scores = crate::persistence::deserialize(data).map_err(EngineError::system)?;
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn name_entry_event(&mut self, event: &Event) -> Result<()> {
+        let (mut name_entry, score) = match self.name_entry {
+            Some(name_entry) => name_entry,
+            None => return Ok(()),
+        };
+        match event {
+            Event::Key(Key::Up, ButtonState::Pressed)
+            | Event::Key(Key::W, ButtonState::Pressed)
+            | Event::GamepadButton(_, GamepadButton::DpadUp, ButtonState::Pressed) => {
+                name_entry.next_letter();
             }
-            self.cycle_timer += 1;
-            if self.cycle_timer % self.config.new_body_cycle == 0 {
-                self.cycle_counter += 1;
-                if self.cycle_counter == self.config.boss_cycle {
-                    self.music_player
-                        .play_music(self.config.boss_music.clone())?;
-                    crate::enemy::create_boss(&mut self.world, self.config.boss_config.clone());
-                } else {
-                    self.entity_factory.create_entity(&mut self.world)?;
-                }
+            Event::Key(Key::Down, ButtonState::Pressed)
+            | Event::Key(Key::S, ButtonState::Pressed)
+            | Event::GamepadButton(_, GamepadButton::DpadDown, ButtonState::Pressed) => {
+                name_entry.previous_letter();
             }
+            Event::Key(Key::Return, ButtonState::Pressed)
+            | Event::Key(Key::Space, ButtonState::Pressed)
+            | Event::GamepadButton(_, GamepadButton::FaceDown, ButtonState::Pressed)
+            | Event::GamepadButton(_, GamepadButton::Start, ButtonState::Pressed)
+                if name_entry.confirm() =>
+            {
+                self.name_entry = None;
+                self.set_label_visible(LabelVariable::NameEntry, false);
+                return self.save_high_score(ScoreEntry {
+                    name: name_entry.name(),
+                    score,
+                });
+            }
+            _ => {}
         }
+        self.name_entry = Some((name_entry, score));
+        self.world
+            .write_resource::<VariableDictionary>()
+            .dictionary
+            .insert(LabelVariable::NameEntry, name_entry.display());
         Ok(())
     }
 
-    fn defeat(&mut self) -> Result<()> {
-        log::debug!("Player has been defeated");
-        self.end_game()?;
-        create_background(&mut self.world, self.config.defeat_background.clone());
-        self.music_player
-            .play_music(self.config.game_over_music.clone())?;
+    #[allow(clippy::result_large_err)]
+    fn save_high_score(&mut self, entry: ScoreEntry) -> Result<()> {
+        if self.high_scores.insert(entry).is_none() {
+            return Ok(());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(path) = &self.config.high_score_path {
+                crate::persistence::save(&self.high_scores, path)?;
+            }
+        }
         Ok(())
     }
 
-    fn victory(&mut self) -> Result<()> {
-        log::debug!("Player is victorious");
-        self.end_game()?;
-        create_background(&mut self.world, self.config.victory_background.clone());
+    pub fn settings(&self) -> Settings {
+        Settings {
+            music_volume: self.music_player.volume(),
+            key_bindings: self.key_bindings(),
+            ..Settings::from_config(&self.config)
+        }
+    }
+
+    pub fn apply_settings(&mut self, settings: Settings) -> EngineResult<()> {
+        let previous_difficulty = self.config.difficulty;
+        settings.apply(&mut self.config);
+        self.world.write_resource::<DifficultyRes>().multiplier +=
+            self.config.difficulty - previous_difficulty;
+        self.entity_factory
+            .select_spawn_table(self.config.difficulty);
         self.music_player
-            .play_music(self.config.victory_music.clone())?;
+            .set_volume(settings.music_volume)
+            .map_err(EngineError::audio)?;
+        *self.world.write_resource::<KeyBindings>() = settings.key_bindings;
+        self.world.write_resource::<ScreenShake>().intensity = settings.shake_intensity;
+        self.world.write_resource::<BulletAssist>().factor = settings.bullet_assist;
         Ok(())
     }
 
-    fn end_game(&mut self) -> Result<()> {
-        self.world.delete_all();
-        self.state = GameState::GameOver;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_settings(&self) -> EngineResult<()> {
+        if let Some(path) = &self.config.settings_path {
+            crate::persistence::save(&self.settings(), path).map_err(EngineError::system)?;
+        }
         Ok(())
     }
 
-    fn update_labels(&mut self, window: &Window) -> Result<()> {
-        let hero_storage = self.world.read_storage::<Hero>();
-        if let Some(hero) = hero_storage.get(self.hero) {
-            let mut dict = self.world.write_resource::<VariableDictionary>();
-            *dict = VariableDictionary {
-                dictionary: [
-                    (
-                        LabelVariable::FramesPerSecond,
-                        format!("{:.0}", window.average_fps()),
-                    ),
-                    (LabelVariable::HeroLives, format!("{}", hero.lives)),
-                    (LabelVariable::Score, format!("{}", hero.score)),
-                    (
-                        LabelVariable::EngineVersion,
-                        format!("v{}", env!("CARGO_PKG_VERSION")),
-                    ),
-                ]
-                .iter()
-                .cloned()
-                .collect(),
-            }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_settings(&mut self) -> EngineResult<()> {
+        if let Some(path) = self.config.settings_path.clone() {
+            let settings = crate::persistence::load(path).map_err(EngineError::system)?;
+            self.apply_settings(settings)?;
         }
         Ok(())
     }
 
-    fn has_loaded_atlas(&mut self) -> Result<bool> {
-        let mut loaded_atlas =
-            self.state != GameState::WaitingInput && self.state != GameState::Initialiazing;
-        if !loaded_atlas {
-            self.atlas.borrow_mut().execute(|_| {
-                loaded_atlas = true;
-                Ok(())
-            })?;
+    #[cfg(target_arch = "wasm32")]
+    pub fn save_settings(&self) -> EngineResult<String> {
+        crate::persistence::serialize(&self.settings()).map_err(EngineError::system)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load_settings(&mut self, data: &str) -> EngineResult<()> {
+        let settings = crate::persistence::deserialize(data).map_err(EngineError::system)?;
+        self.apply_settings(settings)
+    }
+
+    pub fn key_bindings(&self) -> KeyBindings {
+        self.world.read_resource::<KeyBindings>().clone()
+    }
+
+    pub fn is_rebind_menu_shown(&self) -> bool {
+        self.rebind_menu.is_some()
+    }
+
+    fn rebind_entries(&self) -> Vec<(PlayerId, Action)> {
+        let mut players = vec![PlayerId::One];
+        if self.config.player_two_config.is_some() {
+            players.push(PlayerId::Two);
         }
-        Ok(loaded_atlas)
+        players
+            .into_iter()
+            .flat_map(|player| Action::ALL.iter().map(move |&action| (player, action)))
+            .collect()
     }
 
-    fn has_loaded_font(&mut self) -> Result<bool> {
-        let mut loaded_font =
-            self.state != GameState::WaitingInput && self.state != GameState::Initialiazing;
-        if !loaded_font {
-            self.font.borrow_mut().execute(|_| {
-                loaded_font = true;
-                Ok(())
-            })?;
+    fn open_rebind_menu(&mut self) {
+        let label = create_centered_label(
+            &mut self.world,
+            LabelVariable::Rebind,
+            FontStyle::new(36.0, Color::WHITE),
+            Vector::new(0, 0),
+        );
+        self.rebind_menu = Some(RebindMenu {
+            selected: 0,
+            waiting: false,
+            label,
+        });
+        self.update_rebind_label(None);
+    }
+
+    fn close_rebind_menu(&mut self) {
+        if let Some(rebind_menu) = self.rebind_menu.take() {
+            let _ = self.world.delete_entity(rebind_menu.label);
         }
-        Ok(loaded_font)
     }
-}
 
-fn register_components(world: &mut World) {
-    world.register::<Position>();
-    world.register::<Velocity>();
-    world.register::<Render>();
-    world.register::<Shooter>();
-    world.register::<Label>();
-    world.register::<Hero>();
-    world.register::<Boss>();
-    world.register::<ChangeSprite>();
-    world.register::<Enemy>();
-    world.register::<Healing>();
-    world.register::<Background>();
-    world.register::<CalculateOutOfBounds>();
-    world.register::<Fireball>();
-}
+    fn rebind_event(&mut self, event: &Event) {
+        let mut rebind_menu = match self.rebind_menu {
+            Some(rebind_menu) => rebind_menu,
+            None => return,
+        };
+        let entries = self.rebind_entries();
+        let (player, action) = entries[rebind_menu.selected];
+        let mut warning = None;
+        if rebind_menu.waiting {
+            if let Event::Key(Key::Escape, ButtonState::Pressed) = event {
+                rebind_menu.waiting = false;
+            } else if let Some((input, _, ButtonState::Pressed)) = get_input_name(event) {
+                if RESERVED_INPUTS.contains(&input.as_str()) {
+                    warning = Some(format!("{} is reserved", input));
+                } else {
+                    let result = self
+                        .world
+                        .write_resource::<KeyBindings>()
+                        .rebind(player, action, &input);
+                    match result {
+                        Ok(()) => rebind_menu.waiting = false,
+                        Err(conflict) => {
+                            warning = Some(format!(
+                                "{} is already bound to {:?} {:?}",
+                                input, conflict.player, conflict.action
+                            ));
+                        }
+                    }
+                }
+            } else {
+                return;
+            }
+        } else {
+            match event {
+                Event::Key(Key::Up, ButtonState::Pressed)
+                | Event::GamepadButton(_, GamepadButton::DpadUp, ButtonState::Pressed) => {
+                    rebind_menu.selected =
+                        (rebind_menu.selected + entries.len() - 1) % entries.len();
+                }
+                Event::Key(Key::Down, ButtonState::Pressed)
+                | Event::GamepadButton(_, GamepadButton::DpadDown, ButtonState::Pressed) => {
+                    rebind_menu.selected = (rebind_menu.selected + 1) % entries.len();
+                }
+                Event::Key(Key::Return, ButtonState::Pressed)
+                | Event::GamepadButton(_, GamepadButton::FaceDown, ButtonState::Pressed) => {
+                    rebind_menu.waiting = true;
+                }
+                Event::Key(Key::Escape, ButtonState::Pressed)
+                | Event::Key(Key::F1, ButtonState::Pressed)
+                | Event::GamepadButton(_, GamepadButton::Start, ButtonState::Pressed) => {
+                    self.close_rebind_menu();
+                    return;
+                }
+                _ => return,
+            }
+        }
+        self.rebind_menu = Some(rebind_menu);
+        self.update_rebind_label(warning);
+    }
 
-fn add_resorces(world: &mut World) {
-    world.add_resource(GameStateFlagRes { flag: None });
-    world.add_resource(VariableDictionary {
-        dictionary: [
-            (LabelVariable::FramesPerSecond, "60".to_string()),
-            (LabelVariable::HeroLives, "5".to_string()),
-            (LabelVariable::Score, "0".to_string()),
-            (
-                LabelVariable::EngineVersion,
-                format!("v{}", env!("CARGO_PKG_VERSION")),
-            ),
-        ]
-        .iter()
-        .cloned()
-        .collect(),
-    });
-    world.add_resource(PressedKeys {
-        pressed_keys: BitSet::new(),
-    });
-}
+    fn update_rebind_label(&mut self, warning: Option<String>) {
+        let rebind_menu = match self.rebind_menu {
+            Some(rebind_menu) => rebind_menu,
+            None => return,
+        };
+        let (player, action) = self.rebind_entries()[rebind_menu.selected];
+        let text = match warning {
+            Some(warning) => warning,
+            None if rebind_menu.waiting => format!("{:?} {:?}: press a key...", player, action),
+            None => {
+                let bindings = self.world.read_resource::<KeyBindings>();
+                format!(
+                    "{:?} {:?}: {} / {}",
+                    player,
+                    action,
+                    bindings.get_input(player, action, false).unwrap_or("-"),
+                    bindings.get_input(player, action, true).unwrap_or("-"),
+                )
+            }
+        };
+        self.world
+            .write_resource::<VariableDictionary>()
+            .dictionary
+            .insert(LabelVariable::Rebind, text);
+    }
 
-fn create_background(world: &mut World, sprite: String) -> Entity {
-    world
-        .create_entity()
-        .with(Background)
-        .with(Position {
-            position: Vector::new(400, 300),
-        })
-        .with(Render {
-            sprite,
-            bounding_box: None,
-        })
-        .build()
-}
+    pub fn save_checkpoint(&mut self) {
+        log::debug!("Saving practice checkpoint");
+        self.checkpoint = Some(Checkpoint {
+            snapshot: WorldSnapshot::take(&self.world),
+            cycle_timer: self.cycle_timer,
+            cycle_counter: self.cycle_counter,
+            awaiting_boss_kills: self.awaiting_boss_kills,
+            spawn_timer: self.spawn_timer,
+            pacifist: *self.world.read_resource::<Pacifist>(),
+        });
+    }
 
-fn create_label(
-    world: &mut World,
-    variable: LabelVariable,
-    font_style: FontStyle,
-    position: Vector,
-) -> Entity {
-    world
-        .create_entity()
-        .with(Label {
-            bind_variable: variable,
-            font_style,
-        })
-        .with(Position { position })
-        .build()
+    pub fn rewind_to_checkpoint(&mut self) -> bool {
+        let checkpoint = match &self.checkpoint {
+            Some(checkpoint) => checkpoint.clone(),
+            None => return false,
+        };
+        log::debug!("Rewinding to practice checkpoint");
+        if let Some((_, banner)) = self.boss_intro.take() {
+            let _ = self.world.delete_entity(banner);
+        }
+        checkpoint.snapshot.restore(&mut self.world);
+        self.cycle_timer = checkpoint.cycle_timer;
+        self.cycle_counter = checkpoint.cycle_counter;
+        self.awaiting_boss_kills = checkpoint.awaiting_boss_kills;
+        self.spawn_timer = checkpoint.spawn_timer;
+        *self.world.write_resource::<Pacifist>() = checkpoint.pacifist;
+        self.wave_intro = None;
+        self.reset_background_stage();
+        self.world.write_resource::<PressedKeys>().pressed_keys = Default::default();
+        self.world.maintain();
+        true
+    }
+
+    fn reset_background_stage(&mut self) {
+        if self.config.background_stages.is_empty() {
+            return;
+        }
+        self.world.write_resource::<BackgroundStages>().current = 0;
+        let background = self.world.read_storage::<Background>();
+        for (render, _) in (&mut self.world.write_storage::<Render>(), &background).join() {
+            render.sprite = self.config.main_background.clone();
+        }
+    }
+
+    pub fn has_checkpoint(&self) -> bool {
+        self.checkpoint.is_some()
+    }
+
+    pub fn is_quit_prompt_shown(&self) -> bool {
+        self.quit_prompt.is_some()
+    }
+
+    fn request_quit(&mut self) {
+        let previous_state = self.state;
+        self.pause();
+        let prompt = create_centered_label(
+            &mut self.world,
+            LabelVariable::QuitPrompt,
+            FontStyle::new(72.0, Color::BLACK),
+            Vector::new(0, 0),
+        );
+        self.quit_prompt = Some((prompt, previous_state));
+    }
+
+    fn confirm_quit(&mut self) {
+        self.cancel_quit();
+        self.resume();
+        let mut flag = self.world.write_resource::<GameStateFlagRes>();
+        *flag = GameStateFlagRes {
+            flag: Some(GameStateFlag::Defeat),
+        };
+    }
+
+    fn cancel_quit(&mut self) {
+        if let Some((quit_prompt, previous_state)) = self.quit_prompt.take() {
+            let _ = self.world.delete_entity(quit_prompt);
+            self.state = previous_state;
+            self.reset_accumulator();
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.state == GameState::Running {
+            self.state = GameState::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.state == GameState::Paused {
+            self.state = GameState::Running;
+            self.reset_accumulator();
+        }
+    }
+
+    fn reset_accumulator(&mut self) {
+        if let Some(accumulator) = &mut self.accumulator {
+            accumulator.reset();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state == GameState::Paused
+    }
+
+    pub fn set_label_visible(&mut self, variable: LabelVariable, visible: bool) {
+        let mut label = self.world.write_storage::<Label>();
+        for label in (&mut label).join() {
+            if label.bind_variable == variable {
+                label.visible = visible;
+            }
+        }
+    }
+
+    pub fn min_window_size(&self) -> Option<(u32, u32)> {
+        self.config.min_window_size
+    }
+
+    pub fn resize(&mut self, size: Vector) -> bool {
+        let mut play_field = self.world.write_resource::<PlayField>();
+        if !self.config.resize_play_field || play_field.size == size {
+            return false;
+        }
+        play_field.size = size;
+        true
+    }
+
+    pub fn dim_alpha(&self) -> f32 {
+        if self.state == GameState::Paused {
+            self.config.pause_dim_alpha.clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn entity_stats(&self) -> EntityStats {
+        let enemy = self.world.read_storage::<Enemy>();
+        let boss = self.world.read_storage::<Boss>();
+        let fireball = self.world.read_storage::<Fireball>();
+        let particle = self.world.read_storage::<Particle>();
+
+        EntityStats {
+            enemies: (&enemy, !&boss).join().count(),
+            enemy_fireballs: (&fireball)
+                .join()
+                .filter(|fireball| fireball.faction == Faction::Enemy)
+                .count(),
+            hero_fireballs: (&fireball)
+                .join()
+                .filter(|fireball| fireball.faction == Faction::Hero)
+                .count(),
+            particles: (&particle).join().count(),
+            boss_alive: (&boss).join().next().is_some(),
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn run_update_systems(&mut self) -> Result<()> {
+        self.world.write_resource::<SystemProfile>().clear();
+        self.run_system("HeroControlSystem", HeroControlSystem);
+        self.run_system("DashEntranceSystem", DashEntranceSystem);
+        self.run_system("WalkSystem", WalkSystem);
+        self.run_system("MirrorHeroXSystem", MirrorHeroXSystem);
+        self.run_system("PickupMagnetSystem", PickupMagnetSystem);
+        if self.config.entity_factory_config.enemy_separation {
+            let strength = self.config.entity_factory_config.separation_strength;
+            self.run_system("SeparationSystem", SeparationSystem { strength });
+        }
+        if !self.world.read_resource::<AdaptiveQuality>().reduced {
+            self.run_system("TrailSystem", TrailSystem);
+        }
+        self.run_system("AfterimageSystem", AfterimageSystem);
+        self.run_system("AmbientSystem", AmbientSystem);
+        self.run_system("FireballSystem", FireballSystem);
+        self.run_system("MimicSystem", MimicSystem);
+        self.run_system("EmitterSystem", EmitterSystem);
+        self.run_system("ChargeAttackSystem", ChargeAttackSystem);
+        self.run_system("DiveSystem", DiveSystem);
+        self.run_system("BossHealerSystem", BossHealerSystem);
+        self.run_system("RiskZoneSystem", RiskZoneSystem);
+        self.run_system("ComboSystem", ComboSystem);
+        self.run_system("OverdriveSystem", OverdriveSystem);
+        if self.config.show_combo_meter {
+            self.run_system("ComboMeterSystem", ComboMeterSystem);
+        }
+        self.run_system("SelfShieldSystem", SelfShieldSystem);
+        self.run_system("HitFlashSystem", HitFlashSystem);
+        self.run_system("FreezeSystem", FreezeSystem);
+        self.run_system("FollowParentSystem", FollowParentSystem);
+        self.run_system("DroneSystem", DroneSystem);
+        self.run_system("BombSystem", BombSystem);
+        self.run_system("CollisionSystem", CollisionSystem);
+        self.run_system("WaveClearSystem", WaveClearSystem);
+        self.run_system("DamageOverTimeSystem", DamageOverTimeSystem);
+        self.run_system("KillSystem", KillSystem);
+        self.run_system("BossPhaseSystem", BossPhaseSystem);
+        self.run_system("OutOfBoundsSystem", OutOfBoundsSystem);
+        self.run_system("HeroBlinkingSystem", HeroBlinkingSystem);
+        self.run_system("LifetimeSystem", LifetimeSystem);
+        self.run_system("DespawnSystem", DespawnSystem);
+        self.run_system("ScreenShakeSystem", ScreenShakeSystem);
+        self.run_system("ScreenFlashSystem", ScreenFlashSystem);
+        if self.config.show_radar {
+            self.run_system("RadarSystem", RadarSystem);
+        }
+        if !self.config.music_layers.is_empty() {
+            self.run_system("ThreatSystem", ThreatSystem);
+        }
+        if self.config.edge_warning {
+            self.run_system("EdgeWarningSystem", EdgeWarningSystem);
+        }
+        self.run_system("SpawnPacingSystem", SpawnPacingSystem);
+        self.run_system("RubberBandSystem", RubberBandSystem);
+        if self.config.score_decay.is_some() {
+            self.run_system("ScoreDecaySystem", ScoreDecaySystem);
+        }
+        if !self.config.background_stages.is_empty() {
+            self.run_system("BackgroundStageSystem", BackgroundStageSystem);
+        }
+        self.run_system("MetricsSystem", MetricsSystem);
+        Ok(())
+    }
+
+    fn take_system_error(&mut self) -> EngineResult<()> {
+        let mut errors = self.world.write_resource::<SystemErrors>();
+        if errors.errors.is_empty() {
+            return Ok(());
+        }
+        let message = errors.errors.join("; ");
+        errors.errors.clear();
+        Err(EngineError::system(Error::ContextError(message)))
+    }
+
+    fn run_system<S>(&mut self, name: &'static str, mut system: S)
+    where
+        S: for<'a> RunNow<'a>,
+    {
+        if !self.world.read_resource::<SystemProfile>().enabled {
+            system.run_now(&self.world.res);
+            return;
+        }
+        let started = Instant::now();
+        system.run_now(&self.world.res);
+        let elapsed = Instant::now().duration_since(started);
+        self.world
+            .write_resource::<SystemProfile>()
+            .record(name, elapsed);
+    }
+
+    fn entity_factory(&mut self) -> EngineResult<()> {
+        if self.cycle_counter < self.config.boss_cycle || self.awaiting_boss_kills {
+            if self.cycle_timer == 0 {
+                self.music_player
+                    .play_music(self.config.normal_music.clone())
+                    .map_err(EngineError::audio)?;
+            }
+            self.cycle_timer += 1;
+            self.spawn_timer += 1;
+            if self.spawn_timer >= self.spawn_cycle() {
+                if self.hold_for_wave_intro() {
+                    return Ok(());
+                }
+                self.spawn_timer = 0;
+                if !self.awaiting_boss_kills {
+                    self.cycle_counter += 1;
+                }
+                let kills = self.world.read_resource::<GameMetrics>().kills;
+                if should_spawn_boss(
+                    self.cycle_counter,
+                    self.config.boss_cycle,
+                    kills,
+                    self.config.boss_kill_requirement,
+                ) {
+                    self.awaiting_boss_kills = false;
+                    self.spawn_boss().map_err(EngineError::audio)?;
+                } else if self.cycle_counter >= self.config.boss_cycle {
+                    self.awaiting_boss_kills = true;
+                    self.entity_factory
+                        .create_entity(&mut self.world)
+                        .map_err(EngineError::system)?;
+                } else {
+                    let spawn = self.cycle_counter - 1;
+                    if is_wave_start(spawn, self.config.spawns_per_wave) {
+                        self.world.write_resource::<WaveClear>().start();
+                        self.world.write_resource::<DifficultyRes>().multiplier +=
+                            self.config.difficulty_per_wave;
+                    }
+                    self.entity_factory
+                        .create_entity(&mut self.world)
+                        .map_err(EngineError::system)?;
+                    if is_wave_end(spawn, self.config.spawns_per_wave) {
+                        self.world.write_resource::<WaveClear>().spawning = false;
+                    }
+                }
+            }
+        } else if self.config.boss_rush && self.cycle_timer == 0 {
+            self.cycle_timer += 1;
+            self.spawn_boss().map_err(EngineError::audio)?;
+        } else if self.config.entity_factory_config.spawn_during_boss {
+            self.cycle_timer += 1;
+            let boss_spawn_cycle = self.config.new_body_cycle
+                * self
+                    .config
+                    .entity_factory_config
+                    .boss_spawn_cycle_factor
+                    .max(1);
+            if self.cycle_timer.is_multiple_of(boss_spawn_cycle) {
+                self.entity_factory
+                    .create_entity(&mut self.world)
+                    .map_err(EngineError::system)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn hold_for_wave_intro(&mut self) -> bool {
+        let spawns_per_wave = self.config.spawns_per_wave.max(1);
+        let wave = self.cycle_counter / spawns_per_wave + 1;
+        if !self.config.show_wave_intro
+            || self.awaiting_boss_kills
+            || !is_wave_start(self.cycle_counter, spawns_per_wave)
+            || self.cycle_counter + 1 >= self.config.boss_cycle
+        {
+            return false;
+        }
+        let remaining = match self.wave_intro {
+            Some(remaining) => remaining.saturating_sub(FRAME_DURATION.as_millis() as u64),
+            None => {
+                create_wave_intro(&mut self.world, self.config.wave_intro_ms);
+                self.config.wave_intro_ms
+            }
+        };
+        if remaining == 0 {
+            self.wave_intro = None;
+            return false;
+        }
+        self.wave_intro = Some(remaining);
+        self.world
+            .write_resource::<VariableDictionary>()
+            .dictionary
+            .insert(
+                LabelVariable::WaveIntro,
+                get_wave_intro_text(wave, remaining),
+            );
+        true
+    }
+
+    fn hold_for_boss_intro(&mut self) -> bool {
+        let (remaining, banner) = match self.boss_intro {
+            Some(boss_intro) => boss_intro,
+            None => return false,
+        };
+        let remaining = remaining.saturating_sub(FRAME_DURATION.as_millis() as u64);
+        if remaining == 0 {
+            self.boss_intro = None;
+            let _ = self.world.delete_entity(banner);
+            return false;
+        }
+        self.boss_intro = Some((remaining, banner));
+        true
+    }
+
+    fn frame_steps(&mut self) -> u32 {
+        match &mut self.accumulator {
+            Some(accumulator) => {
+                accumulator.tick(Instant::now());
+                accumulator.take_steps()
+            }
+            None => 1,
+        }
+    }
+
+    fn spawn_cycle(&self) -> u64 {
+        let pacing = self.world.read_resource::<SpawnPacing>();
+        let cycle = if pacing.enabled {
+            pacing.cycle
+        } else {
+            self.config.new_body_cycle as f32
+        };
+        let factor = self.world.read_resource::<RubberBand>().factor;
+        ((cycle / factor).round() as u64).max(1)
+    }
+
+    fn update_audio(&mut self) -> EngineResult<()> {
+        self.play_sound_effects().map_err(EngineError::audio)?;
+        self.music_player
+            .update(FRAME_DURATION)
+            .map_err(EngineError::audio)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn play_sound_effects(&mut self) -> Result<()> {
+        let sounds = std::mem::take(&mut self.world.write_resource::<SoundQueue>().sounds);
+        self.sfx_player.play_requests(sounds)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn update_final_phase(&mut self) -> Result<()> {
+        let final_phase_started = self.is_final_phase();
+        let mut final_phase = self.world.write_resource::<FinalPhase>();
+        if !final_phase_started || final_phase.music_started {
+            return Ok(());
+        }
+        final_phase.music_started = true;
+        if self.headless {
+            return Ok(());
+        }
+        if let Some(music) = self.config.final_phase_music.clone() {
+            self.music_player.play_music(music)?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn spawn_boss(&mut self) -> Result<()> {
+        if !self.headless {
+            self.music_player
+                .play_music(self.config.boss_music.clone())?;
+        }
+        crate::enemy::create_boss(&mut self.world, self.config.boss_config.clone());
+        if self.config.boss_config.boss_intro_ms > 0 {
+            let banner = create_boss_intro(&mut self.world);
+            self.boss_intro = Some((self.config.boss_config.boss_intro_ms, banner));
+        }
+        Ok(())
+    }
+
+    fn defeat(&mut self) -> EngineResult<()> {
+        log::debug!("Player has been defeated");
+        self.end_game().map_err(EngineError::system)?;
+        create_background(&mut self.world, self.config.defeat_background.clone());
+        if self.headless {
+            return Ok(());
+        }
+        self.music_player
+            .fade_to_music(
+                self.config.game_over_music.clone(),
+                Duration::from_millis(self.config.outcome_fade_ms),
+            )
+            .map_err(EngineError::audio)?;
+        Ok(())
+    }
+
+    fn victory(&mut self) -> EngineResult<()> {
+        log::debug!("Player is victorious");
+        self.award_pacifist_bonus();
+        self.end_game().map_err(EngineError::system)?;
+        create_background(&mut self.world, self.config.victory_background.clone());
+        if self.headless {
+            return Ok(());
+        }
+        self.music_player
+            .fade_to_music(
+                self.config.victory_music.clone(),
+                Duration::from_millis(self.config.outcome_fade_ms),
+            )
+            .map_err(EngineError::audio)?;
+        Ok(())
+    }
+
+    fn award_pacifist_bonus(&mut self) {
+        let bonus = match self.config.pacifist_bonus {
+            Some(bonus) => bonus,
+            None => return,
+        };
+        if !self.world.read_resource::<Pacifist>().eligible {
+            return;
+        }
+        log::debug!("Pacifist bonus awarded");
+        for hero in (&mut self.world.write_storage::<Hero>()).join() {
+            crate::hero::add_score(hero, bonus);
+        }
+    }
+
+    fn total_score(&self) -> i32 {
+        (&self.world.read_storage::<Hero>())
+            .join()
+            .map(|hero| hero.score)
+            .sum()
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn end_game(&mut self) -> Result<()> {
+        let score = self.total_score();
+        let outcome = self.world.read_resource::<GameStateFlagRes>().flag;
+        {
+            let mut metrics = self.world.write_resource::<GameMetrics>();
+            metrics.score = score;
+            metrics.outcome = outcome;
+        }
+        let metrics = *self.world.read_resource::<GameMetrics>();
+        self.world.delete_all();
+        self.state = GameState::GameOver;
+        self.music_player.set_active_layers(0);
+        if self.config.show_stats_screen {
+            create_stats_labels(
+                &mut self.world,
+                format_score(score, self.config.score_thousands_separator),
+                metrics,
+            );
+        }
+        if self.headless || !self.high_scores.qualifies(score) {
+            return Ok(());
+        }
+        if self.config.name_entry {
+            let name_entry = NameEntry::new();
+            self.world
+                .write_resource::<VariableDictionary>()
+                .dictionary
+                .insert(LabelVariable::NameEntry, name_entry.display());
+            create_centered_label(
+                &mut self.world,
+                LabelVariable::NameEntry,
+                FontStyle::new(48.0, Color::WHITE),
+                Vector::new(0, 150),
+            );
+            self.name_entry = Some((name_entry, score));
+        } else {
+            self.save_high_score(ScoreEntry {
+                name: crate::high_score::ANONYMOUS_NAME.to_string(),
+                score,
+            })?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn update_labels(&mut self, window: &Window) -> Result<()> {
+        update_ammo_labels(&self.world);
+        let hero_storage = self.world.read_storage::<Hero>();
+        let player_storage = self.world.read_storage::<PlayerId>();
+        let mut dict = self.world.write_resource::<VariableDictionary>();
+        dict.dictionary.insert(
+            LabelVariable::FramesPerSecond,
+            format!("{:.0}", window.average_fps()),
+        );
+        dict.dictionary
+            .insert(LabelVariable::HeroLives, "0".to_string());
+        dict.dictionary
+            .insert(LabelVariable::PlayerTwoLives, "0".to_string());
+        for (hero, player) in (&hero_storage, &player_storage).join() {
+            let (lives_variable, score_variable) = match player {
+                PlayerId::One => (LabelVariable::HeroLives, LabelVariable::Score),
+                PlayerId::Two => (LabelVariable::PlayerTwoLives, LabelVariable::PlayerTwoScore),
+            };
+            dict.dictionary
+                .insert(lives_variable, format!("{}", hero.lives));
+            dict.dictionary.insert(
+                score_variable,
+                format_score(hero.score, self.config.score_thousands_separator),
+            );
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn has_loaded_atlas(&mut self) -> Result<bool> {
+        let mut loaded_atlas =
+            self.state != GameState::WaitingInput && self.state != GameState::Initialiazing;
+        if !loaded_atlas {
+            self.atlas.borrow_mut().execute(|_| {
+                loaded_atlas = true;
+                Ok(())
+            })?;
+        }
+        Ok(loaded_atlas)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn has_loaded_font(&mut self) -> Result<bool> {
+        let mut loaded_font =
+            self.state != GameState::WaitingInput && self.state != GameState::Initialiazing;
+        if !loaded_font {
+            self.font.borrow_mut().execute(|_| {
+                loaded_font = true;
+                Ok(())
+            })?;
+        }
+        Ok(loaded_font)
+    }
+}
+
+pub(crate) fn create_world(config: &SceneConfig) -> World {
+    let mut world = World::new();
+    register_components(&mut world);
+    add_resorces(&mut world, config);
+    world
+}
+
+fn register_components(world: &mut World) {
+    world.register::<Position>();
+    world.register::<Velocity>();
+    world.register::<Render>();
+    world.register::<Shooter>();
+    world.register::<Label>();
+    world.register::<Hero>();
+    world.register::<Boss>();
+    world.register::<ChangeSprite>();
+    world.register::<Enemy>();
+    world.register::<Healing>();
+    world.register::<Background>();
+    world.register::<CalculateOutOfBounds>();
+    world.register::<Fireball>();
+    world.register::<Emitter>();
+    world.register::<PowerUp>();
+    world.register::<DropTable>();
+    world.register::<PlayerId>();
+    world.register::<ShieldAura>();
+    world.register::<Lifetime>();
+    world.register::<HeroWeapon>();
+    world.register::<DespawnAnim>();
+    world.register::<Health>();
+    world.register::<DamageOverTime>();
+    world.register::<Particle>();
+    world.register::<Trail>();
+    world.register::<MirrorHeroX>();
+    world.register::<Combo>();
+    world.register::<Dash>();
+    world.register::<BombDrop>();
+    world.register::<Anchor>();
+    world.register::<Focus>();
+    world.register::<Ambient>();
+    world.register::<SelfShield>();
+    world.register::<ShieldDonor>();
+    world.register::<OneHitShield>();
+    world.register::<HitFlash>();
+    world.register::<FreezeFor>();
+    world.register::<ChargeAttack>();
+    world.register::<FollowParent>();
+    world.register::<DroneHost>();
+    world.register::<Acceleration>();
+    world.register::<DashEntrance>();
+    world.register::<Dive>();
+    world.register::<BossHealer>();
+    world.register::<Overdrive>();
+    world.register::<SecondaryWeapon>();
+    world.register::<EntitySounds>();
+    world.register::<Mimic>();
+}
+
+pub fn run_headless(mut config: SceneConfig, replay: Replay) -> EngineResult<GameMetrics> {
+    if replay.seed.is_some() {
+        config.seed = replay.seed;
+    }
+    let max_elapsed = Duration::from_millis(config.headless_max_ms);
+    let mut scene = Scene::new(config).map_err(EngineError::system)?;
+    scene.headless = true;
+    scene.play_replay(replay);
+    while !scene.is_game_over() && scene.world.read_resource::<GameMetrics>().elapsed < max_elapsed
+    {
+        scene.step()?;
+    }
+    if !scene.is_game_over() {
+        let score = scene.total_score();
+        scene.world.write_resource::<GameMetrics>().score = score;
+    }
+    let metrics = *scene.world.read_resource::<GameMetrics>();
+    Ok(metrics)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_persisted<T: DeserializeOwned>(path: &mut Option<String>) -> Option<T> {
+    let result = crate::persistence::load_if_present(path.as_ref()?);
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            let path = path.take();
+            log::error!(
+                "Could not load {:?}, it will not be overwritten: {}",
+                path,
+                error
+            );
+            None
+        }
+    }
+}
+
+pub fn should_spawn_boss(
+    cycle_counter: u32,
+    boss_cycle: u32,
+    kills: u32,
+    kill_requirement: Option<u32>,
+) -> bool {
+    cycle_counter >= boss_cycle && kill_requirement.is_none_or(|required| kills >= required)
+}
+
+pub fn format_score(score: i32, separator: Option<char>) -> String {
+    let digits = score.unsigned_abs().to_string();
+    let separator = match separator {
+        Some(separator) => separator,
+        None => return score.to_string(),
+    };
+    let mut formatted = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(separator);
+        }
+        formatted.push(digit);
+    }
+    if score < 0 {
+        formatted.insert(0, '-');
+    }
+    formatted
+}
+
+pub fn get_asset_path(asset_base: &str, path: &str) -> String {
+    if asset_base.is_empty() || asset_base.ends_with('/') {
+        format!("{}{}", asset_base, path)
+    } else {
+        format!("{}/{}", asset_base, path)
+    }
+}
+
+fn apply_sudden_death(config: &mut SceneConfig) {
+    let heroes = std::iter::once(&mut config.hero_config).chain(config.player_two_config.as_mut());
+    for hero_config in heroes {
+        hero_config.lives = 1;
+        hero_config.allow_extra_lives = false;
+        hero_config.allow_shields = false;
+    }
+}
+
+pub fn get_letterbox_view(screen_size: Vector, field_size: Vector) -> Rectangle {
+    let scale = (screen_size.x / field_size.x).min(screen_size.y / field_size.y);
+    if !scale.is_finite() || scale <= 0.0 {
+        return Rectangle::new_sized(field_size);
+    }
+    let visible = screen_size / scale;
+    Rectangle::new((field_size - visible) / 2.0, visible)
+}
+
+pub fn get_letterbox_bars(screen_size: Vector, field_size: Vector) -> Vec<Rectangle> {
+    let view = get_letterbox_view(screen_size, field_size);
+    let gap = -view.pos;
+    let mut bars = Vec::new();
+    if gap.x > 0.0 {
+        bars.push(Rectangle::new((view.pos.x, 0.0), (gap.x, field_size.y)));
+        bars.push(Rectangle::new((field_size.x, 0.0), (gap.x, field_size.y)));
+    }
+    if gap.y > 0.0 {
+        bars.push(Rectangle::new((0.0, view.pos.y), (field_size.x, gap.y)));
+        bars.push(Rectangle::new((0.0, field_size.y), (field_size.x, gap.y)));
+    }
+    bars
+}
+
+fn get_tint_color(rgba: [f32; 4]) -> Color {
+    Color {
+        r: rgba[0],
+        g: rgba[1],
+        b: rgba[2],
+        a: rgba[3],
+    }
+}
+
+fn add_resorces(world: &mut World, config: &SceneConfig) {
+    world.add_resource(GameStateFlagRes { flag: None });
+    world.add_resource(VariableDictionary {
+        dictionary: [
+            (LabelVariable::FramesPerSecond, "60".to_string()),
+            (LabelVariable::HeroLives, "5".to_string()),
+            (LabelVariable::Score, "0".to_string()),
+            (LabelVariable::PlayerTwoLives, "0".to_string()),
+            (LabelVariable::PlayerTwoScore, "0".to_string()),
+            (LabelVariable::Announcement, String::new()),
+            (LabelVariable::WaveIntro, String::new()),
+            (LabelVariable::BossIntro, "Boss approaching!".to_string()),
+            (LabelVariable::SecondaryAmmo, String::new()),
+            (LabelVariable::PlayerTwoAmmo, String::new()),
+            (LabelVariable::QuitPrompt, "Quit? Y/N".to_string()),
+            (
+                LabelVariable::ControlHints,
+                get_control_hints(config.player_two_config.is_some()).to_string(),
+            ),
+            (
+                LabelVariable::EngineVersion,
+                format!("v{}", env!("CARGO_PKG_VERSION")),
+            ),
+            (
+                LabelVariable::Seed,
+                format!("Seed: {}", config.seed.unwrap_or_default()),
+            ),
+        ]
+        .iter()
+        .cloned()
+        .collect(),
+    });
+    world.add_resource(PressedKeys::default());
+    world.add_resource(config.key_bindings.clone());
+    world.add_resource(RandomGenerator::new(config.seed));
+    world.add_resource(GameMetrics::default());
+    world.add_resource(Radar::default());
+    world.add_resource(ComboMeter::default());
+    world.add_resource(EdgeWarning {
+        distance: config.edge_warning_distance,
+        ..EdgeWarning::default()
+    });
+    world.add_resource(DifficultyRes {
+        multiplier: config.difficulty,
+    });
+    world.add_resource(NoFireZones {
+        zones: config
+            .no_fire_zones
+            .iter()
+            .map(|zone| Rectangle::new(zone.position, zone.size))
+            .collect(),
+    });
+    world.add_resource(PlayField {
+        size: Vector::new(config.play_field_size.0, config.play_field_size.1),
+    });
+    world.add_resource(AdaptiveQuality {
+        enabled: config.adaptive_quality,
+        low_fps: config.low_quality_fps,
+        recover_fps: config.recover_quality_fps,
+        reduced: false,
+    });
+    world.add_resource(OutcomeTint {
+        defeat: config.defeat_tint.map(get_tint_color),
+        victory: config.victory_tint.map(get_tint_color),
+    });
+    world.add_resource(HeroShots::default());
+    world.add_resource(Kills::default());
+    world.add_resource(SystemErrors::default());
+    world.add_resource(Pacifist::default());
+    world.add_resource(SoundQueue::default());
+    world.add_resource(RubberBand::new(config.rubber_band));
+    world.add_resource(config.score_decay.map(ScoreDecay::new).unwrap_or_default());
+    world.add_resource(BackgroundStages::new(
+        &config.background_stages,
+        ScreenFlash::new(
+            config.background_fade_color.map(get_tint_color),
+            config.background_fade_ms,
+        ),
+    ));
+    world.add_resource(BulletAssist {
+        factor: config.bullet_assist,
+        radius: config.bullet_assist_radius,
+    });
+    world.add_resource(HitFlashSettings {
+        color: config.hit_flash_color.map(get_tint_color),
+        duration: Duration::from_millis(config.hit_flash_ms),
+    });
+    world.add_resource(HitFreeze {
+        freeze_ms: config.hit_freeze_ms,
+    });
+    world.add_resource(SpawnPacing::new(
+        config.entity_factory_config.spawn_pacing,
+        config.new_body_cycle,
+    ));
+    world.add_resource(InputHistory {
+        enabled: config.debug_mode,
+        capacity: config.input_history_size,
+        ..InputHistory::default()
+    });
+    world.add_resource(SystemProfile {
+        enabled: config.profile_systems,
+        show_overlay: config.debug_mode,
+        ..SystemProfile::default()
+    });
+    world.add_resource(WaveClear {
+        bonus: config.wave_clear_bonus,
+        ..WaveClear::default()
+    });
+    world.add_resource(ScreenShake {
+        curve: config.shake_curve,
+        intensity: config.shake_intensity,
+        duration: Duration::from_millis(config.shake_ms),
+        ..ScreenShake::default()
+    });
+    world.add_resource(Threat {
+        boss_weight: config.boss_threat,
+        ..Threat::default()
+    });
+    world.add_resource(FinalPhase {
+        tint: config.boss_config.final_phase_tint.map(get_tint_color),
+        shake: config.boss_config.final_phase_shake,
+        ..FinalPhase::default()
+    });
+    world.add_resource(ScreenFlash::new(
+        config.bomb_flash_color.map(get_tint_color),
+        config.bomb_flash_ms,
+    ));
+    world.add_resource(FireGrace {
+        duration: Duration::from_millis(config.entity_factory_config.fire_grace_ms),
+    });
+    world.add_resource(AnnouncementFilter {
+        suppress_during_boss: config.suppress_announcements_during_boss,
+    });
+}
+
+fn get_control_hints(two_players: bool) -> &'static str {
+    if two_players {
+        "P1: W A D, Space fire, Q weapon    P2: arrows, RCtrl fire, RShift weapon    P: pause"
+    } else {
+        "Move: A D / arrows    Jump: W / up    Fire: Space    Weapon: Q    P: pause"
+    }
+}
+
+fn create_control_hints(world: &mut World, duration_ms: u64) -> Entity {
+    let hints = create_centered_label(
+        world,
+        LabelVariable::ControlHints,
+        FontStyle::new(36.0, Color::BLACK),
+        Vector::new(0, -200),
+    );
+    set_label_lifetime(world, hints, duration_ms);
+    hints
+}
+
+fn update_ammo_labels(world: &World) {
+    let secondary_weapon = world.read_storage::<SecondaryWeapon>();
+    let player = world.read_storage::<PlayerId>();
+    let mut dict = world.write_resource::<VariableDictionary>();
+    for (secondary_weapon, player) in (&secondary_weapon, &player).join() {
+        let variable = match player {
+            PlayerId::One => LabelVariable::SecondaryAmmo,
+            PlayerId::Two => LabelVariable::PlayerTwoAmmo,
+        };
+        dict.dictionary
+            .insert(variable, format!("Ammo: {}", secondary_weapon.ammo));
+    }
+}
+
+fn create_wave_intro(world: &mut World, duration_ms: u64) -> Entity {
+    let intro = create_centered_label(
+        world,
+        LabelVariable::WaveIntro,
+        FontStyle::new(48.0, Color::BLACK),
+        Vector::new(0, -100),
+    );
+    set_label_lifetime(world, intro, duration_ms);
+    intro
+}
+
+fn create_boss_intro(world: &mut World) -> Entity {
+    create_centered_label(
+        world,
+        LabelVariable::BossIntro,
+        FontStyle::new(48.0, Color::RED),
+        Vector::new(0, -100),
+    )
+}
+
+fn set_label_lifetime(world: &mut World, label: Entity, duration_ms: u64) {
+    world
+        .write_storage::<Lifetime>()
+        .insert(
+            label,
+            Lifetime {
+                remaining: Duration::from_millis(duration_ms),
+            },
+        )
+        .unwrap();
+}
+
+pub fn is_wave_start(spawn: u32, spawns_per_wave: u32) -> bool {
+    spawn.is_multiple_of(spawns_per_wave.max(1))
+}
+
+pub fn is_wave_end(spawn: u32, spawns_per_wave: u32) -> bool {
+    is_wave_start(spawn + 1, spawns_per_wave)
+}
+
+pub fn get_wave_intro_text(wave: u32, remaining_ms: u64) -> String {
+    format!("Wave {} - {}", wave, remaining_ms.div_ceil(1000))
+}
+
+fn create_stats_labels(world: &mut World, score: String, metrics: GameMetrics) {
+    let seconds = metrics.elapsed.as_secs();
+    let stats = [
+        (LabelVariable::StatsScore, format!("Score: {}", score), -50),
+        (
+            LabelVariable::StatsTime,
+            format!("Time: {}:{:02}", seconds / 60, seconds % 60),
+            0,
+        ),
+        (
+            LabelVariable::StatsKills,
+            format!("Kills: {}", metrics.kills),
+            50,
+        ),
+    ];
+    for (variable, text, y) in stats.iter().cloned() {
+        world
+            .write_resource::<VariableDictionary>()
+            .dictionary
+            .insert(variable, text);
+        create_centered_label(
+            world,
+            variable,
+            FontStyle::new(48.0, Color::WHITE),
+            Vector::new(0, y),
+        );
+    }
+}
+
+fn get_input_name(event: &Event) -> Option<(String, Option<u32>, ButtonState)> {
+    match *event {
+        Event::Key(key, state) => Some((format!("Key:{:?}", key), None, state)),
+        Event::GamepadButton(id, button, state) => {
+            Some((format!("Pad:{:?}", button), Some(id as u32), state))
+        }
+        _ => None,
+    }
+}
+
+fn get_key_binding(
+    event: &Event,
+    bindings: &KeyBindings,
+    two_players: bool,
+) -> Option<(PlayerId, KeyboardKeys, ButtonState)> {
+    let (input, gamepad_id, state) = get_input_name(event)?;
+    let binding = bindings.find(&input)?;
+    let player = match gamepad_id {
+        Some(id) => get_gamepad_player(id),
+        None => binding.player,
+    };
+    let key = binding.action.keyboard_key();
+    if two_players {
+        Some((player, key, state))
+    } else {
+        Some((PlayerId::One, key, state))
+    }
+}
+
+fn get_gamepad_player(id: u32) -> PlayerId {
+    if id == 0 {
+        PlayerId::One
+    } else {
+        PlayerId::Two
+    }
+}
+
+fn create_background(world: &mut World, sprite: String) -> Entity {
+    world
+        .create_entity()
+        .with(Background)
+        .with(Position {
+            position: Vector::new(400, 300),
+        })
+        .with(Anchor {
+            corner: Corner::Center,
+            offset: Vector::new(0, 0),
+        })
+        .with(Render {
+            sprite,
+            bounding_box: None,
+        })
+        .build()
+}
+
+fn create_anchored_label(
+    world: &mut World,
+    variable: LabelVariable,
+    font_style: FontStyle,
+    corner: Corner,
+    offset: Vector,
+) -> Entity {
+    let anchor = Anchor { corner, offset };
+    let field_size = world.read_resource::<PlayField>().size;
+    let label = create_label(
+        world,
+        variable,
+        font_style,
+        crate::system::resolve_anchor(&anchor, field_size),
+    );
+    world
+        .write_storage::<Anchor>()
+        .insert(label, anchor)
+        .unwrap();
+    label
+}
+
+fn create_centered_label(
+    world: &mut World,
+    variable: LabelVariable,
+    font_style: FontStyle,
+    offset: Vector,
+) -> Entity {
+    create_anchored_label(world, variable, font_style, Corner::Center, offset)
+}
+
+fn create_label(
+    world: &mut World,
+    variable: LabelVariable,
+    font_style: FontStyle,
+    position: Vector,
+) -> Entity {
+    world
+        .create_entity()
+        .with(Label {
+            bind_variable: variable,
+            font_style,
+            visible: true,
+        })
+        .with(Position { position })
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{enemy::EmitterPattern, resources::SoundPriority, test_support::weapon_mode};
+
+    fn create_fireball(world: &mut World, faction: Faction) {
+        world
+            .create_entity()
+            .with(Fireball {
+                owner_id: None,
+                faction,
+                damage_over_time: None,
+                contact_damage: None,
+            })
+            .build();
+    }
+
+    #[test]
+    fn player_two_binding_moves_only_player_two() {
+        let scene = Scene::new(SceneConfig {
+            player_two_config: Some(HeroConfig::default()),
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        let event = Event::Key(Key::Right, ButtonState::Pressed);
+        let (player, key, _) =
+            get_key_binding(&event, &scene.world.read_resource::<KeyBindings>(), true).unwrap();
+        scene.world.write_resource::<PressedKeys>().pressed_keys[player as usize].add(key as u32);
+
+        HeroControlSystem.run_now(&scene.world.res);
+
+        let player = scene.world.read_storage::<PlayerId>();
+        let vel = scene.world.read_storage::<Velocity>();
+        for (player, vel) in (&player, &vel).join() {
+            match player {
+                PlayerId::One => assert_eq!(vel.velocity.x, 0.0),
+                PlayerId::Two => assert!(vel.velocity.x > 0.0),
+            }
+        }
+    }
+
+    fn run_frames<S: for<'a> RunNow<'a>>(world: &mut World, mut system: S, frames: u32) {
+        for _ in 0..frames {
+            system.run_now(&world.res);
+            world.maintain();
+        }
+    }
+
+    fn count_labels(world: &World, variable: LabelVariable) -> usize {
+        (&world.read_storage::<Label>())
+            .join()
+            .filter(|label| label.bind_variable == variable)
+            .count()
+    }
+
+    #[test]
+    fn control_hints_are_removed_after_duration() {
+        let mut scene = Scene::new(SceneConfig::default()).unwrap();
+        create_control_hints(&mut scene.world, 500);
+        assert_eq!(count_labels(&scene.world, LabelVariable::ControlHints), 1);
+
+        run_frames(&mut scene.world, LifetimeSystem, 29);
+        assert_eq!(count_labels(&scene.world, LabelVariable::ControlHints), 1);
+        run_frames(&mut scene.world, LifetimeSystem, 1);
+        assert_eq!(count_labels(&scene.world, LabelVariable::ControlHints), 0);
+    }
+
+    #[test]
+    fn game_over_stats_show_metrics_from_before_delete_all() {
+        let mut scene = Scene::new(SceneConfig {
+            show_stats_screen: true,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        {
+            let mut metrics = scene.world.write_resource::<GameMetrics>();
+            metrics.kills = 7;
+            metrics.elapsed = Duration::from_secs(75);
+        }
+        for hero in (&mut scene.world.write_storage::<Hero>()).join() {
+            hero.score = 120;
+        }
+
+        scene.end_game().unwrap();
+
+        assert_eq!(count_labels(&scene.world, LabelVariable::StatsKills), 1);
+        let dict = scene.world.read_resource::<VariableDictionary>();
+        assert_eq!(dict.dictionary[&LabelVariable::StatsScore], "Score: 120");
+        assert_eq!(dict.dictionary[&LabelVariable::StatsTime], "Time: 1:15");
+        assert_eq!(dict.dictionary[&LabelVariable::StatsKills], "Kills: 7");
+    }
+
+    fn count_enemies_spawned_during_boss(spawn_during_boss: bool) -> usize {
+        let mut config = SceneConfig::default();
+        config.entity_factory_config.spawn_during_boss = spawn_during_boss;
+        let mut scene = Scene::new(config).unwrap();
+        scene.cycle_counter = scene.config.boss_cycle;
+        scene.spawn_boss().unwrap();
+        let frames = scene.config.new_body_cycle
+            * scene.config.entity_factory_config.boss_spawn_cycle_factor
+            * 3;
+        for _ in 0..frames {
+            scene.entity_factory().unwrap();
+        }
+        scene.world.maintain();
+        scene.entity_stats().enemies
+    }
+
+    #[test]
+    fn spawn_during_boss_keeps_spawning_enemies() {
+        assert_eq!(count_enemies_spawned_during_boss(true), 3);
+        assert_eq!(count_enemies_spawned_during_boss(false), 0);
+    }
+
+    #[test]
+    fn pause_and_resume_only_toggle_a_running_game() {
+        let mut scene = Scene::new(SceneConfig::default()).unwrap();
+        scene.state = GameState::Running;
+        scene.pause();
+        assert!(scene.state == GameState::Paused);
+        scene.resume();
+        assert!(scene.state == GameState::Running);
+
+        scene.state = GameState::GameOver;
+        scene.pause();
+        assert!(scene.state == GameState::GameOver);
+    }
+
+    #[test]
+    fn entity_stats_counts_spawned_entities() {
+        let mut scene = Scene::new(SceneConfig::default()).unwrap();
+        let world = &mut scene.world;
+        world
+            .create_entity()
+            .with(Enemy {
+                score: 10,
+                despawn_ms: 0,
+                armor: None,
+                reflects: false,
+                weapon_drain: None,
+            })
+            .build();
+        create_fireball(world, Faction::Enemy);
+        create_fireball(world, Faction::Enemy);
+        create_fireball(world, Faction::Hero);
+        for _ in 0..3 {
+            world
+                .create_entity()
+                .with(Particle {
+                    color: Color::WHITE,
+                    radius: 1.0,
+                    duration: Duration::from_millis(100),
+                })
+                .build();
+        }
+
+        let stats = scene.entity_stats();
+        assert_eq!(stats.enemies, 1);
+        assert_eq!(stats.enemy_fireballs, 2);
+        assert_eq!(stats.hero_fireballs, 1);
+        assert_eq!(stats.particles, 3);
+        assert!(!stats.boss_alive);
+    }
+
+    #[test]
+    fn asset_base_is_prepended_to_asset_paths() {
+        assert_eq!(
+            get_asset_path("assets/", "music/normal.ogg"),
+            "assets/music/normal.ogg"
+        );
+        assert_eq!(
+            get_asset_path("assets", "music/normal.ogg"),
+            "assets/music/normal.ogg"
+        );
+        assert_eq!(get_asset_path("", "music/normal.ogg"), "music/normal.ogg");
+    }
+
+    #[test]
+    fn pause_dim_applies_only_while_paused() {
+        let json = serde_json::to_string(&SceneConfig {
+            pause_dim_alpha: 0.5,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        let config: SceneConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(config.pause_dim_alpha, 0.5);
+
+        let mut scene = Scene::new(config).unwrap();
+        scene.state = GameState::Running;
+        assert_eq!(scene.dim_alpha(), 0.0);
+        scene.pause();
+        assert_eq!(scene.dim_alpha(), 0.5);
+        scene.resume();
+        assert_eq!(scene.dim_alpha(), 0.0);
+    }
+
+    #[test]
+    fn score_is_formatted_with_thousands_separators() {
+        assert_eq!(format_score(1_234_567, Some(',')), "1,234,567");
+        assert_eq!(format_score(1_234_567, None), "1234567");
+        assert_eq!(format_score(-1_000, Some(',')), "-1,000");
+        assert_eq!(format_score(i32::MIN, Some(',')), "-2,147,483,648");
+    }
+
+    #[test]
+    fn failures_surface_as_engine_errors() {
+        let mut scene = Scene::new(SceneConfig::default()).unwrap();
+        scene
+            .world
+            .write_resource::<SoundQueue>()
+            .push(&Some("missing/sound.ogg".to_string()), SoundPriority::High);
+        assert!(matches!(scene.update_audio(), Err(EngineError::Audio(_))));
+
+        scene.state = GameState::Running;
+        let e = scene.world.create_entity().build();
+        scene.world.delete_entity(e).unwrap();
+        let error = scene.world.delete_entity(e).unwrap_err();
+        scene
+            .world
+            .write_resource::<SystemErrors>()
+            .errors
+            .push(error.to_string());
+        assert!(matches!(scene.step(), Err(EngineError::System(_))));
+        assert!(scene.step().is_ok());
+    }
+
+    #[test]
+    fn boss_rush_spawns_the_boss_on_the_first_frame() {
+        let mut scene = Scene::new(SceneConfig {
+            boss_rush: true,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        scene.state = GameState::Running;
+
+        scene.step().unwrap();
+
+        assert!(scene.entity_stats().boss_alive);
+        assert_eq!(scene.music_player.current_track(), Some("music/boss.ogg"));
+    }
+
+    #[test]
+    fn hidden_label_is_skipped_while_others_stay_visible() {
+        let mut scene = Scene::new(SceneConfig::default()).unwrap();
+
+        scene.set_label_visible(LabelVariable::FramesPerSecond, false);
+
+        let label = scene.world.read_storage::<Label>();
+        let visible: Vec<LabelVariable> = (&label)
+            .join()
+            .filter(|label| label.visible)
+            .map(|label| label.bind_variable)
+            .collect();
+        assert!(!visible.contains(&LabelVariable::FramesPerSecond));
+        assert!(visible.contains(&LabelVariable::Score));
+    }
+
+    #[test]
+    fn sudden_death_ends_the_run_on_the_first_hit() {
+        let mut scene = Scene::new(SceneConfig {
+            sudden_death: true,
+            hero_config: HeroConfig {
+                lives: 5,
+                allow_extra_lives: true,
+                ..HeroConfig::default()
+            },
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        let world = &mut scene.world;
+        let (e, hero, position) = {
+            let hero = world.read_storage::<Hero>();
+            let pos = world.read_storage::<Position>();
+            let (e, hero, pos) = (&world.entities(), &hero, &pos).join().next().unwrap();
+            (e, *hero, pos.position)
+        };
+        assert_eq!(hero.lives, 1);
+        assert!(!hero.allow_extra_lives);
+
+        world
+            .create_entity()
+            .with(Fireball {
+                owner_id: None,
+                faction: Faction::Enemy,
+                damage_over_time: None,
+                contact_damage: None,
+            })
+            .with(Position { position })
+            .with(Render {
+                sprite: "fogo".to_string(),
+                bounding_box: Some(Rectangle::new_sized((10, 10))),
+            })
+            .build();
+        let mut render = world.write_storage::<Render>();
+        render.get_mut(e).unwrap().bounding_box = Some(Rectangle::new_sized((40, 40)));
+        drop(render);
+        CollisionSystem.run_now(&world.res);
+
+        assert_eq!(world.read_storage::<Hero>().get(e).unwrap().lives, 0);
+        assert_eq!(
+            world.read_resource::<GameStateFlagRes>().flag,
+            Some(GameStateFlag::Defeat)
+        );
+    }
+
+    #[test]
+    fn resized_play_field_moves_centered_labels_and_bounds() {
+        let mut fixed = Scene::new(SceneConfig::default()).unwrap();
+        assert!(!fixed.resize(Vector::new(1024, 768)));
+
+        let mut scene = Scene::new(SceneConfig {
+            resize_play_field: true,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        assert!(scene.resize(Vector::new(1024, 768)));
+        assert!(!scene.resize(Vector::new(1024, 768)));
+
+        let hints = create_control_hints(&mut scene.world, 500);
+        let anchor = *scene.world.read_storage::<Anchor>().get(hints).unwrap();
+        let field_size = scene.world.read_resource::<PlayField>().size;
+        assert_eq!(
+            crate::system::resolve_anchor(&anchor, field_size),
+            Vector::new(512, 184)
+        );
+
+        let inside = scene
+            .world
+            .create_entity()
+            .with(Position {
+                position: Vector::new(950, 300),
+            })
+            .with(CalculateOutOfBounds)
+            .build();
+        scene.world.maintain();
+        OutOfBoundsSystem.run_now(&scene.world.res);
+        scene.world.maintain();
+        assert!(scene.world.is_alive(inside));
+    }
+
+    fn record_key_presses(record_replay: bool) -> Scene {
+        let mut scene = Scene::new(SceneConfig {
+            record_replay,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        for state in [ButtonState::Pressed, ButtonState::Released].iter() {
+            scene.key_binding_event(&Event::Key(Key::D, *state));
+        }
+        scene
+    }
+
+    #[test]
+    fn recorded_replay_round_trips_through_export_and_import() {
+        assert!(record_key_presses(false).replay().inputs.is_empty());
+
+        let recorded = record_key_presses(true);
+        assert_eq!(recorded.replay().inputs.len(), 2);
+        let path = std::env::temp_dir().join("ea_engine_replay_round_trip.json");
+        recorded.export_replay(&path).unwrap();
+        let mut imported = Scene::new(SceneConfig::default()).unwrap();
+        imported.import_replay(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(imported.replay().inputs, recorded.replay().inputs);
+    }
+
+    #[test]
+    fn cancelling_quit_restores_the_state_before_the_prompt() {
+        let mut scene = Scene::new(SceneConfig {
+            confirm_quit: true,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        scene.state = GameState::Paused;
+        scene.request_quit();
+        assert!(scene.is_quit_prompt_shown());
+        scene.cancel_quit();
+        assert!(!scene.is_quit_prompt_shown());
+        assert!(scene.state == GameState::Paused);
+
+        scene.state = GameState::Running;
+        scene.request_quit();
+        assert!(scene.state == GameState::Paused);
+        scene.cancel_quit();
+        assert!(scene.state == GameState::Running);
+    }
+
+    #[test]
+    fn boss_waits_for_the_kill_requirement_after_the_cycle() {
+        let mut scene = Scene::new(SceneConfig {
+            boss_cycle: 2,
+            new_body_cycle: 1,
+            boss_kill_requirement: Some(3),
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        scene.state = GameState::Running;
+        for _ in 0..10 {
+            scene.step().unwrap();
+        }
+        assert!(!scene.entity_stats().boss_alive);
+
+        scene.world.write_resource::<GameMetrics>().kills = 3;
+        scene.step().unwrap();
+        assert!(scene.entity_stats().boss_alive);
+    }
+
+    #[test]
+    fn first_cycle_boss_waits_for_kills_without_repeating_wave_starts() {
+        let mut scene = Scene::new(SceneConfig {
+            boss_cycle: 1,
+            new_body_cycle: 1,
+            boss_kill_requirement: Some(2),
+            spawns_per_wave: 1,
+            show_wave_intro: true,
+            difficulty_per_wave: 0.5,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        scene.state = GameState::Running;
+        for _ in 0..10 {
+            scene.step().unwrap();
+        }
+        assert!(scene.awaiting_boss_kills);
+        assert!(!scene.entity_stats().boss_alive);
+        assert!(scene.entity_stats().enemies > 0);
+        assert_eq!(scene.wave_intro, None);
+        assert_eq!(scene.world.read_resource::<DifficultyRes>().multiplier, 1.0);
+
+        scene.world.write_resource::<GameMetrics>().kills = 2;
+        scene.step().unwrap();
+        assert!(scene.entity_stats().boss_alive);
+        assert!(!scene.awaiting_boss_kills);
+    }
+
+    fn get_high_score_scene(path: &std::path::Path) -> Scene {
+        let mut scene = Scene::new(SceneConfig {
+            high_score_path: Some(path.to_string_lossy().into_owned()),
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        scene.name_entry = Some((NameEntry::new(), 500));
+        for key in [
+            Key::Up,
+            Key::Return,
+            Key::Return,
+            Key::Up,
+            Key::Up,
+            Key::Return,
+        ]
+        .iter()
+        {
+            scene
+                .name_entry_event(&Event::Key(*key, ButtonState::Pressed))
+                .unwrap();
+        }
+        scene
+    }
+
+    #[test]
+    fn confirmed_initials_are_saved_with_the_score() {
+        let path = std::env::temp_dir().join("ea_engine_high_scores.json");
+        let _ = std::fs::remove_file(&path);
+        get_high_score_scene(&path);
+        let table: HighScoreTable = crate::persistence::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            table.entries,
+            vec![ScoreEntry {
+                name: "BAC".to_string(),
+                score: 500,
+            }]
+        );
+    }
+
+    #[test]
+    fn unreadable_high_score_file_is_left_untouched() {
+        let path = std::env::temp_dir().join("ea_engine_corrupt_high_scores.json");
+        std::fs::write(&path, "not json").unwrap();
+        let scene = get_high_score_scene(&path);
+        let data = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(data, "not json");
+        assert_eq!(scene.high_scores.entries.len(), 1);
+    }
+
+    #[test]
+    fn closing_menus_tolerates_labels_that_were_already_removed() {
+        let mut scene = Scene::new(SceneConfig::default()).unwrap();
+        scene.state = GameState::Running;
+        scene.open_rebind_menu();
+        scene.world.delete_all();
+        scene.close_rebind_menu();
+        assert!(!scene.is_rebind_menu_shown());
+
+        scene.request_quit();
+        scene.world.delete_all();
+        scene.cancel_quit();
+        assert!(!scene.is_quit_prompt_shown());
+        assert!(scene.state == GameState::Running);
+    }
+
+    #[test]
+    fn rebinding_rejects_reserved_and_conflicting_keys() {
+        let mut scene = Scene::new(SceneConfig::default()).unwrap();
+        scene.open_rebind_menu();
+        let selected = scene
+            .rebind_entries()
+            .iter()
+            .position(|&entry| entry == (PlayerId::One, Action::MoveLeft))
+            .unwrap();
+        scene.rebind_menu.as_mut().unwrap().selected = selected;
+        let press = |scene: &mut Scene, key: Key| {
+            scene.rebind_event(&Event::Key(key, ButtonState::Pressed));
+            scene.world.read_resource::<VariableDictionary>().dictionary[&LabelVariable::Rebind]
+                .clone()
+        };
+        press(&mut scene, Key::Return);
+
+        assert_eq!(press(&mut scene, Key::Y), "Key:Y is reserved");
+        assert!(press(&mut scene, Key::D).contains("already bound"));
+        press(&mut scene, Key::J);
+        let bindings = scene.world.read_resource::<KeyBindings>();
+        assert_eq!(bindings.find("Key:J").unwrap().action, Action::MoveLeft);
+        assert!(bindings.find("Key:A").is_none());
+    }
+
+    #[test]
+    fn saved_settings_apply_live_and_reload_into_a_fresh_scene() {
+        let path = std::env::temp_dir().join("ea_engine_settings_round_trip.json");
+        let config = SceneConfig {
+            settings_path: Some(path.to_string_lossy().into_owned()),
+            ..SceneConfig::default()
+        };
+        let _ = std::fs::remove_file(&path);
+        let mut scene = Scene::new(config.clone()).unwrap();
+        let mut key_bindings = KeyBindings::default();
+        key_bindings
+            .rebind(PlayerId::One, Action::MoveLeft, "Key:J")
+            .unwrap();
+        let settings = Settings {
+            music_volume: 0.25,
+            difficulty: 1.5,
+            key_bindings,
+            ..Settings::default()
+        };
+        scene.apply_settings(settings.clone()).unwrap();
+        assert_eq!(scene.world.read_resource::<DifficultyRes>().multiplier, 1.5);
+        scene.save_settings().unwrap();
+
+        let reloaded = Scene::new(config).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reloaded.settings(), settings);
+        assert_eq!(
+            reloaded.world.read_resource::<DifficultyRes>().multiplier,
+            1.5
+        );
+    }
+
+    #[test]
+    fn rewind_restores_hero_position_enemies_and_timed_effects() {
+        let mut scene = Scene::new(SceneConfig {
+            practice_checkpoints: true,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        let enemy = crate::enemy::create_walker(&mut scene.world);
+        scene
+            .world
+            .write_storage::<HitFlash>()
+            .insert(
+                enemy,
+                HitFlash {
+                    remaining: Duration::from_millis(80),
+                },
+            )
+            .unwrap();
+        scene
+            .world
+            .write_storage::<FreezeFor>()
+            .insert(
+                enemy,
+                FreezeFor {
+                    remaining_ms: 400,
+                    saved_velocity: Some(Vector::new(-100, 0)),
+                },
+            )
+            .unwrap();
+        let hero_position = |world: &World| {
+            (
+                &world.read_storage::<Position>(),
+                &world.read_storage::<Hero>(),
+            )
+                .join()
+                .map(|(pos, _)| pos.position)
+                .next()
+                .unwrap()
+        };
+        let start = hero_position(&scene.world);
+        scene.save_checkpoint();
+
+        for pos in (&mut scene.world.write_storage::<Position>()).join() {
+            pos.position += Vector::new(50, 0);
+        }
+        scene.world.delete_entity(enemy).unwrap();
+        assert!(scene.rewind_to_checkpoint());
+
+        assert_eq!(hero_position(&scene.world), start);
+        let enemy = scene.world.read_storage::<Enemy>();
+        let hit_flash = scene.world.read_storage::<HitFlash>();
+        let freeze_for = scene.world.read_storage::<FreezeFor>();
+        let restored: Vec<(Duration, u64)> = (&enemy, &hit_flash, &freeze_for)
+            .join()
+            .map(|(_, hit_flash, freeze_for)| (hit_flash.remaining, freeze_for.remaining_ms))
+            .collect();
+        assert_eq!(restored, vec![(Duration::from_millis(80), 400)]);
+    }
+
+    fn spawn_seeded_enemies(seed: u64) -> (u64, Vec<(f32, f32, f32)>) {
+        let mut config = SceneConfig {
+            seed: Some(seed),
+            ..SceneConfig::default()
+        };
+        config.entity_factory_config.factory_type = crate::entity_factory::FactoryType::Random;
+        config.entity_factory_config.enemy_types = vec![
+            crate::enemy::EnemyType::Walker,
+            crate::enemy::EnemyType::Shooter,
+            crate::enemy::EnemyType::Flyer,
+            crate::enemy::EnemyType::FireballShower,
+        ];
+        let mut scene = Scene::new(config).unwrap();
+        for _ in 0..6 {
+            scene
+                .entity_factory
+                .create_entity(&mut scene.world)
+                .unwrap();
+        }
+        let pos = scene.world.read_storage::<Position>();
+        let vel = scene.world.read_storage::<Velocity>();
+        let spawns = (&pos, &vel)
+            .join()
+            .map(|(pos, vel)| (pos.position.x, pos.position.y, vel.velocity.y))
+            .collect();
+        (scene.seed(), spawns)
+    }
+
+    #[test]
+    fn same_seed_runs_spawn_identical_enemies() {
+        let (seed, first) = spawn_seeded_enemies(42);
+        let (_, second) = spawn_seeded_enemies(42);
+        assert_eq!(seed, 42);
+        assert!(first.len() > 6);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn wave_intro_holds_only_at_wave_boundaries_and_clears_on_rewind() {
+        let mut scene = Scene::new(SceneConfig {
+            new_body_cycle: 1,
+            spawns_per_wave: 2,
+            show_wave_intro: true,
+            wave_intro_ms: 50,
+            boss_cycle: 100,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        scene.state = GameState::Running;
+        scene.save_checkpoint();
+        let mut held = Vec::new();
+        while scene.cycle_counter < 5 {
+            scene.step().unwrap();
+            if scene.wave_intro.is_some() {
+                let dictionary = &scene.world.read_resource::<VariableDictionary>().dictionary;
+                held.push((
+                    scene.cycle_counter,
+                    dictionary[&LabelVariable::WaveIntro].clone(),
+                ));
+            }
+        }
+        held.dedup_by_key(|(cycle_counter, _)| *cycle_counter);
+        let waves: Vec<(u32, &str)> = held
+            .iter()
+            .map(|(cycle_counter, text)| (*cycle_counter, &text[..6]))
+            .collect();
+        assert_eq!(waves, vec![(0, "Wave 1"), (2, "Wave 2"), (4, "Wave 3")]);
+
+        while scene.wave_intro.is_none() {
+            scene.step().unwrap();
+        }
+        assert!(scene.rewind_to_checkpoint());
+        assert_eq!(scene.wave_intro, None);
+    }
+
+    #[test]
+    fn profile_drops_systems_that_stopped_running_and_tracks_the_play_field() {
+        let mut scene = Scene::new(SceneConfig {
+            profile_systems: true,
+            show_combo_meter: true,
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        scene.state = GameState::Running;
+        let profiled = |scene: &Scene, name: &str| {
+            scene
+                .system_timings()
+                .iter()
+                .any(|&(system, _)| system == name)
+        };
+
+        scene.step().unwrap();
+        assert!(profiled(&scene, "ComboMeterSystem"));
+        scene.config.show_combo_meter = false;
+        scene.step().unwrap();
+        assert!(!profiled(&scene, "ComboMeterSystem"));
+        assert!(profiled(&scene, "CollisionSystem"));
+    }
+
+    fn secondary_hero(position: Vector, ammo: u32) -> HeroConfig {
+        HeroConfig {
+            position,
+            secondary_weapon: Some(crate::hero::SecondaryWeaponConfig {
+                mode: crate::hero::WeaponModeConfig {
+                    projectile_sprite: "tiro".to_string(),
+                    pattern: crate::enemy::EmitterPattern::Aimed,
+                    projectile_speed: 600.0,
+                    cooldown_ms: 300,
+                    damage_over_time: None,
+                    contact_damage: None,
+                },
+                ammo,
+            }),
+            ..HeroConfig::default()
+        }
+    }
+
+    #[test]
+    fn each_player_gets_an_ammo_label() {
+        let scene = Scene::new(SceneConfig {
+            hero_config: secondary_hero(Vector::new(200, 400), 3),
+            player_two_config: Some(secondary_hero(Vector::new(600, 400), 5)),
+            ..SceneConfig::default()
+        })
+        .unwrap();
+
+        update_ammo_labels(&scene.world);
+
+        let label = scene.world.read_storage::<Label>();
+        let variables: Vec<LabelVariable> =
+            (&label).join().map(|label| label.bind_variable).collect();
+        assert!(variables.contains(&LabelVariable::SecondaryAmmo));
+        assert!(variables.contains(&LabelVariable::PlayerTwoAmmo));
+        let dictionary = &scene.world.read_resource::<VariableDictionary>().dictionary;
+        assert_eq!(dictionary[&LabelVariable::SecondaryAmmo], "Ammo: 3");
+        assert_eq!(dictionary[&LabelVariable::PlayerTwoAmmo], "Ammo: 5");
+    }
+
+    #[test]
+    fn boss_intro_suspends_updates_and_clears_its_banner() {
+        let mut scene = Scene::new(SceneConfig::default()).unwrap();
+        scene.state = GameState::Running;
+        scene.save_checkpoint();
+        let hero = (&scene.world.entities(), &scene.world.read_storage::<Hero>())
+            .join()
+            .next()
+            .unwrap()
+            .0;
+        let hero_x = |scene: &Scene| {
+            scene
+                .world
+                .read_storage::<Position>()
+                .get(hero)
+                .unwrap()
+                .position
+                .x
+        };
+        let frame_ms = FRAME_DURATION.as_millis() as u64;
+        let banner = create_boss_intro(&mut scene.world);
+        scene.boss_intro = Some((frame_ms * 3, banner));
+        scene.world.write_resource::<PressedKeys>().pressed_keys[0]
+            .add(KeyboardKeys::KeyRight as u32);
+
+        let start = hero_x(&scene);
+        scene.step().unwrap();
+        scene.step().unwrap();
+        assert_eq!(hero_x(&scene), start);
+        scene.world.delete_entity(banner).unwrap();
+        scene.step().unwrap();
+        assert_eq!(scene.boss_intro, None);
+        scene.step().unwrap();
+        assert!(hero_x(&scene) > start);
+
+        let banner = create_boss_intro(&mut scene.world);
+        scene.boss_intro = Some((frame_ms * 3, banner));
+        assert!(scene.rewind_to_checkpoint());
+        assert_eq!(scene.boss_intro, None);
+        assert!(!scene.world.is_alive(banner));
+    }
+
+    #[test]
+    fn headless_runs_are_stable_and_skip_scores_and_music() {
+        let config = SceneConfig {
+            headless_max_ms: 5_000,
+            hero_config: HeroConfig {
+                weapon_modes: vec![weapon_mode(EmitterPattern::Aimed)],
+                ..HeroConfig::default()
+            },
+            ..SceneConfig::default()
+        };
+        let mut replay = Replay::new(Some(9));
+        replay.record(0, 0, KeyboardKeys::KeyFire as u32, true);
+        replay.record(500, 0, KeyboardKeys::KeyUp as u32, true);
+        replay.record(1500, 0, KeyboardKeys::KeyUp as u32, false);
+        let summary = |metrics: GameMetrics| {
+            (
+                metrics.elapsed,
+                metrics.kills,
+                metrics.score,
+                metrics.outcome,
+            )
+        };
+
+        let first = run_headless(config.clone(), replay.clone()).unwrap();
+        let second = run_headless(config, replay).unwrap();
+        assert_eq!(summary(first), summary(second));
+        assert!(first.elapsed >= Duration::from_millis(5_000));
+        assert_eq!(first.outcome, None);
+
+        let mut scene = Scene::new(SceneConfig::default()).unwrap();
+        scene.headless = true;
+        scene.state = GameState::Running;
+        scene
+            .world
+            .write_resource::<SoundQueue>()
+            .push(&Some("hit.ogg".to_string()), SoundPriority::High);
+        scene.world.write_resource::<GameStateFlagRes>().flag = Some(GameStateFlag::Defeat);
+        scene.step().unwrap();
+        assert!(scene.is_game_over());
+        assert!(scene.high_scores().is_empty());
+        assert!(!scene.music_player.is_fading());
+        assert!(scene.world.read_resource::<SoundQueue>().sounds.is_empty());
+    }
+
+    #[test]
+    fn letterbox_bars_cover_the_mismatched_axis_only() {
+        let field_size = Vector::new(800, 600);
+
+        assert!(get_letterbox_bars(Vector::new(1600, 1200), field_size).is_empty());
+        assert_eq!(
+            get_letterbox_view(Vector::new(1000, 600), field_size),
+            Rectangle::new((-100, 0), (1000, 600))
+        );
+        assert_eq!(
+            get_letterbox_bars(Vector::new(1000, 600), field_size),
+            vec![
+                Rectangle::new((-100, 0), (100, 600)),
+                Rectangle::new((800, 0), (100, 600)),
+            ]
+        );
+        assert_eq!(
+            get_letterbox_bars(Vector::new(400, 400), field_size),
+            vec![
+                Rectangle::new((0, -100), (800, 100)),
+                Rectangle::new((0, 600), (800, 100)),
+            ]
+        );
+    }
+
+    #[test]
+    fn background_stages_sort_crossfade_on_their_own_and_reset_on_rewind() {
+        let mut scene = Scene::new(SceneConfig {
+            background_stages: vec![(200, "night".to_string()), (100, "dusk".to_string())],
+            ..SceneConfig::default()
+        })
+        .unwrap();
+        scene.state = GameState::Running;
+        scene.save_checkpoint();
+        let background_sprite = |scene: &Scene| {
+            (
+                &scene.world.read_storage::<Render>(),
+                &scene.world.read_storage::<Background>(),
+            )
+                .join()
+                .next()
+                .unwrap()
+                .0
+                .sprite
+                .clone()
+        };
+        let set_score = |scene: &mut Scene, score: i32| {
+            for hero in (&mut scene.world.write_storage::<Hero>()).join() {
+                hero.score = score;
+            }
+        };
+
+        set_score(&mut scene, 150);
+        scene.step().unwrap();
+        assert_eq!(background_sprite(&scene), "dusk");
+        assert!(
+            scene
+                .world
+                .read_resource::<BackgroundStages>()
+                .fade
+                .remaining
+                > Duration::from_millis(0)
+        );
+        assert_eq!(
+            scene.world.read_resource::<ScreenFlash>().remaining,
+            Duration::from_millis(0)
+        );
+
+        set_score(&mut scene, 250);
+        scene.step().unwrap();
+        assert_eq!(background_sprite(&scene), "night");
+
+        assert!(scene.rewind_to_checkpoint());
+        assert_eq!(scene.world.read_resource::<BackgroundStages>().current, 0);
+        assert_eq!(background_sprite(&scene), "cenario");
+    }
+
+    #[test]
+    fn pacifist_bonus_is_awarded_until_the_first_shot_and_restored_on_rewind() {
+        let config = SceneConfig {
+            pacifist_bonus: Some(5_000),
+            hero_config: HeroConfig {
+                weapon_modes: vec![weapon_mode(EmitterPattern::Aimed)],
+                ..HeroConfig::default()
+            },
+            ..SceneConfig::default()
+        };
+        let eligible = |scene: &Scene| scene.world.read_resource::<Pacifist>().eligible;
+        let fire = |scene: &mut Scene| {
+            scene.world.write_resource::<PressedKeys>().pressed_keys[0]
+                .add(KeyboardKeys::KeyFire as u32);
+            scene.step().unwrap();
+        };
+
+        let mut pacifist = Scene::new(config.clone()).unwrap();
+        pacifist.headless = true;
+        pacifist.state = GameState::Running;
+        for _ in 0..10 {
+            pacifist.step().unwrap();
+        }
+        assert!(eligible(&pacifist));
+        pacifist.victory().unwrap();
+        assert_eq!(pacifist.world.read_resource::<GameMetrics>().score, 5_000);
+
+        let mut shooter = Scene::new(config).unwrap();
+        shooter.headless = true;
+        shooter.state = GameState::Running;
+        shooter.save_checkpoint();
+        fire(&mut shooter);
+        assert!(!eligible(&shooter));
+        assert!(shooter.rewind_to_checkpoint());
+        assert!(eligible(&shooter));
+        fire(&mut shooter);
+        assert!(!eligible(&shooter));
+        let score = shooter.total_score();
+        shooter.victory().unwrap();
+        assert_eq!(shooter.world.read_resource::<GameMetrics>().score, score);
+    }
 }
@@ -0,0 +1,138 @@
+use crate::{component::Hero, resources::GameMetrics, system::FRAME_DURATION};
+use serde::{Deserialize, Serialize};
+use specs::{Join, Read, System, Write, WriteStorage};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct ScoreDecayConfig {
+    pub idle_ms: u64,
+    pub interval_ms: u64,
+    pub amount: i32,
+}
+
+impl Default for ScoreDecayConfig {
+    fn default() -> ScoreDecayConfig {
+        ScoreDecayConfig {
+            idle_ms: 5000,
+            interval_ms: 500,
+            amount: 5,
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct ScoreDecay {
+    pub idle_threshold: Duration,
+    pub interval: Duration,
+    pub amount: i32,
+    pub idle: Duration,
+    pub timer: Duration,
+    pub last_kills: u32,
+}
+
+impl ScoreDecay {
+    pub fn new(config: ScoreDecayConfig) -> ScoreDecay {
+        ScoreDecay {
+            idle_threshold: Duration::from_millis(config.idle_ms),
+            interval: Duration::from_millis(config.interval_ms),
+            amount: config.amount,
+            ..ScoreDecay::default()
+        }
+    }
+}
+
+pub struct ScoreDecaySystem;
+
+impl<'a> System<'a> for ScoreDecaySystem {
+    type SystemData = (
+        WriteStorage<'a, Hero>,
+        Read<'a, GameMetrics>,
+        Write<'a, ScoreDecay>,
+    );
+
+    fn run(&mut self, (mut hero, metrics, mut decay): Self::SystemData) {
+        if metrics.kills != decay.last_kills {
+            decay.last_kills = metrics.kills;
+            decay.idle = Duration::from_millis(0);
+            decay.timer = Duration::from_millis(0);
+            return;
+        }
+        decay.idle += FRAME_DURATION;
+        if decay.idle < decay.idle_threshold {
+            return;
+        }
+        decay.timer += FRAME_DURATION;
+        if decay.timer < decay.interval {
+            return;
+        }
+        decay.timer = Duration::from_millis(0);
+        for hero in (&mut hero).join() {
+            hero.score = get_decayed_score(hero.score, decay.amount);
+        }
+    }
+}
+
+pub fn get_decayed_score(score: i32, amount: i32) -> i32 {
+    if score <= 0 {
+        score
+    } else {
+        (score - amount.max(0)).max(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::PlayerId,
+        hero::HeroConfig,
+        scene::{create_world, SceneConfig},
+        test_support::run,
+    };
+    use specs::World;
+
+    #[test]
+    fn score_decays_after_idle_threshold_until_a_kill_resets_it() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        world.write_storage::<Hero>().get_mut(hero).unwrap().score = 12;
+        *world.write_resource::<ScoreDecay>() = ScoreDecay {
+            idle_threshold: FRAME_DURATION * 3,
+            interval: FRAME_DURATION,
+            amount: 5,
+            ..ScoreDecay::default()
+        };
+        let step = |world: &mut World| {
+            run(world, ScoreDecaySystem);
+            world.read_storage::<Hero>().get(hero).unwrap().score
+        };
+
+        assert_eq!(step(&mut world), 12);
+        assert_eq!(step(&mut world), 12);
+        assert_eq!(step(&mut world), 7);
+
+        world.write_resource::<GameMetrics>().kills += 1;
+        assert_eq!(step(&mut world), 7);
+        assert_eq!(step(&mut world), 7);
+        assert_eq!(step(&mut world), 7);
+        assert_eq!(step(&mut world), 2);
+        assert_eq!(step(&mut world), 0);
+    }
+
+    #[test]
+    fn scene_config_sets_the_decay_thresholds() {
+        let world = create_world(&SceneConfig {
+            score_decay: Some(ScoreDecayConfig {
+                idle_ms: 2000,
+                interval_ms: 250,
+                amount: 3,
+            }),
+            ..SceneConfig::default()
+        });
+        let decay = *world.read_resource::<ScoreDecay>();
+        assert_eq!(decay.idle_threshold, Duration::from_millis(2000));
+        assert_eq!(decay.interval, Duration::from_millis(250));
+        assert_eq!(decay.amount, 3);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{resources::KeyBindings, scene::SceneConfig};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub music_volume: f32,
+    pub difficulty: f32,
+    pub key_bindings: KeyBindings,
+    pub shake_intensity: f32,
+    pub pause_dim_alpha: f32,
+    pub show_control_hints: bool,
+    pub bullet_assist: Option<f32>,
+}
+
+impl Settings {
+    pub fn from_config(config: &SceneConfig) -> Settings {
+        Settings {
+            music_volume: config.music_volume,
+            difficulty: config.difficulty,
+            key_bindings: config.key_bindings.clone(),
+            shake_intensity: config.shake_intensity,
+            pause_dim_alpha: config.pause_dim_alpha,
+            show_control_hints: config.show_control_hints,
+            bullet_assist: config.bullet_assist,
+        }
+    }
+
+    pub fn apply(&self, config: &mut SceneConfig) {
+        config.music_volume = self.music_volume;
+        config.difficulty = self.difficulty;
+        config.key_bindings = self.key_bindings.clone();
+        config.shake_intensity = self.shake_intensity;
+        config.pause_dim_alpha = self.pause_dim_alpha;
+        config.show_control_hints = self.show_control_hints;
+        config.bullet_assist = self.bullet_assist;
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings::from_config(&SceneConfig::default())
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{
+    component::{
+        Acceleration, BombDrop, Boss, BossHealer, CalculateOutOfBounds, ChangeSprite, ChargeAttack,
+        Combo, DamageOverTime, Dash, DashEntrance, DespawnAnim, Dive, DroneHost, DropTable,
+        Emitter, Enemy, EntitySounds, Fireball, Focus, FollowParent, FreezeFor, Healing, Health,
+        Hero, HeroWeapon, HitFlash, Lifetime, Mimic, MirrorHeroX, OneHitShield, Overdrive,
+        PlayerId, Position, PowerUp, Render, SecondaryWeapon, SelfShield, ShieldAura, ShieldDonor,
+        Shooter, Trail, Velocity,
+    },
+    resources::{DifficultyRes, GameMetrics},
+};
+use specs::{prelude::*, world::Index};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+struct EntitySnapshot {
+    id: Index,
+    position: Option<Position>,
+    velocity: Option<Velocity>,
+    render: Option<Render>,
+    calculate_out_of_bounds: Option<CalculateOutOfBounds>,
+    hero: Option<Hero>,
+    player: Option<PlayerId>,
+    hero_weapon: Option<HeroWeapon>,
+    dash: Option<Dash>,
+    focus: Option<Focus>,
+    combo: Option<Combo>,
+    enemy: Option<Enemy>,
+    health: Option<Health>,
+    boss: Option<Boss>,
+    follow_parent: Option<FollowParent>,
+    drone_host: Option<DroneHost>,
+    change_sprite: Option<ChangeSprite>,
+    shooter: Option<Shooter>,
+    emitter: Option<Emitter>,
+    fireball: Option<Fireball>,
+    damage_over_time: Option<DamageOverTime>,
+    shield_aura: Option<ShieldAura>,
+    self_shield: Option<SelfShield>,
+    one_hit_shield: Option<OneHitShield>,
+    shield_donor: Option<ShieldDonor>,
+    mirror_hero_x: Option<MirrorHeroX>,
+    drop_table: Option<DropTable>,
+    bomb_drop: Option<BombDrop>,
+    entity_sounds: Option<EntitySounds>,
+    acceleration: Option<Acceleration>,
+    dash_entrance: Option<DashEntrance>,
+    dive: Option<Dive>,
+    boss_healer: Option<BossHealer>,
+    overdrive: Option<Overdrive>,
+    healing: Option<Healing>,
+    power_up: Option<PowerUp>,
+    trail: Option<Trail>,
+    despawn_anim: Option<DespawnAnim>,
+    lifetime: Option<Lifetime>,
+    hit_flash: Option<HitFlash>,
+    mimic: Option<Mimic>,
+    freeze_for: Option<FreezeFor>,
+    charge_attack: Option<ChargeAttack>,
+    secondary_weapon: Option<SecondaryWeapon>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldSnapshot {
+    entities: Vec<EntitySnapshot>,
+    metrics: GameMetrics,
+    difficulty: DifficultyRes,
+}
+
+impl WorldSnapshot {
+    pub fn take(world: &World) -> WorldSnapshot {
+        let entities = get_snapshot_entities(world)
+            .into_iter()
+            .map(|e| EntitySnapshot {
+                id: e.id(),
+                position: get_component(world, e),
+                velocity: get_component(world, e),
+                render: get_component(world, e),
+                calculate_out_of_bounds: get_component(world, e),
+                hero: get_component(world, e),
+                player: get_component(world, e),
+                hero_weapon: get_component(world, e),
+                dash: get_component(world, e),
+                focus: get_component(world, e),
+                combo: get_component(world, e),
+                enemy: get_component(world, e),
+                health: get_component(world, e),
+                boss: get_component(world, e),
+                follow_parent: get_component(world, e),
+                drone_host: get_component(world, e),
+                change_sprite: get_component(world, e),
+                shooter: get_component(world, e),
+                emitter: get_component(world, e),
+                fireball: get_component(world, e),
+                damage_over_time: get_component(world, e),
+                shield_aura: get_component(world, e),
+                self_shield: get_component(world, e),
+                one_hit_shield: get_component(world, e),
+                shield_donor: get_component(world, e),
+                mirror_hero_x: get_component(world, e),
+                drop_table: get_component(world, e),
+                bomb_drop: get_component(world, e),
+                entity_sounds: get_component(world, e),
+                acceleration: get_component(world, e),
+                dash_entrance: get_component(world, e),
+                dive: get_component(world, e),
+                boss_healer: get_component(world, e),
+                overdrive: get_component(world, e),
+                healing: get_component(world, e),
+                power_up: get_component(world, e),
+                trail: get_component(world, e),
+                despawn_anim: get_component(world, e),
+                lifetime: get_component(world, e),
+                hit_flash: get_component(world, e),
+                mimic: get_component(world, e),
+                freeze_for: get_component(world, e),
+                charge_attack: get_component(world, e),
+                secondary_weapon: get_component(world, e),
+            })
+            .collect();
+        WorldSnapshot {
+            entities,
+            metrics: *world.read_resource::<GameMetrics>(),
+            difficulty: *world.read_resource::<DifficultyRes>(),
+        }
+    }
+
+    pub fn restore(&self, world: &mut World) {
+        let current = get_snapshot_entities(world);
+        world.delete_entities(&current).unwrap();
+
+        let mut ids: HashMap<Index, Entity> = HashMap::new();
+        for snapshot in self.entities.iter() {
+            let e = world.create_entity().build();
+            ids.insert(snapshot.id, e);
+            set_component(world, e, snapshot.position);
+            set_component(world, e, snapshot.velocity);
+            set_component(world, e, snapshot.render.clone());
+            set_component(world, e, snapshot.calculate_out_of_bounds);
+            set_component(world, e, snapshot.hero);
+            set_component(world, e, snapshot.player);
+            set_component(world, e, snapshot.hero_weapon.clone());
+            set_component(world, e, snapshot.dash);
+            set_component(world, e, snapshot.focus);
+            set_component(world, e, snapshot.combo);
+            set_component(world, e, snapshot.enemy);
+            set_component(world, e, snapshot.health);
+            set_component(world, e, snapshot.boss);
+            set_component(world, e, snapshot.follow_parent);
+            set_component(world, e, snapshot.drone_host.clone());
+            set_component(world, e, snapshot.change_sprite.clone());
+            set_component(world, e, snapshot.shooter.clone());
+            set_component(world, e, snapshot.emitter.clone());
+            set_component(world, e, snapshot.fireball);
+            set_component(world, e, snapshot.damage_over_time);
+            set_component(world, e, snapshot.shield_aura);
+            set_component(world, e, snapshot.self_shield);
+            set_component(world, e, snapshot.one_hit_shield);
+            set_component(world, e, snapshot.shield_donor);
+            set_component(world, e, snapshot.mirror_hero_x);
+            set_component(world, e, snapshot.drop_table.clone());
+            set_component(world, e, snapshot.bomb_drop.clone());
+            set_component(world, e, snapshot.entity_sounds.clone());
+            set_component(world, e, snapshot.acceleration);
+            set_component(world, e, snapshot.dash_entrance);
+            set_component(world, e, snapshot.dive);
+            set_component(world, e, snapshot.boss_healer);
+            set_component(world, e, snapshot.overdrive.clone());
+            set_component(world, e, snapshot.healing);
+            set_component(world, e, snapshot.power_up);
+            set_component(world, e, snapshot.trail);
+            set_component(world, e, snapshot.despawn_anim);
+            set_component(world, e, snapshot.lifetime);
+            set_component(world, e, snapshot.hit_flash);
+            set_component(world, e, snapshot.mimic.clone());
+            set_component(world, e, snapshot.freeze_for);
+            set_component(world, e, snapshot.charge_attack.clone());
+            set_component(world, e, snapshot.secondary_weapon.clone());
+        }
+
+        for fireball in (&mut world.write_storage::<Fireball>()).join() {
+            fireball.owner_id = fireball
+                .owner_id
+                .and_then(|owner_id| ids.get(&owner_id))
+                .map(|owner| owner.id());
+        }
+        for damage_over_time in (&mut world.write_storage::<DamageOverTime>()).join() {
+            damage_over_time.source_id = damage_over_time
+                .source_id
+                .and_then(|source_id| ids.get(&source_id))
+                .map(|source| source.id());
+        }
+        for follow_parent in (&mut world.write_storage::<FollowParent>()).join() {
+            if let Some(&parent) = ids.get(&follow_parent.parent.id()) {
+                follow_parent.parent = parent;
+            }
+        }
+        *world.write_resource::<GameMetrics>() = self.metrics;
+        *world.write_resource::<DifficultyRes>() = self.difficulty;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+fn get_snapshot_entities(world: &World) -> Vec<Entity> {
+    let entities = world.entities();
+    let hero = world.read_storage::<Hero>();
+    let enemy = world.read_storage::<Enemy>();
+    let fireball = world.read_storage::<Fireball>();
+    let healing = world.read_storage::<Healing>();
+    let power_up = world.read_storage::<PowerUp>();
+    (&entities)
+        .join()
+        .filter(|&e| {
+            hero.contains(e)
+                || enemy.contains(e)
+                || fireball.contains(e)
+                || healing.contains(e)
+                || power_up.contains(e)
+        })
+        .collect()
+}
+
+fn get_component<T: Component + Clone>(world: &World, e: Entity) -> Option<T> {
+    world.read_storage::<T>().get(e).cloned()
+}
+
+fn set_component<T: Component>(world: &mut World, e: Entity, component: Option<T>) {
+    if let Some(component) = component {
+        world.write_storage::<T>().insert(e, component).unwrap();
+    }
+}
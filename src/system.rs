@@ -1,43 +1,118 @@
 use std::{cell::RefCell, rc::Rc, time::Duration};
 
 use crate::{
+    background::BackgroundStages,
+    boss::FinalPhase,
+    combo::ComboMeter,
     component::{
-        Boss, CalculateOutOfBounds, ChangeSprite, Enemy, Fireball, Healing, Hero, Label, Position,
-        Render, Shooter, Velocity,
+        Acceleration, Ambient, Anchor, Background, BombDrop, Boss, CalculateOutOfBounds,
+        ChangeSprite, ChargeAttack, Combo, Corner, DamageOverTime, Dash, DashEntrance, DespawnAnim,
+        DroneHost, DropTable, Emitter, Enemy, EntitySounds, Faction, Fireball, Focus, FollowParent,
+        FreezeFor, Healing, Health, Hero, HeroWeapon, HitFlash, Label, Lifetime, OneHitShield,
+        Overdrive, Particle, PlayerId, Position, PowerUp, Render, SecondaryWeapon, SelfShield,
+        ShieldAura, ShieldDonor, Shooter, Trail, Velocity,
     },
-    enemy::FireballConfig,
-    resources::{GameStateFlag, GameStateFlagRes, KeyboardKeys, PressedKeys, VariableDictionary},
+    debug::{get_profile_overlay_position, InputHistory, SystemProfile},
+    difficulty::RubberBand,
+    effects::{get_hit_flash_tint, HitFlashSettings, HitFreeze, ScreenFlash, ScreenShake},
+    enemy::{DamageOverTimeConfig, FireballConfig},
+    particle::{ParticleConfig, TRAIL_INTERVAL},
+    power_up::PowerUpKind,
+    radar::{EdgeWarning, Radar},
+    resources::{
+        AdaptiveQuality, AnnouncementFilter, BulletAssist, DifficultyRes, FireGrace, GameMetrics,
+        GameStateFlag, GameStateFlagRes, HeroShot, HeroShots, KeyboardKeys, Kill, Kills,
+        LabelVariable, NoFireZones, OutcomeTint, Pacifist, PlayField, PressedKeys, RandomGenerator,
+        SoundPriority, SoundQueue, SystemErrors, VariableDictionary,
+    },
+    wave_clear::WaveClear,
 };
 
-use specs::{Entities, Entity, Join, LazyUpdate, Read, ReadStorage, System, Write, WriteStorage};
+use rand::Rng;
+
+use specs::{
+    world::Index, Builder, Entities, Entity, Join, LazyUpdate, Read, ReadStorage, System, World,
+    Write, WriteStorage,
+};
 
 use quicksilver::{
-    geom::{Shape, Vector},
-    graphics::{Atlas, Background::Img, Font},
+    geom::{Circle, Rectangle, Shape, Vector},
+    graphics::{
+        Atlas, AtlasItem,
+        Background::{Blended, Col, Img},
+        Color, Font, FontStyle,
+    },
     lifecycle::{Asset, Window},
-    Result,
+    Error, Result,
 };
 
+pub const FRAME_DURATION: Duration = Duration::from_nanos(16_700_000);
+const ANNOUNCEMENT_DURATION: Duration = Duration::from_millis(1500);
+const SELF_SHIELD_RADIUS: f32 = 30.0;
+const CHARGE_BAR_WIDTH: f32 = 40.0;
+const CHARGE_BAR_HEIGHT: f32 = 5.0;
+const CHARGE_BAR_OFFSET: f32 = 10.0;
+const DASH_MARKER_RADIUS: f32 = 12.0;
+const DASH_MARKER_MARGIN: f32 = 20.0;
+const COMBO_METER_WIDTH: f32 = 50.0;
+const COMBO_METER_HEIGHT: f32 = 6.0;
+const COMBO_METER_OFFSET: f32 = 40.0;
+const EDGE_GLOW_WIDTH: f32 = 24.0;
+const EDGE_GLOW_ALPHA: f32 = 0.4;
+const RADAR_INDICATOR_RADIUS: f32 = 6.0;
+
 pub struct RenderSystem<'a> {
     window: &'a mut Window,
     atlas: Rc<RefCell<Asset<Atlas>>>,
+    error: Option<Error>,
 }
 
 impl<'a> RenderSystem<'a> {
+    #[allow(clippy::result_large_err)]
     pub fn new(
         window: &'a mut Window,
         atlas: Rc<RefCell<Asset<Atlas>>>,
     ) -> Result<RenderSystem<'a>> {
-        Ok(RenderSystem { window, atlas })
+        Ok(RenderSystem {
+            window,
+            atlas,
+            error: None,
+        })
+    }
+
+    pub fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
     }
 
-    fn do_render(&mut self, render: &mut Render, sprite: String, position: Vector) -> Result<()> {
+    #[allow(clippy::result_large_err)]
+    fn do_render(
+        &mut self,
+        render: &mut Render,
+        sprite: String,
+        position: Vector,
+        scale: f32,
+        offset: Vector,
+        tint: Option<Color>,
+    ) -> Result<()> {
         let window = &mut self.window;
         self.atlas.borrow_mut().execute(|loaded_atlas| {
-            let image = loaded_atlas.get(&sprite).unwrap().unwrap_image();
+            let image = match loaded_atlas.get(&sprite) {
+                Some(AtlasItem::Image(image)) => image,
+                Some(AtlasItem::Animation(_)) => {
+                    return Err(Error::ContextError(format!(
+                        "Sprite {} is an animation",
+                        sprite
+                    )))
+                }
+                None => return Err(Error::ContextError(format!("Missing sprite {}", sprite))),
+            };
             let area = image.area();
             render.bounding_box = Some(area);
-            window.draw(&area.with_center(position), Img(&image));
+            let area = Rectangle::new_sized(area.size * scale).with_center(position + offset);
+            match tint {
+                Some(tint) => window.draw(&area, Blended(&image, tint)),
+                None => window.draw(&area, Img(&image)),
+            }
             Ok(())
         })
     }
@@ -53,6 +128,15 @@ impl<'a> RenderSystem<'a> {
             render.sprite.clone()
         }
     }
+
+    fn get_scale(despawn_anim: Option<&DespawnAnim>) -> f32 {
+        match despawn_anim {
+            Some(despawn_anim) if despawn_anim.duration_ms > 0 => {
+                despawn_anim.remaining_ms as f32 / despawn_anim.duration_ms as f32
+            }
+            _ => 1.0,
+        }
+    }
 }
 
 impl<'a> System<'a> for RenderSystem<'a> {
@@ -62,48 +146,372 @@ impl<'a> System<'a> for RenderSystem<'a> {
         WriteStorage<'a, Render>,
         ReadStorage<'a, Hero>,
         ReadStorage<'a, ChangeSprite>,
+        ReadStorage<'a, DespawnAnim>,
+        ReadStorage<'a, Boss>,
+        Read<'a, Radar>,
+        Read<'a, ScreenShake>,
+        ReadStorage<'a, Particle>,
+        ReadStorage<'a, Lifetime>,
+        Read<'a, GameStateFlagRes>,
+        Read<'a, OutcomeTint>,
+        ReadStorage<'a, Focus>,
+        (ReadStorage<'a, Background>, ReadStorage<'a, Anchor>),
+        ReadStorage<'a, Ambient>,
+        ReadStorage<'a, SelfShield>,
+        ReadStorage<'a, OneHitShield>,
+        Read<'a, AdaptiveQuality>,
+        (ReadStorage<'a, HitFlash>, Read<'a, HitFlashSettings>),
+        (ReadStorage<'a, ChargeAttack>, ReadStorage<'a, DashEntrance>),
+        Read<'a, EdgeWarning>,
+        Read<'a, PlayField>,
+        (
+            Read<'a, ScreenFlash>,
+            Read<'a, FinalPhase>,
+            Read<'a, BackgroundStages>,
+        ),
+        Read<'a, ComboMeter>,
+        ReadStorage<'a, Overdrive>,
     );
 
-    fn run(&mut self, (entities, pos, mut render, hero, change_sprite): Self::SystemData) {
-        for (e, pos, render) in (&entities, &pos, &mut render).join() {
+    fn run(
+        &mut self,
+        (
+            entities,
+            pos,
+            mut render,
+            hero,
+            change_sprite,
+            despawn_anim,
+            boss,
+            radar,
+            screen_shake,
+            particle,
+            lifetime,
+            flag,
+            outcome_tint,
+            focus,
+            (background, anchor),
+            ambient,
+            self_shield,
+            one_hit_shield,
+            quality,
+            (hit_flash, hit_flash_settings),
+            (charge_attack, dash_entrance),
+            edge_warning,
+            play_field,
+            (screen_flash, final_phase, background_stages),
+            combo_meter,
+            overdrive,
+        ): Self::SystemData,
+    ) {
+        for (pos, render, anchor, _) in (&pos, &mut render, anchor.maybe(), &background).join() {
+            let sprite = render.sprite.clone();
+            let position = match anchor {
+                Some(anchor) => resolve_anchor(anchor, play_field.size),
+                None => pos.position,
+            };
+            let result = self.do_render(render, sprite, position, 1.0, screen_shake.offset, None);
+            if let Err(error) = result {
+                self.error.get_or_insert(error);
+            }
+        }
+
+        for (pos, ambient) in (&pos, &ambient).join() {
+            if quality.reduced {
+                continue;
+            }
+            self.window.draw(
+                &Circle::new(pos.position + screen_shake.offset, ambient.radius),
+                Col(ambient.color),
+            );
+        }
+
+        for (e, pos, render, _) in (&entities, &pos, &mut render, !&background).join() {
             let hero: Option<&Hero> = hero.get(e);
             let change_sprite: Option<&ChangeSprite> = change_sprite.get(e);
 
+            if dash_entrance.get(e).filter(|dash| !dash.dashing).is_some() {
+                let marker = crate::enemy::get_dash_marker_position(
+                    pos.position,
+                    play_field.size,
+                    DASH_MARKER_MARGIN,
+                );
+                self.window.draw(
+                    &Circle::new(marker + screen_shake.offset, DASH_MARKER_RADIUS),
+                    Col(Color::RED),
+                );
+                continue;
+            }
+
+            if let Some(boss) = boss.get(e) {
+                if (boss.invulnerable.as_millis() / 100) % 2 == 1 {
+                    continue;
+                }
+            }
+
             if let Some(hero) = hero {
                 if hero.render {
                     let sprite = RenderSystem::get_sprite(render, change_sprite);
-                    self.do_render(render, sprite, pos.position).unwrap();
+                    let tint = overdrive
+                        .get(e)
+                        .filter(|overdrive| overdrive.active)
+                        .map(|_| Color::YELLOW);
+                    let result = self.do_render(
+                        render,
+                        sprite,
+                        pos.position,
+                        1.0,
+                        screen_shake.offset,
+                        tint,
+                    );
+                    if let Err(error) = result {
+                        self.error.get_or_insert(error);
+                    }
                 }
             } else {
                 let sprite = RenderSystem::get_sprite(render, change_sprite);
-                self.do_render(render, sprite, pos.position).unwrap();
+                let scale = RenderSystem::get_scale(despawn_anim.get(e));
+                let tint = get_hit_flash_tint(hit_flash.get(e), &hit_flash_settings);
+                let result = self.do_render(
+                    render,
+                    sprite,
+                    pos.position,
+                    scale,
+                    screen_shake.offset,
+                    tint,
+                );
+                if let Err(error) = result {
+                    self.error.get_or_insert(error);
+                }
+            }
+        }
+
+        for (pos, render, focus) in (&pos, &render, &focus).join() {
+            if !focus.active || !focus.show_hitbox {
+                continue;
+            }
+            if let Some(bounding_box) = render.bounding_box {
+                let (hero_body_area, _) =
+                    crate::hero::get_hero_body_feet_area(bounding_box, pos.position);
+                self.window.draw(
+                    &hero_body_area.translate(screen_shake.offset),
+                    Col(Color::RED.with_alpha(0.4)),
+                );
+            }
+        }
+
+        for (pos, render, self_shield) in (&pos, &render, &self_shield).join() {
+            if !self_shield.active {
+                continue;
+            }
+            let radius = render
+                .bounding_box
+                .map_or(SELF_SHIELD_RADIUS, |bounding_box| {
+                    bounding_box.size.x.max(bounding_box.size.y) / 2.0
+                });
+            self.window.draw(
+                &Circle::new(pos.position + screen_shake.offset, radius),
+                Col(Color::BLUE.with_alpha(0.3)),
+            );
+        }
+
+        for (pos, render, _) in (&pos, &render, &one_hit_shield).join() {
+            let radius = render
+                .bounding_box
+                .map_or(SELF_SHIELD_RADIUS, |bounding_box| {
+                    bounding_box.size.x.max(bounding_box.size.y) / 2.0
+                });
+            self.window.draw(
+                &Circle::new(pos.position + screen_shake.offset, radius),
+                Col(Color::CYAN.with_alpha(0.3)),
+            );
+        }
+
+        for (pos, render, charge_attack) in (&pos, &render, &charge_attack).join() {
+            if let Some(progress) = crate::enemy::get_charge_progress(charge_attack) {
+                let top = render
+                    .bounding_box
+                    .map_or(SELF_SHIELD_RADIUS, |bounding_box| bounding_box.size.y / 2.0);
+                let origin = pos.position + screen_shake.offset
+                    - Vector::new(CHARGE_BAR_WIDTH / 2.0, top + CHARGE_BAR_OFFSET);
+                self.window.draw(
+                    &Rectangle::new(origin, (CHARGE_BAR_WIDTH, CHARGE_BAR_HEIGHT)),
+                    Col(Color::BLACK.with_alpha(0.5)),
+                );
+                self.window.draw(
+                    &Rectangle::new(origin, (CHARGE_BAR_WIDTH * progress, CHARGE_BAR_HEIGHT)),
+                    Col(Color::ORANGE),
+                );
+            }
+        }
+
+        for reading in combo_meter.readings.iter() {
+            let origin = reading.position
+                + screen_shake.offset
+                + Vector::new(-COMBO_METER_WIDTH / 2.0, COMBO_METER_OFFSET);
+            if reading.flashing {
+                self.window.draw(
+                    &Rectangle::new(origin, (COMBO_METER_WIDTH, COMBO_METER_HEIGHT)),
+                    Col(Color::RED),
+                );
+                continue;
+            }
+            if reading.fill <= 0.0 {
+                continue;
+            }
+            let level_width = COMBO_METER_WIDTH * reading.level;
+            self.window.draw(
+                &Rectangle::new(origin, (COMBO_METER_WIDTH, COMBO_METER_HEIGHT)),
+                Col(Color::BLACK.with_alpha(0.5)),
+            );
+            self.window.draw(
+                &Rectangle::new(origin, (level_width, COMBO_METER_HEIGHT)),
+                Col(Color::YELLOW.with_alpha(0.5)),
+            );
+            self.window.draw(
+                &Rectangle::new(origin, (level_width * reading.fill, COMBO_METER_HEIGHT)),
+                Col(Color::ORANGE),
+            );
+        }
+
+        for (pos, particle, lifetime) in (&pos, &particle, &lifetime).join() {
+            let mut color = particle.color;
+            color.a = crate::particle::get_particle_alpha(particle, lifetime);
+            self.window.draw(
+                &Circle::new(pos.position + screen_shake.offset, particle.radius),
+                Col(color),
+            );
+        }
+
+        let size = play_field.size;
+        let edges = [
+            (
+                edge_warning.left,
+                Rectangle::new((0.0, 0.0), (EDGE_GLOW_WIDTH, size.y)),
+            ),
+            (
+                edge_warning.right,
+                Rectangle::new((size.x - EDGE_GLOW_WIDTH, 0.0), (EDGE_GLOW_WIDTH, size.y)),
+            ),
+            (
+                edge_warning.top,
+                Rectangle::new((0.0, 0.0), (size.x, EDGE_GLOW_WIDTH)),
+            ),
+            (
+                edge_warning.bottom,
+                Rectangle::new((0.0, size.y - EDGE_GLOW_WIDTH), (size.x, EDGE_GLOW_WIDTH)),
+            ),
+        ];
+        for (intensity, area) in edges.iter() {
+            if *intensity > 0.0 {
+                self.window.draw(
+                    area,
+                    Col(Color::RED.with_alpha(EDGE_GLOW_ALPHA * intensity)),
+                );
+            }
+        }
+
+        for indicator in radar.indicators.iter() {
+            self.window.draw(
+                &Circle::new(indicator.position, RADAR_INDICATOR_RADIUS),
+                Col(Color::RED),
+            );
+        }
+
+        let enraged = (&boss).join().any(|boss| boss.enraged);
+        if let Some(tint) = final_phase.tint.filter(|_| enraged) {
+            self.window
+                .draw(&Rectangle::new_sized(play_field.size), Col(tint));
+        }
+
+        for flash in [&*screen_flash, &background_stages.fade].iter() {
+            if let Some(color) = flash.color {
+                let intensity = flash.current_intensity();
+                if intensity > 0.0 {
+                    self.window.draw(
+                        &Rectangle::new_sized(play_field.size),
+                        Col(color.with_alpha(color.a * intensity)),
+                    );
+                }
             }
         }
+
+        if let Some(tint) = get_outcome_tint(&outcome_tint, flag.flag) {
+            self.window
+                .draw(&Rectangle::new_sized(play_field.size), Col(tint));
+        }
     }
 }
 
 pub struct WalkSystem;
 
 impl<'a> System<'a> for WalkSystem {
-    type SystemData = (ReadStorage<'a, Velocity>, WriteStorage<'a, Position>);
+    type SystemData = (
+        WriteStorage<'a, Velocity>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Fireball>,
+        ReadStorage<'a, Hero>,
+        Read<'a, BulletAssist>,
+        ReadStorage<'a, Acceleration>,
+    );
 
-    fn run(&mut self, (vel, mut pos): Self::SystemData) {
+    fn run(
+        &mut self,
+        (mut vel, mut pos, fireball, hero, bullet_assist, acceleration): Self::SystemData,
+    ) {
         let time_step = 0.0167;
+        let heroes: Vec<Vector> = (&pos, &hero).join().map(|(pos, _)| pos.position).collect();
+
+        for (vel, acceleration) in (&mut vel, &acceleration).join() {
+            vel.velocity =
+                crate::enemy::get_accelerated_velocity(vel.velocity, acceleration, time_step);
+        }
+        for (vel, pos, fireball) in (&vel, &mut pos, fireball.maybe()).join() {
+            let scale = match fireball {
+                Some(fireball) if fireball.faction == Faction::Enemy => {
+                    get_bullet_assist_scale(pos.position, &heroes, &bullet_assist)
+                }
+                _ => 1.0,
+            };
+            pos.position += vel.velocity * time_step * scale;
+        }
+    }
+}
 
-        for (vel, pos) in (&vel, &mut pos).join() {
-            pos.position += vel.velocity * time_step;
+pub fn get_bullet_assist_scale(position: Vector, heroes: &[Vector], assist: &BulletAssist) -> f32 {
+    match assist.factor {
+        Some(factor)
+            if heroes
+                .iter()
+                .any(|hero| hero.distance(position) <= assist.radius) =>
+        {
+            factor.clamp(0.0, 1.0)
         }
+        _ => 1.0,
     }
 }
 
 pub struct LabelRenderSystem<'a> {
     window: &'a mut Window,
     font: Rc<RefCell<Asset<Font>>>,
+    error: Option<Error>,
 }
 
 impl<'a> LabelRenderSystem<'a> {
-    pub fn new(window: &mut Window, font: Rc<RefCell<Asset<Font>>>) -> Result<LabelRenderSystem> {
-        Ok(LabelRenderSystem { window, font })
+    #[allow(clippy::result_large_err)]
+    pub fn new(
+        window: &mut Window,
+        font: Rc<RefCell<Asset<Font>>>,
+    ) -> Result<LabelRenderSystem<'_>> {
+        Ok(LabelRenderSystem {
+            window,
+            font,
+            error: None,
+        })
+    }
+
+    pub fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
     }
 }
 
@@ -112,23 +520,94 @@ impl<'a> System<'a> for LabelRenderSystem<'a> {
         Read<'a, VariableDictionary>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, Label>,
+        Read<'a, InputHistory>,
+        ReadStorage<'a, Anchor>,
+        Read<'a, PlayField>,
+        Read<'a, SystemProfile>,
     );
 
-    fn run(&mut self, (dict, pos, render): Self::SystemData) {
-        for (pos, render) in (&pos, &render).join() {
+    #[allow(clippy::result_large_err)]
+    fn run(
+        &mut self,
+        (dict, pos, render, input_history, anchor, play_field, profile): Self::SystemData,
+    ) {
+        for (pos, render, anchor) in (&pos, &render, anchor.maybe()).join() {
+            if !render.visible {
+                continue;
+            }
+            let position = match anchor {
+                Some(anchor) => resolve_anchor(anchor, play_field.size),
+                None => pos.position,
+            };
             let window = &mut self.window;
-            self.font
-                .borrow_mut()
-                .execute(|font| {
-                    let rendered_label =
-                        font.render(&dict.dictionary[&render.bind_variable], &render.font_style)?;
+            let text = match dict.dictionary.get(&render.bind_variable) {
+                Some(text) => text,
+                None => {
+                    self.error.get_or_insert(Error::ContextError(format!(
+                        "Missing label variable {:?}",
+                        render.bind_variable
+                    )));
+                    continue;
+                }
+            };
+            let result = self.font.borrow_mut().execute(|font| {
+                let rendered_label = font.render(text, &render.font_style)?;
+                window.draw(
+                    &rendered_label.area().with_center(position),
+                    Img(&rendered_label),
+                );
+                Ok(())
+            });
+            if let Err(error) = result {
+                self.error.get_or_insert(error);
+            }
+        }
+
+        if input_history.enabled {
+            for (i, record) in input_history.entries.iter().enumerate() {
+                let window = &mut self.window;
+                let text = format!(
+                    "{}.{:03} {}",
+                    record.timestamp.as_secs(),
+                    record.timestamp.subsec_millis(),
+                    record.description
+                );
+                let result = self.font.borrow_mut().execute(|font| {
+                    let rendered_label = font.render(&text, &FontStyle::new(18.0, Color::BLACK))?;
+                    window.draw(
+                        &Rectangle::new(
+                            (10.0, 100.0 + i as f32 * 20.0),
+                            rendered_label.area().size,
+                        ),
+                        Img(&rendered_label),
+                    );
+                    Ok(())
+                });
+                if let Err(error) = result {
+                    self.error.get_or_insert(error);
+                }
+            }
+        }
+
+        if profile.enabled && profile.show_overlay {
+            for (i, (name, elapsed)) in profile.timings.iter().enumerate() {
+                let window = &mut self.window;
+                let text = format!("{} {}us", name, elapsed.as_micros());
+                let result = self.font.borrow_mut().execute(|font| {
+                    let rendered_label = font.render(&text, &FontStyle::new(14.0, Color::BLACK))?;
                     window.draw(
-                        &rendered_label.area().with_center(pos.position),
+                        &Rectangle::new(
+                            get_profile_overlay_position(play_field.size, i),
+                            rendered_label.area().size,
+                        ),
                         Img(&rendered_label),
                     );
                     Ok(())
-                })
-                .unwrap();
+                });
+                if let Err(error) = result {
+                    self.error.get_or_insert(error);
+                }
+            }
         }
     }
 }
@@ -141,15 +620,48 @@ impl<'a> System<'a> for HeroControlSystem {
         WriteStorage<'a, Hero>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, Velocity>,
+        ReadStorage<'a, PlayerId>,
+        WriteStorage<'a, HeroWeapon>,
+        WriteStorage<'a, Dash>,
+        WriteStorage<'a, Focus>,
+        Entities<'a>,
+        WriteStorage<'a, SecondaryWeapon>,
+        Read<'a, LazyUpdate>,
+        Read<'a, NoFireZones>,
+        Write<'a, Pacifist>,
     );
 
-    fn run(&mut self, (pressed_keys, mut hero, mut pos, mut vel): Self::SystemData) {
-        for (vel, pos, hero) in (&mut vel, &mut pos, &mut hero).join() {
+    fn run(
+        &mut self,
+        (
+            pressed_keys,
+            mut hero,
+            mut pos,
+            mut vel,
+            player,
+            mut weapon,
+            mut dash,
+            mut focus,
+            entities,
+            mut secondary_weapon,
+            lazy,
+            no_fire_zones,
+            mut pacifist,
+        ): Self::SystemData,
+    ) {
+        for (vel, pos, hero, player, dash, focus) in (
+            &mut vel,
+            &mut pos,
+            &mut hero,
+            &player,
+            (&mut dash).maybe(),
+            (&mut focus).maybe(),
+        )
+            .join()
+        {
+            let keys = &pressed_keys.pressed_keys[*player as usize];
             vel.velocity.y = if pos.position.y >= 425.0 {
-                if pressed_keys
-                    .pressed_keys
-                    .contains(KeyboardKeys::KeyUp as u32)
-                {
+                if keys.contains(KeyboardKeys::KeyUp as u32) {
                     -400.0
                 } else {
                     0.0
@@ -160,31 +672,110 @@ impl<'a> System<'a> for HeroControlSystem {
                 vel.velocity.y
             };
 
-            vel.velocity.x = if pressed_keys
-                .pressed_keys
-                .contains(KeyboardKeys::KeyRight as u32)
-                && !pressed_keys
-                    .pressed_keys
-                    .contains(KeyboardKeys::KeyLeft as u32)
+            vel.velocity.x = if keys.contains(KeyboardKeys::KeyRight as u32)
+                && !keys.contains(KeyboardKeys::KeyLeft as u32)
             {
                 250.0
-            } else if !pressed_keys
-                .pressed_keys
-                .contains(KeyboardKeys::KeyRight as u32)
-                && pressed_keys
-                    .pressed_keys
-                    .contains(KeyboardKeys::KeyLeft as u32)
+            } else if !keys.contains(KeyboardKeys::KeyRight as u32)
+                && keys.contains(KeyboardKeys::KeyLeft as u32)
             {
                 -250.0
             } else {
                 0.0
             };
 
+            if let Some(focus) = focus {
+                focus.active = keys.contains(KeyboardKeys::KeyFocus as u32);
+                if focus.active {
+                    vel.velocity.x *= focus.speed_factor;
+                }
+            }
+
+            if let Some(dash) = dash {
+                if vel.velocity.x != 0.0 {
+                    dash.direction = vel.velocity.x.signum();
+                }
+                if update_dash(dash, keys.contains(KeyboardKeys::KeyDash as u32)) {
+                    vel.velocity.x = dash.direction * dash.speed;
+                }
+            }
+
             if hero.reset_position {
-                pos.position = Vector::new(15.0, 300.0);
+                pos.position = Vector::new(hero.movement_margin, 300.0);
                 hero.reset_position = false;
             }
         }
+
+        for (weapon, player) in (&mut weapon, &player).join() {
+            let keys = &pressed_keys.pressed_keys[*player as usize];
+            if keys.contains(KeyboardKeys::KeyRight as u32)
+                && !keys.contains(KeyboardKeys::KeyLeft as u32)
+            {
+                weapon.aim = Vector::new(1.0, 0.0);
+            } else if keys.contains(KeyboardKeys::KeyLeft as u32)
+                && !keys.contains(KeyboardKeys::KeyRight as u32)
+            {
+                weapon.aim = Vector::new(-1.0, 0.0);
+            }
+
+            let cycle_pressed = keys.contains(KeyboardKeys::KeyCycleWeapon as u32);
+            if cycle_pressed && !weapon.cycle_held && !weapon.modes.is_empty() {
+                weapon.current_mode = crate::hero::get_next_weapon_mode(
+                    weapon.current_mode,
+                    crate::hero::get_unlocked_weapon_modes(weapon),
+                );
+            }
+            weapon.cycle_held = cycle_pressed;
+        }
+
+        for (e, pos, secondary_weapon, player, weapon) in (
+            &entities,
+            &pos,
+            &mut secondary_weapon,
+            &player,
+            weapon.maybe(),
+        )
+            .join()
+        {
+            if secondary_weapon.cooldown > FRAME_DURATION {
+                secondary_weapon.cooldown -= FRAME_DURATION;
+            } else {
+                secondary_weapon.cooldown = Duration::from_millis(0);
+            }
+
+            let keys = &pressed_keys.pressed_keys[*player as usize];
+            let pressed = keys.contains(KeyboardKeys::KeySecondary as u32);
+            let triggered = pressed
+                && !secondary_weapon.held
+                && secondary_weapon.ammo > 0
+                && secondary_weapon.cooldown == Duration::from_millis(0)
+                && !crate::hero::is_in_no_fire_zone(&no_fire_zones.zones, pos.position);
+            secondary_weapon.held = pressed;
+            if !triggered {
+                continue;
+            }
+            secondary_weapon.ammo -= 1;
+            pacifist.eligible = false;
+
+            let aim = weapon.map_or(Vector::new(1.0, 0.0), |weapon| weapon.aim);
+            let mode = &secondary_weapon.mode;
+            for direction in crate::enemy::get_emitter_directions(mode.pattern, aim) {
+                let fireball_config = FireballConfig {
+                    sprite: mode.projectile_sprite.clone(),
+                    position: pos.position,
+                    velocity: direction * mode.projectile_speed,
+                    damage_over_time: mode.damage_over_time,
+                    contact_damage: mode.contact_damage,
+                };
+                crate::enemy::create_fireball(
+                    lazy.create_entity(&entities),
+                    Some(e.id()),
+                    Faction::Hero,
+                    fireball_config,
+                );
+            }
+            secondary_weapon.cooldown = Duration::from_millis(mode.cooldown_ms);
+        }
     }
 }
 
@@ -196,21 +787,59 @@ impl<'a> System<'a> for OutOfBoundsSystem {
         ReadStorage<'a, Hero>,
         ReadStorage<'a, CalculateOutOfBounds>,
         WriteStorage<'a, Position>,
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, DespawnAnim>,
+        Read<'a, LazyUpdate>,
+        Write<'a, WaveClear>,
+        Read<'a, PlayField>,
+        Write<'a, SystemErrors>,
+        ReadStorage<'a, FollowParent>,
     );
 
-    fn run(&mut self, (entities, hero, oob, mut pos): Self::SystemData) {
-        for (_, pos, _, _) in (&entities, &mut pos, &oob, &hero).join() {
-            pos.position.x = if pos.position.x < 15.0 {
-                15.0
-            } else if pos.position.x > 785.0 {
-                785.0
-            } else {
-                pos.position.x
-            }
+    fn run(
+        &mut self,
+        (
+            entities,
+            hero,
+            oob,
+            mut pos,
+            enemy,
+            despawn_anim,
+            lazy,
+            mut wave_clear,
+            play_field,
+            mut errors,
+            follow_parent,
+        ): Self::SystemData,
+    ) {
+        for (_, pos, _, hero) in (&entities, &mut pos, &oob, &hero).join() {
+            let margin = hero.movement_margin;
+            pos.position.x = pos.position.x.max(margin).min(play_field.size.x - margin);
         }
-        for (e, pos, _, _) in (&entities, &mut pos, &oob, !&hero).join() {
-            if pos.position.y > 700.0 || pos.position.x < -100.0 || pos.position.x > 900.0 {
-                entities.delete(e).unwrap();
+        for (e, pos, _, _, _, _) in (
+            &entities,
+            &mut pos,
+            &oob,
+            !&hero,
+            !&despawn_anim,
+            !&follow_parent,
+        )
+            .join()
+        {
+            if pos.position.y > play_field.size.y + 100.0
+                || pos.position.y < -200.0
+                || pos.position.x < -100.0
+                || pos.position.x > play_field.size.x + 100.0
+            {
+                if enemy.get(e).is_some() {
+                    wave_clear.escaped = true;
+                }
+                match enemy.get(e) {
+                    Some(enemy) if enemy.despawn_ms > 0 => {
+                        lazy.insert(e, get_despawn_anim(enemy));
+                    }
+                    _ => delete_entity(&entities, e, &mut errors),
+                }
             }
         }
     }
@@ -221,32 +850,74 @@ pub struct CollisionSystem;
 impl CollisionSystem {
     fn hero_enemy_collision(
         hero: &mut Hero,
-        enemy: &Enemy,
+        weapon_drain: Option<Duration>,
         hero_render: &Render,
         enemy_render: &Render,
         hero_pos: Vector,
         enemy_pos: Vector,
-        entities: &Entities,
-        e: Entity,
-    ) {
-        if hero_render.bounding_box.is_some() && enemy_render.bounding_box.is_some() {
+        shielded: bool,
+    ) -> bool {
+        if let (Some(hero_box), Some(enemy_box)) =
+            (hero_render.bounding_box, enemy_render.bounding_box)
+        {
             let (hero_body_area, hero_feet_area) =
-                crate::hero::get_hero_body_feet_area(hero_render.bounding_box.unwrap(), hero_pos);
-            let (enemy_head_area, enemy_body_area) = crate::enemy::get_enemy_head_body_area(
-                enemy_render.bounding_box.unwrap(),
-                enemy_pos,
-            );
+                crate::hero::get_hero_body_feet_area(hero_box, hero_pos);
+            let (enemy_head_area, enemy_body_area) =
+                crate::enemy::get_enemy_head_body_area(enemy_box, enemy_pos);
 
             if enemy_head_area.overlaps(&hero_feet_area) {
-                hero.score += enemy.score;
-                entities.delete(e).unwrap();
+                return !shielded;
             } else if enemy_body_area.overlaps(&hero_body_area) && !hero.blinking {
-                hero.lives -= 1;
-                hero.blinking = true;
+                match weapon_drain {
+                    Some(cooldown) => {
+                        if hero.weapon_drain_cooldown == Duration::from_millis(0) {
+                            hero.weapon_drain_cooldown = cooldown;
+                        }
+                    }
+                    None => {
+                        hero.lives -= 1;
+                        hero.blinking = true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn hero_power_up_collision(
+        hero: &mut Hero,
+        power_up: &PowerUp,
+        hero_render: &Render,
+        power_up_render: &Render,
+        hero_pos: Vector,
+        power_up_pos: Vector,
+        entities: &Entities,
+        e: Entity,
+        errors: &mut SystemErrors,
+    ) {
+        if let (Some(hero_box), Some(power_up_box)) =
+            (hero_render.bounding_box, power_up_render.bounding_box)
+        {
+            let hero_bounding_box = hero_box.with_center(hero_pos);
+            let power_up_bounding_box = power_up_box.with_center(power_up_pos);
+
+            if hero_bounding_box.overlaps(&power_up_bounding_box) {
+                match power_up.kind {
+                    PowerUpKind::ExtraLife if hero.allow_extra_lives => hero.lives += 1,
+                    PowerUpKind::Invincibility if hero.allow_shields => {
+                        hero.blinking = true;
+                        hero.blink_timer = Duration::from_millis(0);
+                    }
+                    PowerUpKind::Bomb => hero.bombs += 1,
+                    _ => {}
+                }
+                delete_entity(entities, e, errors);
             }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn hero_healing_collision(
         hero: &mut Hero,
         healing: &Healing,
@@ -256,22 +927,25 @@ impl CollisionSystem {
         healing_pos: Vector,
         entities: &Entities,
         e: Entity,
+        errors: &mut SystemErrors,
     ) {
-        if hero_render.bounding_box.is_some() && healing_render.bounding_box.is_some() {
-            let hero_bounding_box = hero_render.bounding_box.unwrap().with_center(hero_pos);
-            let healing_bounding_box = healing_render
-                .bounding_box
-                .unwrap()
-                .with_center(healing_pos);
+        if let (Some(hero_box), Some(healing_box)) =
+            (hero_render.bounding_box, healing_render.bounding_box)
+        {
+            let hero_bounding_box = hero_box.with_center(hero_pos);
+            let healing_bounding_box = healing_box.with_center(healing_pos);
 
             if hero_bounding_box.overlaps(&healing_bounding_box) {
-                hero.lives += 1;
-                hero.score += healing.score;
-                entities.delete(e).unwrap();
+                if hero.allow_extra_lives {
+                    hero.lives += 1;
+                }
+                crate::hero::add_score(hero, healing.score);
+                delete_entity(entities, e, errors);
             }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn hero_fireball_collision(
         hero: &mut Hero,
         hero_render: &Render,
@@ -280,22 +954,66 @@ impl CollisionSystem {
         fireball_pos: Vector,
         entities: &Entities,
         e: Entity,
+        errors: &mut SystemErrors,
     ) {
-        if hero_render.bounding_box.is_some() && fireball_render.bounding_box.is_some() {
-            let hero_bounding_box = hero_render.bounding_box.unwrap().with_center(hero_pos);
-            let fireball_bounding_box = fireball_render
-                .bounding_box
-                .unwrap()
-                .with_center(fireball_pos);
+        if let (Some(hero_box), Some(fireball_box)) =
+            (hero_render.bounding_box, fireball_render.bounding_box)
+        {
+            let hero_bounding_box = hero_box.with_center(hero_pos);
+            let fireball_bounding_box = fireball_box.with_center(fireball_pos);
 
             if hero_bounding_box.overlaps(&fireball_bounding_box) && !hero.blinking {
                 hero.blinking = true;
                 hero.lives -= 1;
-                entities.delete(e).unwrap();
+                delete_entity(entities, e, errors);
+            }
+        }
+    }
+
+    fn fireball_enemy_overlap(
+        fireball_render: &Render,
+        enemy_render: &Render,
+        fireball_pos: Vector,
+        enemy_pos: Vector,
+    ) -> bool {
+        if let (Some(fireball_box), Some(enemy_box)) =
+            (fireball_render.bounding_box, enemy_render.bounding_box)
+        {
+            let fireball_bounding_box = fireball_box.with_center(fireball_pos);
+            let enemy_bounding_box = enemy_box.with_center(enemy_pos);
+            fireball_bounding_box.overlaps(&enemy_bounding_box)
+        } else {
+            false
+        }
+    }
+
+    fn damage_boss<'a>(
+        flag: &mut Write<'a, GameStateFlagRes>,
+        hero: &mut Hero,
+        boss: &mut Boss,
+        elapsed: Duration,
+        entities: &Entities,
+        e: Entity,
+        errors: &mut SystemErrors,
+    ) -> bool {
+        if boss.invulnerable > Duration::from_millis(0) || boss.parts_alive > 0 {
+            return false;
+        }
+        boss.lives -= 1;
+        boss.normal_lives -= 1;
+        if boss.lives == 0 {
+            flag.flag = Some(GameStateFlag::Victory);
+            delete_entity(entities, e, errors);
+            if let Some(time_bonus) = boss.time_bonus {
+                let time_to_kill = elapsed.saturating_sub(boss.spawned_at);
+                let bonus = crate::enemy::get_boss_time_bonus(&time_bonus, time_to_kill);
+                crate::hero::add_score(hero, bonus);
             }
         }
+        true
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn hero_boss_collision<'a>(
         flag: &mut Write<'a, GameStateFlagRes>,
         hero: &mut Hero,
@@ -305,35 +1023,24 @@ impl CollisionSystem {
         enemy_render: &Render,
         hero_pos: Vector,
         enemy_pos: Vector,
+        elapsed: Duration,
         entities: &Entities,
         e: Entity,
-        change_sprite: Option<&mut ChangeSprite>,
-        shooter: Option<&mut Shooter>,
+        errors: &mut SystemErrors,
     ) {
-        if hero_render.bounding_box.is_some() && enemy_render.bounding_box.is_some() {
+        if let (Some(hero_box), Some(enemy_box)) =
+            (hero_render.bounding_box, enemy_render.bounding_box)
+        {
             let (hero_body_area, hero_feet_area) =
-                crate::hero::get_hero_body_feet_area(hero_render.bounding_box.unwrap(), hero_pos);
-            let (enemy_head_area, enemy_body_area) = crate::enemy::get_enemy_head_body_area(
-                enemy_render.bounding_box.unwrap(),
-                enemy_pos,
-            );
+                crate::hero::get_hero_body_feet_area(hero_box, hero_pos);
+            let (enemy_head_area, enemy_body_area) =
+                crate::enemy::get_enemy_head_body_area(enemy_box, enemy_pos);
 
             if enemy_head_area.overlaps(&hero_feet_area) {
-                hero.score += enemy.score;
                 hero.reset_position = true;
                 hero.blinking = true;
-                boss.lives -= 1;
-                boss.normal_lives -= 1;
-                if boss.lives == 0 {
-                    flag.flag = Some(GameStateFlag::Victory);
-                    entities.delete(e).unwrap();
-                } else if boss.normal_lives == 0 {
-                    if let Some(change_sprite) = change_sprite {
-                        change_sprite.do_change = true;
-                    }
-                    if let Some(shooter) = shooter {
-                        shooter.maximum_fireballs = 4;
-                    }
+                if CollisionSystem::damage_boss(flag, hero, boss, elapsed, entities, e, errors) {
+                    crate::hero::add_kill_score(hero, enemy.score);
                 }
             } else if enemy_body_area.overlaps(&hero_body_area) && !hero.blinking {
                 hero.lives -= 1;
@@ -353,9 +1060,26 @@ impl<'a> System<'a> for CollisionSystem {
         ReadStorage<'a, Healing>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, Render>,
-        WriteStorage<'a, ChangeSprite>,
-        WriteStorage<'a, Shooter>,
         ReadStorage<'a, Fireball>,
+        ReadStorage<'a, PowerUp>,
+        Write<'a, Kills>,
+        Read<'a, LazyUpdate>,
+        ReadStorage<'a, ShieldAura>,
+        Read<'a, GameMetrics>,
+        ReadStorage<'a, DespawnAnim>,
+        WriteStorage<'a, Health>,
+        WriteStorage<'a, DamageOverTime>,
+        Write<'a, ScreenShake>,
+        ReadStorage<'a, Velocity>,
+        (
+            ReadStorage<'a, SelfShield>,
+            WriteStorage<'a, OneHitShield>,
+            ReadStorage<'a, DroneHost>,
+            ReadStorage<'a, HeroWeapon>,
+        ),
+        (Read<'a, HitFlashSettings>, Read<'a, HitFreeze>),
+        (ReadStorage<'a, EntitySounds>, Write<'a, SoundQueue>),
+        Write<'a, SystemErrors>,
     );
 
     fn run(
@@ -369,18 +1093,43 @@ impl<'a> System<'a> for CollisionSystem {
             healing,
             pos,
             render,
-            mut change_sprite,
-            mut shooter,
             fireball,
+            power_up,
+            mut kills,
+            lazy,
+            shield_aura,
+            metrics,
+            despawn_anim,
+            mut health,
+            mut damage_over_time,
+            mut screen_shake,
+            vel,
+            (self_shield, mut one_hit_shield, drone_host, hero_weapon),
+            (hit_flash_settings, hit_freeze),
+            (entity_sounds, mut sound_queue),
+            mut errors,
         ): Self::SystemData,
     ) {
+        let shielders: Vec<(Entity, Vector, f32)> = (&entities, &pos, &shield_aura, !&despawn_anim)
+            .join()
+            .map(|(e, pos, shield_aura, _)| (e, pos.position, shield_aura.radius))
+            .collect();
         for (e_hero, hero, hero_pos, hero_render) in (&entities, &mut hero, &pos, &render).join() {
-            for (e, enemy_pos, enemy_render, enemy) in (&entities, &pos, &render, &enemy).join() {
+            let lives_before = hero.lives;
+            hero.weapon_drain_cooldown = if hero.weapon_drain_cooldown > FRAME_DURATION {
+                hero.weapon_drain_cooldown - FRAME_DURATION
+            } else {
+                Duration::from_millis(0)
+            };
+            for (e, enemy_pos, enemy_render, enemy, _) in
+                (&entities, &pos, &render, &enemy, !&despawn_anim).join()
+            {
+                if kills.kills.iter().any(|kill| kill.enemy == e) {
+                    continue;
+                }
                 let boss: Option<&mut Boss> = boss.get_mut(e);
                 match boss {
                     Some(boss) => {
-                        let change_sprite: Option<&mut ChangeSprite> = change_sprite.get_mut(e);
-                        let shooter: Option<&mut Shooter> = shooter.get_mut(e);
                         CollisionSystem::hero_boss_collision(
                             &mut flag,
                             hero,
@@ -390,45 +1139,219 @@ impl<'a> System<'a> for CollisionSystem {
                             enemy_render,
                             hero_pos.position,
                             enemy_pos.position,
+                            metrics.elapsed,
                             &entities,
                             e,
-                            change_sprite,
-                            shooter,
+                            &mut errors,
                         );
                     }
                     None => {
-                        CollisionSystem::hero_enemy_collision(
+                        let drain_ready = hero.weapon_drain_cooldown == Duration::from_millis(0);
+                        let drainable = hero_weapon
+                            .get(e_hero)
+                            .is_some_and(|weapon| weapon.level.is_some());
+                        let stomped = CollisionSystem::hero_enemy_collision(
                             hero,
-                            enemy,
+                            enemy.weapon_drain.filter(|_| drainable),
                             hero_render,
                             enemy_render,
                             hero_pos.position,
                             enemy_pos.position,
-                            &entities,
-                            e,
+                            crate::enemy::is_shielded(e, enemy_pos.position, &shielders)
+                                || crate::enemy::is_self_shielded(self_shield.get(e)),
                         );
+                        if stomped && one_hit_shield.remove(e).is_none() {
+                            kills.kills.push(Kill {
+                                enemy: e,
+                                position: enemy_pos.position,
+                                hero_id: Some(e_hero.id()),
+                            });
+                        }
+                        if drain_ready && hero.weapon_drain_cooldown > Duration::from_millis(0) {
+                            lazy.exec_mut(move |world| {
+                                apply_weapon_drain(world, e_hero);
+                            });
+                        }
                     }
                 };
             }
 
-            for (e, healing_pos, healing_render, healing) in
-                (&entities, &pos, &render, &healing).join()
+            for (e_fireball, fireball_pos, fireball_render, fireball) in
+                (&entities, &pos, &render, &fireball).join()
             {
-                CollisionSystem::hero_healing_collision(
-                    hero,
-                    healing,
-                    hero_render,
-                    healing_render,
+                if fireball.faction != Faction::Hero || fireball.owner_id != Some(e_hero.id()) {
+                    continue;
+                }
+                for (e, enemy_pos, enemy_render, enemy, _) in
+                    (&entities, &pos, &render, &enemy, !&despawn_anim).join()
+                {
+                    if kills.kills.iter().any(|kill| kill.enemy == e)
+                        || !CollisionSystem::fireball_enemy_overlap(
+                            fireball_render,
+                            enemy_render,
+                            fireball_pos.position,
+                            enemy_pos.position,
+                        )
+                    {
+                        continue;
+                    }
+                    let contact_damage = fireball.contact_damage;
+                    if contact_damage.is_none() {
+                        delete_entity(&entities, e_fireball, &mut errors);
+                    }
+                    let approach = vel.get(e_fireball).map_or(Vector::ZERO, |vel| vel.velocity);
+                    let reflect_facing = enemy
+                        .armor
+                        .filter(|_| enemy.reflects && contact_damage.is_none());
+                    if crate::enemy::is_armor_blocked(enemy, approach) {
+                        if let Some(facing) = reflect_facing {
+                            let fireball_config = FireballConfig {
+                                sprite: fireball_render.sprite.clone(),
+                                position: fireball_pos.position,
+                                velocity: crate::enemy::get_reflected_velocity(approach, facing),
+                                damage_over_time: None,
+                                contact_damage: None,
+                            };
+                            crate::enemy::create_fireball(
+                                lazy.create_entity(&entities),
+                                Some(e.id()),
+                                Faction::Enemy,
+                                fireball_config,
+                            );
+                        } else {
+                            crate::particle::create_particle(
+                                lazy.create_entity(&entities),
+                                fireball_pos.position,
+                                ParticleConfig {
+                                    color: Color::ORANGE,
+                                    radius: 5.0,
+                                    velocity: Vector::ZERO,
+                                    lifetime_ms: 150,
+                                },
+                            );
+                        }
+                    } else if one_hit_shield.remove(e).is_some() {
+                        crate::particle::create_particle(
+                            lazy.create_entity(&entities),
+                            fireball_pos.position,
+                            ParticleConfig {
+                                color: Color::CYAN,
+                                radius: 5.0,
+                                velocity: Vector::ZERO,
+                                lifetime_ms: 150,
+                            },
+                        );
+                    } else if !crate::enemy::is_shielded(e, enemy_pos.position, &shielders)
+                        && !crate::enemy::is_self_shielded(self_shield.get(e))
+                        && !crate::enemy::is_drone_shielded(drone_host.get(e))
+                    {
+                        let damaged = match boss.get_mut(e) {
+                            Some(_) if contact_damage.is_some() => false,
+                            Some(boss) => CollisionSystem::damage_boss(
+                                &mut flag,
+                                hero,
+                                boss,
+                                metrics.elapsed,
+                                &entities,
+                                e,
+                                &mut errors,
+                            ),
+                            None => {
+                                let impact = match (fireball.damage_over_time, contact_damage) {
+                                    (Some(_), _) => 0,
+                                    (None, Some(contact_damage)) => contact_damage.per_tick,
+                                    (None, None) => 1,
+                                };
+                                let alive = match health.get_mut(e) {
+                                    Some(health) => {
+                                        health.current -= impact;
+                                        health.current > 0
+                                    }
+                                    None => false,
+                                };
+                                if alive {
+                                    if let Some(sounds) = entity_sounds.get(e) {
+                                        sound_queue.push(&sounds.hit, SoundPriority::Normal);
+                                    }
+                                    if hit_flash_settings.color.is_some() {
+                                        lazy.insert(
+                                            e,
+                                            HitFlash {
+                                                remaining: hit_flash_settings.duration,
+                                            },
+                                        );
+                                    }
+                                    if let Some(freeze_ms) = hit_freeze.freeze_ms {
+                                        lazy.exec_mut(move |world| {
+                                            apply_freeze(world, e, freeze_ms);
+                                        });
+                                    }
+                                    if let Some(config) = fireball.damage_over_time {
+                                        apply_damage_over_time(
+                                            &mut damage_over_time,
+                                            e,
+                                            Some(e_hero.id()),
+                                            config,
+                                        );
+                                    }
+                                } else {
+                                    kills.kills.push(Kill {
+                                        enemy: e,
+                                        position: enemy_pos.position,
+                                        hero_id: Some(e_hero.id()),
+                                    });
+                                }
+                                false
+                            }
+                        };
+                        if damaged {
+                            crate::hero::add_kill_score(hero, enemy.score);
+                        }
+                    }
+                    if contact_damage.is_none() {
+                        break;
+                    }
+                }
+            }
+
+            for (e, healing_pos, healing_render, healing) in
+                (&entities, &pos, &render, &healing).join()
+            {
+                CollisionSystem::hero_healing_collision(
+                    hero,
+                    healing,
+                    hero_render,
+                    healing_render,
                     hero_pos.position,
                     healing_pos.position,
                     &entities,
                     e,
+                    &mut errors,
                 );
             }
 
-            for (e, fireball_pos, fireball_render, _) in
+            for (e, power_up_pos, power_up_render, power_up) in
+                (&entities, &pos, &render, &power_up).join()
+            {
+                CollisionSystem::hero_power_up_collision(
+                    hero,
+                    power_up,
+                    hero_render,
+                    power_up_render,
+                    hero_pos.position,
+                    power_up_pos.position,
+                    &entities,
+                    e,
+                    &mut errors,
+                );
+            }
+
+            for (e, fireball_pos, fireball_render, fireball) in
                 (&entities, &pos, &render, &fireball).join()
             {
+                if fireball.faction != Faction::Enemy {
+                    continue;
+                }
                 CollisionSystem::hero_fireball_collision(
                     hero,
                     hero_render,
@@ -437,26 +1360,33 @@ impl<'a> System<'a> for CollisionSystem {
                     fireball_pos.position,
                     &entities,
                     e,
+                    &mut errors,
                 );
             }
 
+            if hero.lives < lives_before {
+                screen_shake.start();
+            }
             if hero.lives == 0 {
-                flag.flag = Some(GameStateFlag::Defeat);
-                entities.delete(e_hero).unwrap();
+                delete_entity(&entities, e_hero, &mut errors);
             }
         }
+
+        if (&hero).join().all(|hero| hero.lives == 0) {
+            flag.flag = Some(GameStateFlag::Defeat);
+        }
     }
 }
 
 pub struct HeroBlinkingSystem;
 
 impl<'a> System<'a> for HeroBlinkingSystem {
-    type SystemData = (WriteStorage<'a, Hero>);
+    type SystemData = WriteStorage<'a, Hero>;
 
     fn run(&mut self, mut hero: Self::SystemData) {
         for hero in (&mut hero).join() {
             if hero.blinking {
-                hero.blink_timer += Duration::from_nanos(16700000);
+                hero.blink_timer += FRAME_DURATION;
 
                 let blinking_time_sec = hero.blink_timer.as_secs() as f64
                     + (f64::from(hero.blink_timer.subsec_nanos()) * 1e-9);
@@ -484,19 +1414,66 @@ impl<'a> System<'a> for FireballSystem {
         WriteStorage<'a, Shooter>,
         ReadStorage<'a, Fireball>,
         Read<'a, LazyUpdate>,
+        Read<'a, PressedKeys>,
+        ReadStorage<'a, PlayerId>,
+        WriteStorage<'a, HeroWeapon>,
+        Read<'a, GameMetrics>,
+        Read<'a, FireGrace>,
+        ReadStorage<'a, DespawnAnim>,
+        Read<'a, DifficultyRes>,
+        Write<'a, HeroShots>,
+        Read<'a, NoFireZones>,
+        ReadStorage<'a, Overdrive>,
+        Write<'a, Pacifist>,
+        Read<'a, RubberBand>,
+        Write<'a, RandomGenerator>,
     );
 
-    fn run(&mut self, (entities, mut pos, mut shooter, fireball, lazy): Self::SystemData) {
-        for (e, pos, shooter) in (&entities, &mut pos, &mut shooter).join() {
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut pos,
+            mut shooter,
+            fireball,
+            lazy,
+            pressed_keys,
+            player,
+            mut weapon,
+            metrics,
+            fire_grace,
+            despawn_anim,
+            difficulty,
+            mut hero_shots,
+            no_fire_zones,
+            overdrive,
+            mut pacifist,
+            rubber_band,
+            mut rng,
+        ): Self::SystemData,
+    ) {
+        hero_shots.shots.clear();
+        let tick = FRAME_DURATION.mul_f32(rubber_band.factor);
+        for (e, pos, shooter, _) in (&entities, &mut pos, &mut shooter, !&despawn_anim).join() {
+            if metrics.elapsed < fire_grace.duration {
+                continue;
+            }
             shooter.fireball_amount = 0;
             for fireball in (&fireball).join() {
                 if fireball.owner_id.is_some() && fireball.owner_id.unwrap() == e.id() {
                     shooter.fireball_amount += 1;
                 }
             }
+            if shooter.timer > tick {
+                shooter.timer -= tick;
+            } else {
+                shooter.timer = Duration::from_millis(0);
+            }
 
-            while shooter.fireball_amount < shooter.maximum_fireballs {
-                let randomness = rand::random::<f32>() / 12.;
+            while shooter.fireball_amount < shooter.maximum_fireballs
+                && shooter.timer == Duration::from_millis(0)
+            {
+                let randomness = rng.rng.gen::<f32>() / 12.;
 
                 let fireball_config = FireballConfig {
                     sprite: shooter.projectile_sprite.clone(),
@@ -507,16 +1484,1490 @@ impl<'a> System<'a> for FireballSystem {
                                 + shooter.coefficient.1)
                                 + randomness),
                         0.0,
-                    ),
+                    ) * difficulty.multiplier,
+                    damage_over_time: None,
+                    contact_damage: None,
                 };
                 crate::enemy::create_fireball(
                     lazy.create_entity(&entities),
                     Some(e.id()),
+                    Faction::Enemy,
                     fireball_config,
                 );
 
                 shooter.fireball_amount += 1;
+                shooter.timer = shooter.cooldown;
+            }
+        }
+
+        for (e, pos, weapon, player) in (&entities, &pos, &mut weapon, &player).join() {
+            if weapon.cooldown > FRAME_DURATION {
+                weapon.cooldown -= FRAME_DURATION;
+            } else {
+                weapon.cooldown = Duration::from_millis(0);
+            }
+
+            let keys = &pressed_keys.pressed_keys[*player as usize];
+            if !keys.contains(KeyboardKeys::KeyFire as u32)
+                || weapon.cooldown > Duration::from_millis(0)
+                || weapon.modes.is_empty()
+                || crate::hero::is_in_no_fire_zone(&no_fire_zones.zones, pos.position)
+            {
+                continue;
+            }
+
+            let mode = &weapon.modes[weapon.current_mode];
+            for direction in crate::enemy::get_emitter_directions(mode.pattern, weapon.aim) {
+                let fireball_config = FireballConfig {
+                    sprite: mode.projectile_sprite.clone(),
+                    position: pos.position,
+                    velocity: direction * mode.projectile_speed,
+                    damage_over_time: mode.damage_over_time,
+                    contact_damage: mode.contact_damage,
+                };
+                hero_shots.shots.push(HeroShot {
+                    sprite: mode.projectile_sprite.clone(),
+                    speed: mode.projectile_speed,
+                });
+                pacifist.eligible = false;
+                let mut builder = lazy.create_entity(&entities);
+                if weapon.fireball_trail {
+                    builder = builder.with(Trail {
+                        color: Color::ORANGE,
+                        interval: TRAIL_INTERVAL,
+                        timer: Duration::from_millis(0),
+                    });
+                }
+                crate::enemy::create_fireball(
+                    builder,
+                    Some(e.id()),
+                    Faction::Hero,
+                    fireball_config,
+                );
+            }
+            weapon.cooldown = crate::hero::get_overdrive_cooldown(
+                Duration::from_millis(mode.cooldown_ms),
+                overdrive.get(e),
+            );
+        }
+    }
+}
+
+pub struct EmitterSystem;
+
+impl<'a> System<'a> for EmitterSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Hero>,
+        WriteStorage<'a, Emitter>,
+        Read<'a, LazyUpdate>,
+        Read<'a, GameMetrics>,
+        Read<'a, FireGrace>,
+        ReadStorage<'a, DespawnAnim>,
+        Read<'a, RubberBand>,
+        ReadStorage<'a, FreezeFor>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            pos,
+            hero,
+            mut emitter,
+            lazy,
+            metrics,
+            fire_grace,
+            despawn_anim,
+            rubber_band,
+            freeze,
+        ): Self::SystemData,
+    ) {
+        if metrics.elapsed < fire_grace.duration {
+            return;
+        }
+        let hero_position = (&pos, &hero).join().map(|(pos, _)| pos.position).next();
+
+        for (pos, emitter, _, _) in (&pos, &mut emitter, !&despawn_anim, !&freeze).join() {
+            if emitter.delay > Duration::from_millis(0) {
+                emitter.delay = if emitter.delay > FRAME_DURATION {
+                    emitter.delay - FRAME_DURATION
+                } else {
+                    Duration::from_millis(0)
+                };
+                continue;
+            }
+            emitter.timer += FRAME_DURATION.mul_f32(rubber_band.factor);
+            if emitter.timer < emitter.interval {
+                continue;
+            }
+            emitter.timer = Duration::from_millis(0);
+
+            let aim = match hero_position {
+                Some(hero_position) => hero_position - pos.position,
+                None => Vector::new(-1.0, 0.0),
+            };
+            for direction in crate::enemy::get_emitter_directions(emitter.pattern, aim) {
+                let fireball_config = FireballConfig {
+                    sprite: emitter.projectile_sprite.clone(),
+                    position: pos.position,
+                    velocity: direction * emitter.projectile_speed,
+                    damage_over_time: None,
+                    contact_damage: None,
+                };
+                crate::enemy::create_fireball(
+                    lazy.create_entity(&entities),
+                    None,
+                    Faction::Enemy,
+                    fireball_config,
+                );
+            }
+        }
+    }
+}
+
+pub struct FollowParentSystem;
+
+impl<'a> System<'a> for FollowParentSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, FollowParent>,
+        WriteStorage<'a, Boss>,
+        WriteStorage<'a, DroneHost>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, DespawnAnim>,
+        Write<'a, SystemErrors>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut follow_parent,
+            mut boss,
+            mut drone_host,
+            mut pos,
+            despawn_anim,
+            mut errors,
+        ): Self::SystemData,
+    ) {
+        let mut alive: Vec<Entity> = Vec::new();
+        for (e, follow_parent, _) in (&entities, &mut follow_parent, !&despawn_anim).join() {
+            let parent_position = match pos.get(follow_parent.parent) {
+                Some(parent_pos) => parent_pos.position,
+                None => {
+                    delete_entity(&entities, e, &mut errors);
+                    continue;
+                }
+            };
+            follow_parent.offset = crate::enemy::get_rotated_offset(
+                follow_parent.offset,
+                follow_parent.orbit_speed * FRAME_DURATION.as_secs_f32(),
+            );
+            if let Some(pos) = pos.get_mut(e) {
+                pos.position = parent_position + follow_parent.offset;
+            }
+            alive.push(follow_parent.parent);
+        }
+        for (e, boss) in (&entities, &mut boss).join() {
+            boss.parts_alive = alive.iter().filter(|&&parent| parent == e).count() as u32;
+        }
+        for (e, drone_host) in (&entities, &mut drone_host).join() {
+            drone_host.drones_alive = alive.iter().filter(|&&parent| parent == e).count() as u32;
+        }
+    }
+}
+
+pub struct RiskZoneSystem;
+
+impl<'a> System<'a> for RiskZoneSystem {
+    type SystemData = (
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, PlayerId>,
+        WriteStorage<'a, Hero>,
+        Read<'a, PressedKeys>,
+        Read<'a, PlayField>,
+    );
+
+    fn run(&mut self, (pos, player, mut hero, pressed_keys, play_field): Self::SystemData) {
+        for (pos, player, hero) in (&pos, &player, &mut hero).join() {
+            let firing =
+                pressed_keys.pressed_keys[*player as usize].contains(KeyboardKeys::KeyFire as u32);
+            hero.base_score_multiplier = match hero.risk_zone {
+                Some(risk_zone)
+                    if firing && play_field.size.y - pos.position.y <= risk_zone.distance =>
+                {
+                    risk_zone.multiplier
+                }
+                _ => 1.0,
+            };
+            hero.score_multiplier = hero.base_score_multiplier;
+        }
+    }
+}
+
+pub struct LifetimeSystem;
+
+impl<'a> System<'a> for LifetimeSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Lifetime>,
+        Write<'a, SystemErrors>,
+    );
+
+    fn run(&mut self, (entities, mut lifetime, mut errors): Self::SystemData) {
+        for (e, lifetime) in (&entities, &mut lifetime).join() {
+            if lifetime.remaining > FRAME_DURATION {
+                lifetime.remaining -= FRAME_DURATION;
+            } else {
+                delete_entity(&entities, e, &mut errors);
+            }
+        }
+    }
+}
+
+pub struct DamageOverTimeSystem;
+
+impl<'a> System<'a> for DamageOverTimeSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Health>,
+        WriteStorage<'a, DamageOverTime>,
+        ReadStorage<'a, DespawnAnim>,
+        Write<'a, Kills>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            enemy,
+            pos,
+            mut health,
+            mut damage_over_time,
+            despawn_anim,
+            mut kills,
+        ): Self::SystemData,
+    ) {
+        let frame_ms = FRAME_DURATION.as_millis() as u64;
+        let mut expired: Vec<Entity> = Vec::new();
+        for (e, _, pos, health, damage_over_time, _) in (
+            &entities,
+            &enemy,
+            &pos,
+            &mut health,
+            &mut damage_over_time,
+            !&despawn_anim,
+        )
+            .join()
+        {
+            damage_over_time.timer_ms += frame_ms;
+            while damage_over_time.interval_ms > 0
+                && damage_over_time.timer_ms >= damage_over_time.interval_ms
+            {
+                damage_over_time.timer_ms -= damage_over_time.interval_ms;
+                health.current -= damage_over_time.per_tick;
+            }
+
+            if health.current <= 0 {
+                kills.kills.push(Kill {
+                    enemy: e,
+                    position: pos.position,
+                    hero_id: damage_over_time.source_id,
+                });
+            } else if damage_over_time.remaining_ms > frame_ms {
+                damage_over_time.remaining_ms -= frame_ms;
+            } else {
+                expired.push(e);
+            }
+        }
+        for e in expired {
+            damage_over_time.remove(e);
+        }
+    }
+}
+
+pub struct KillSystem;
+
+impl<'a> System<'a> for KillSystem {
+    type SystemData = (
+        Entities<'a>,
+        Write<'a, Kills>,
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, Boss>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, DespawnAnim>,
+        WriteStorage<'a, Hero>,
+        WriteStorage<'a, Combo>,
+        Write<'a, GameMetrics>,
+        (ReadStorage<'a, EntitySounds>, Write<'a, SoundQueue>),
+        ReadStorage<'a, BombDrop>,
+        ReadStorage<'a, DropTable>,
+        Write<'a, RandomGenerator>,
+        ReadStorage<'a, ShieldDonor>,
+        WriteStorage<'a, OneHitShield>,
+        Read<'a, LazyUpdate>,
+        Write<'a, SystemErrors>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut kills,
+            enemy,
+            boss,
+            pos,
+            despawn_anim,
+            mut hero,
+            mut combo,
+            mut metrics,
+            (entity_sounds, mut sound_queue),
+            bomb_drop,
+            drop_table,
+            mut rng,
+            shield_donor,
+            mut one_hit_shield,
+            lazy,
+            mut errors,
+        ): Self::SystemData,
+    ) {
+        let mut killed: Vec<Kill> = Vec::new();
+        for kill in kills.kills.drain(..) {
+            if entities.is_alive(kill.enemy)
+                && despawn_anim.get(kill.enemy).is_none()
+                && !killed.iter().any(|killed| killed.enemy == kill.enemy)
+            {
+                killed.push(kill);
+            }
+        }
+
+        let survivors: Vec<(Entity, Vector)> = (&entities, &pos, &enemy, !&boss, !&despawn_anim)
+            .join()
+            .filter(|&(e, _, _, _, _)| !killed.iter().any(|kill| kill.enemy == e))
+            .map(|(e, pos, _, _, _)| (e, pos.position))
+            .collect();
+        let mut scorers: Vec<Entity> = Vec::new();
+        for kill in killed {
+            let e = kill.enemy;
+            metrics.kills += 1;
+            if let Some(sounds) = entity_sounds.get(e) {
+                sound_queue.push(&sounds.death, SoundPriority::High);
+            }
+            if let Some(enemy) = enemy.get(e) {
+                let scorer = kill.hero_id.map(|hero_id| entities.entity(hero_id));
+                if let Some((scorer, hero)) = scorer.and_then(|e| Some((e, hero.get_mut(e)?))) {
+                    crate::hero::add_kill_score(hero, enemy.score);
+                    if !scorers.contains(&scorer) {
+                        scorers.push(scorer);
+                    }
+                }
+            }
+            match enemy.get(e) {
+                Some(enemy) if enemy.despawn_ms > 0 => {
+                    lazy.insert(e, get_despawn_anim(enemy));
+                }
+                _ => delete_entity(&entities, e, &mut errors),
+            }
+            if let Some(bomb_drop) = bomb_drop.get(e) {
+                crate::power_up::create_bomb_drop(
+                    lazy.create_entity(&entities),
+                    kill.position,
+                    bomb_drop,
+                );
             }
+            if let Some(drop_table) = drop_table.get(e) {
+                let power_up_config =
+                    crate::power_up::roll_drop_table(&drop_table.entries, &mut rng.rng);
+                if let Some(power_up_config) = power_up_config {
+                    crate::power_up::create_power_up(
+                        lazy.create_entity(&entities),
+                        kill.position,
+                        power_up_config,
+                    );
+                }
+            }
+            if shield_donor.get(e).is_some() {
+                if let Some(survivor) = crate::enemy::find_nearest_enemy(kill.position, &survivors)
+                {
+                    one_hit_shield.insert(survivor, OneHitShield).unwrap();
+                }
+            }
+        }
+        for scorer in scorers {
+            if let Some(combo) = combo.get_mut(scorer) {
+                crate::hero::register_combo_kill(combo);
+            }
+        }
+    }
+}
+
+pub struct DespawnSystem;
+
+impl<'a> System<'a> for DespawnSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, DespawnAnim>,
+        Write<'a, SystemErrors>,
+    );
+
+    fn run(&mut self, (entities, mut despawn_anim, mut errors): Self::SystemData) {
+        let frame_ms = FRAME_DURATION.as_millis() as u64;
+        for (e, despawn_anim) in (&entities, &mut despawn_anim).join() {
+            if despawn_anim.remaining_ms > frame_ms {
+                despawn_anim.remaining_ms -= frame_ms;
+            } else {
+                delete_entity(&entities, e, &mut errors);
+            }
+        }
+    }
+}
+
+pub struct MetricsSystem;
+
+impl<'a> System<'a> for MetricsSystem {
+    type SystemData = Write<'a, GameMetrics>;
+
+    fn run(&mut self, mut metrics: Self::SystemData) {
+        metrics.elapsed += FRAME_DURATION;
+    }
+}
+
+fn apply_weapon_drain(world: &mut World, e: Entity) {
+    if let Some(weapon) = world.write_storage::<HeroWeapon>().get_mut(e) {
+        crate::hero::downgrade_weapon(weapon);
+    }
+}
+
+fn apply_freeze(world: &mut World, e: Entity, freeze_ms: u64) {
+    let mut freeze = world.write_storage::<FreezeFor>();
+    let saved_velocity = freeze.get(e).and_then(|freeze| freeze.saved_velocity);
+    let _ = freeze.insert(
+        e,
+        FreezeFor {
+            remaining_ms: freeze_ms,
+            saved_velocity,
+        },
+    );
+}
+
+fn update_dash(dash: &mut Dash, pressed: bool) -> bool {
+    if dash.cooldown_timer > FRAME_DURATION {
+        dash.cooldown_timer -= FRAME_DURATION;
+    } else {
+        dash.cooldown_timer = Duration::from_millis(0);
+    }
+    if pressed && !dash.held && dash.cooldown_timer == Duration::from_millis(0) {
+        dash.remaining = dash.duration;
+        dash.cooldown_timer = dash.cooldown;
+    }
+    dash.held = pressed;
+
+    if dash.remaining > FRAME_DURATION {
+        dash.remaining -= FRAME_DURATION;
+        true
+    } else {
+        dash.remaining = Duration::from_millis(0);
+        false
+    }
+}
+
+pub fn get_outcome_tint(outcome_tint: &OutcomeTint, flag: Option<GameStateFlag>) -> Option<Color> {
+    match flag {
+        Some(GameStateFlag::Defeat) => outcome_tint.defeat,
+        Some(GameStateFlag::Victory) => outcome_tint.victory,
+        None => None,
+    }
+}
+
+pub fn resolve_anchor(anchor: &Anchor, field_size: Vector) -> Vector {
+    match anchor.corner {
+        Corner::TopLeft => anchor.offset,
+        Corner::TopRight => Vector::new(field_size.x - anchor.offset.x, anchor.offset.y),
+        Corner::BottomLeft => Vector::new(anchor.offset.x, field_size.y - anchor.offset.y),
+        Corner::BottomRight => field_size - anchor.offset,
+        Corner::Center => field_size * 0.5 + anchor.offset,
+    }
+}
+
+fn get_despawn_anim(enemy: &Enemy) -> DespawnAnim {
+    DespawnAnim {
+        remaining_ms: enemy.despawn_ms,
+        duration_ms: enemy.despawn_ms,
+    }
+}
+
+pub(crate) fn delete_entity(entities: &Entities, e: Entity, errors: &mut SystemErrors) {
+    if let Err(error) = entities.delete(e) {
+        errors.errors.push(error.to_string());
+    }
+}
+
+fn apply_damage_over_time(
+    damage_over_time: &mut WriteStorage<DamageOverTime>,
+    e: Entity,
+    source_id: Option<Index>,
+    config: DamageOverTimeConfig,
+) {
+    if let Some(damage_over_time) = damage_over_time.get_mut(e) {
+        crate::enemy::stack_damage_over_time(damage_over_time, config);
+        damage_over_time.source_id = source_id;
+        return;
+    }
+    let _ = damage_over_time.insert(e, crate::enemy::create_damage_over_time(source_id, config));
+}
+
+fn is_announcement_suppressed(filter: &AnnouncementFilter, boss_alive: bool) -> bool {
+    filter.suppress_during_boss && boss_alive
+}
+
+pub(crate) fn create_announcement(
+    lazy: &LazyUpdate,
+    entities: &Entities,
+    dictionary: &mut VariableDictionary,
+    filter: &AnnouncementFilter,
+    boss_alive: bool,
+    text: String,
+) {
+    if is_announcement_suppressed(filter, boss_alive) {
+        return;
+    }
+    dictionary
+        .dictionary
+        .insert(LabelVariable::Announcement, text);
+    lazy.create_entity(entities)
+        .with(Label {
+            bind_variable: LabelVariable::Announcement,
+            font_style: FontStyle::new(36.0, Color::BLACK),
+            visible: true,
+        })
+        .with(Position {
+            position: Vector::new(400, 150),
+        })
+        .with(Anchor {
+            corner: Corner::Center,
+            offset: Vector::new(0, -150),
+        })
+        .with(Lifetime {
+            remaining: ANNOUNCEMENT_DURATION,
+        })
+        .build();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        combo::OverdriveSystem,
+        component::PlayerId,
+        difficulty::RubberBandConfig,
+        enemy::{
+            BossConfig, BossPartConfig, BossTimeBonusConfig, ContactDamage, DotStacking,
+            EmitterConfig, EmitterPattern,
+        },
+        hero::{
+            ComboConfig, FocusConfig, HeroConfig, NoFireZoneConfig, OverdriveConfig, RiskZoneConfig,
+        },
+        scene::{create_world, SceneConfig},
+        test_support::{
+            count_fireballs, create_test_enemy, fire_hero_dot_fireball, fire_hero_fireball, run,
+            set_bounding_box, set_key, weapon_mode,
+        },
+    };
+    use specs::RunNow;
+
+    #[test]
+    fn hero_stops_at_play_field_edge_minus_margin() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            position: Vector::new(2000, 300),
+            movement_margin: 15.0,
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        let oversized = HeroConfig {
+            movement_margin: 1000.0,
+            ..HeroConfig::default()
+        };
+        crate::hero::create_hero(&mut world, oversized, PlayerId::Two);
+
+        run(&mut world, OutOfBoundsSystem);
+
+        let size = world.read_resource::<PlayField>().size;
+        let pos = world.read_storage::<Position>().get(hero).unwrap().position;
+        assert_eq!(pos, Vector::new(size.x - 15.0, 300.0));
+    }
+
+    fn get_risk_zone_kill_score(y: f32, firing: bool) -> i32 {
+        let mut world = create_world(&SceneConfig::default());
+        let field_size = world.read_resource::<PlayField>().size;
+        let config = HeroConfig {
+            position: Vector::new(400.0, field_size.y - y),
+            risk_zone: Some(RiskZoneConfig {
+                distance: 50.0,
+                multiplier: 2.0,
+            }),
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        if firing {
+            world.write_resource::<PressedKeys>().pressed_keys[0].add(KeyboardKeys::KeyFire as u32);
+        }
+
+        run(&mut world, RiskZoneSystem);
+
+        let hero = *world.read_storage::<Hero>().get(hero).unwrap();
+        crate::hero::get_kill_score(&hero, 100)
+    }
+
+    #[test]
+    fn risk_zone_kill_scores_more_while_firing_near_bottom() {
+        assert_eq!(get_risk_zone_kill_score(20.0, true), 200);
+        assert_eq!(get_risk_zone_kill_score(200.0, true), 100);
+        assert_eq!(get_risk_zone_kill_score(20.0, false), 100);
+    }
+
+    #[test]
+    fn shield_aura_blocks_damage_until_shielder_dies() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let shielder = create_test_enemy(&mut world, Vector::new(300, 400), 1);
+        world
+            .write_storage::<ShieldAura>()
+            .insert(shielder, ShieldAura { radius: 200.0 })
+            .unwrap();
+        let enemy = create_test_enemy(&mut world, Vector::new(400, 400), 2);
+        let health = |world: &World| world.read_storage::<Health>().get(enemy).unwrap().current;
+
+        fire_hero_fireball(&mut world, hero, Vector::new(400, 400));
+        run(&mut world, CollisionSystem);
+        assert_eq!(health(&world), 2);
+
+        world
+            .write_storage::<DespawnAnim>()
+            .insert(
+                shielder,
+                DespawnAnim {
+                    remaining_ms: 100,
+                    duration_ms: 100,
+                },
+            )
+            .unwrap();
+        fire_hero_fireball(&mut world, hero, Vector::new(400, 400));
+        run(&mut world, CollisionSystem);
+        assert_eq!(health(&world), 1);
+    }
+
+    #[test]
+    fn enemy_stomped_by_both_heroes_scores_once() {
+        let mut world = create_world(&SceneConfig::default());
+        let enemy = create_test_enemy(&mut world, Vector::new(400, 400), 1);
+        let config = HeroConfig {
+            position: Vector::new(400, 362),
+            ..HeroConfig::default()
+        };
+        for player in [PlayerId::One, PlayerId::Two] {
+            let hero = crate::hero::create_hero(&mut world, config.clone(), player);
+            set_bounding_box(&mut world, hero, Vector::new(40, 40));
+        }
+
+        run(&mut world, CollisionSystem);
+        run(&mut world, KillSystem);
+
+        let total: i32 = (&world.read_storage::<Hero>())
+            .join()
+            .map(|hero| hero.score)
+            .sum();
+        assert_eq!(total, 100);
+        assert!(!world.is_alive(enemy));
+    }
+
+    #[test]
+    fn cycling_past_last_weapon_mode_wraps_to_first() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            weapon_modes: vec![
+                weapon_mode(EmitterPattern::Aimed),
+                weapon_mode(EmitterPattern::Arc(3, 30.0)),
+            ],
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        let mut modes = Vec::new();
+        for _ in 0..2 {
+            set_key(&mut world, KeyboardKeys::KeyCycleWeapon, true);
+            run(&mut world, HeroControlSystem);
+            set_key(&mut world, KeyboardKeys::KeyCycleWeapon, false);
+            run(&mut world, HeroControlSystem);
+            modes.push(
+                world
+                    .read_storage::<HeroWeapon>()
+                    .get(hero)
+                    .unwrap()
+                    .current_mode,
+            );
+        }
+        assert_eq!(modes, vec![1, 0]);
+
+        set_key(&mut world, KeyboardKeys::KeyFire, true);
+        run(&mut world, FireballSystem);
+        assert_eq!(count_fireballs(&world, Faction::Hero), 1);
+    }
+
+    #[test]
+    fn shooters_hold_fire_during_grace_period() {
+        let mut config = SceneConfig::default();
+        config.entity_factory_config.fire_grace_ms = 1000;
+        let mut world = create_world(&config);
+        crate::enemy::create_shooter(&mut world);
+
+        world.write_resource::<GameMetrics>().elapsed = Duration::from_millis(500);
+        run(&mut world, FireballSystem);
+        assert_eq!(count_fireballs(&world, Faction::Enemy), 0);
+
+        world.write_resource::<GameMetrics>().elapsed = Duration::from_millis(1500);
+        run(&mut world, FireballSystem);
+        assert_eq!(count_fireballs(&world, Faction::Enemy), 2);
+    }
+
+    #[test]
+    fn despawning_enemy_shrinks_until_animation_completes() {
+        let mut world = create_world(&SceneConfig::default());
+        let enemy = create_test_enemy(&mut world, Vector::new(400, 400), 1);
+        let despawn_anim = get_despawn_anim(&Enemy {
+            despawn_ms: 100,
+            ..*world.read_storage::<Enemy>().get(enemy).unwrap()
+        });
+        world
+            .write_storage::<DespawnAnim>()
+            .insert(enemy, despawn_anim)
+            .unwrap();
+
+        let mut scale = 1.0;
+        for _ in 0..6 {
+            run(&mut world, DespawnSystem);
+            assert!(world.is_alive(enemy));
+            let next_scale =
+                RenderSystem::get_scale(world.read_storage::<DespawnAnim>().get(enemy));
+            assert!(next_scale < scale);
+            scale = next_scale;
+        }
+        run(&mut world, DespawnSystem);
+        assert!(!world.is_alive(enemy));
+    }
+
+    fn poison(world: &mut World, enemy_health: i32) -> (Entity, Entity) {
+        let hero = crate::hero::create_hero(world, HeroConfig::default(), PlayerId::One);
+        let enemy = create_test_enemy(world, Vector::new(400, 400), enemy_health);
+        let config = DamageOverTimeConfig {
+            per_tick: 1,
+            interval_ms: 100,
+            duration_ms: 250,
+            stacking: DotStacking::Refresh,
+        };
+        fire_hero_dot_fireball(world, hero, Vector::new(400, 400), Some(config));
+        run(world, CollisionSystem);
+        (hero, enemy)
+    }
+
+    fn run_damage_over_time(world: &mut World, frames: u32) {
+        for _ in 0..frames {
+            run(world, DamageOverTimeSystem);
+            run(world, KillSystem);
+        }
+    }
+
+    #[test]
+    fn damage_over_time_ticks_until_it_expires() {
+        let mut world = create_world(&SceneConfig::default());
+        let (_, enemy) = poison(&mut world, 5);
+        let health = |world: &World| world.read_storage::<Health>().get(enemy).unwrap().current;
+        assert_eq!(health(&world), 5);
+
+        run_damage_over_time(&mut world, 7);
+        assert_eq!(health(&world), 4);
+        run_damage_over_time(&mut world, 23);
+        assert_eq!(health(&world), 3);
+        assert!(world.read_storage::<DamageOverTime>().get(enemy).is_none());
+    }
+
+    #[test]
+    fn damage_over_time_kill_scores_for_the_hero() {
+        let mut world = create_world(&SceneConfig::default());
+        let (hero, enemy) = poison(&mut world, 1);
+        assert!(world.is_alive(enemy));
+
+        run_damage_over_time(&mut world, 7);
+        assert!(!world.is_alive(enemy));
+        assert_eq!(world.read_storage::<Hero>().get(hero).unwrap().score, 100);
+        assert_eq!(world.read_resource::<GameMetrics>().kills, 1);
+    }
+
+    #[test]
+    fn ring_emitter_fires_evenly_spaced_fireballs() {
+        let mut world = create_world(&SceneConfig::default());
+        world
+            .create_entity()
+            .with(Position {
+                position: Vector::new(400, 300),
+            })
+            .with(crate::enemy::create_emitter(EmitterConfig {
+                pattern: EmitterPattern::Ring(8),
+                projectile_sprite: "fogo".to_string(),
+                projectile_speed: 100.0,
+                interval_ms: 0,
+            }))
+            .build();
+
+        run(&mut world, EmitterSystem);
+
+        let fireball = world.read_storage::<Fireball>();
+        let vel = world.read_storage::<Velocity>();
+        let mut angles: Vec<f32> = (&fireball, &vel)
+            .join()
+            .map(|(_, vel)| vel.velocity.angle())
+            .collect();
+        angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(angles.len(), 8);
+        for pair in angles.windows(2) {
+            assert!((pair[1] - pair[0] - 45.0).abs() < 0.01);
+        }
+    }
+
+    fn count_announcements(suppress_during_boss: bool, boss_alive: bool) -> usize {
+        let mut world = create_world(&SceneConfig::default());
+        create_announcement(
+            &world.read_resource::<LazyUpdate>(),
+            &world.entities(),
+            &mut world.write_resource::<VariableDictionary>(),
+            &AnnouncementFilter {
+                suppress_during_boss,
+            },
+            boss_alive,
+            "Wave clear!".to_string(),
+        );
+        world.maintain();
+
+        let label = world.read_storage::<Label>();
+        (&label)
+            .join()
+            .filter(|label| label.bind_variable == LabelVariable::Announcement)
+            .count()
+    }
+
+    #[test]
+    fn announcements_are_suppressed_while_the_boss_is_alive() {
+        assert_eq!(count_announcements(true, false), 1);
+        assert_eq!(count_announcements(true, true), 0);
+        assert_eq!(count_announcements(false, true), 1);
+    }
+
+    fn get_first_enemy_fireball_speed(difficulty: f32) -> f32 {
+        let mut world = create_world(&SceneConfig {
+            seed: Some(7),
+            ..SceneConfig::default()
+        });
+        world.write_resource::<DifficultyRes>().multiplier = difficulty;
+        crate::enemy::create_shooter(&mut world);
+
+        run(&mut world, FireballSystem);
+
+        let fireball = world.read_storage::<Fireball>();
+        let vel = world.read_storage::<Velocity>();
+        (&fireball, &vel)
+            .join()
+            .map(|(_, vel)| vel.velocity.x.abs())
+            .fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn enemy_fireball_speed_scales_with_difficulty() {
+        let base = 1000.0 * 0.175;
+        let spread = 1000.0 / 12.0;
+        let normal = get_first_enemy_fireball_speed(1.0);
+        let hard = get_first_enemy_fireball_speed(1.5);
+        assert!(normal >= base && normal < base + spread);
+        assert_eq!(hard, 1.5 * normal);
+    }
+
+    #[test]
+    fn outcome_selects_its_configured_tint() {
+        let outcome_tint = OutcomeTint {
+            defeat: Some(Color::RED),
+            victory: Some(Color::YELLOW),
+        };
+        let tint = |flag| get_outcome_tint(&outcome_tint, flag);
+        assert_eq!(tint(Some(GameStateFlag::Defeat)), Some(Color::RED));
+        assert_eq!(tint(Some(GameStateFlag::Victory)), Some(Color::YELLOW));
+        assert_eq!(tint(None), None);
+    }
+
+    #[test]
+    fn armored_front_blocks_fireballs_but_rear_hits_damage() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let enemy = create_test_enemy(&mut world, Vector::new(400, 300), 3);
+        world.write_storage::<Enemy>().get_mut(enemy).unwrap().armor = Some(Vector::new(-1, 0));
+        let hit = |world: &mut World, velocity: Vector| {
+            fire_hero_fireball(world, hero, Vector::new(400, 300));
+            for (_, vel) in (
+                &world.read_storage::<Fireball>(),
+                &mut world.write_storage::<Velocity>(),
+            )
+                .join()
+            {
+                vel.velocity = velocity;
+            }
+            run(world, CollisionSystem);
+            world.read_storage::<Health>().get(enemy).unwrap().current
+        };
+
+        assert_eq!(hit(&mut world, Vector::new(300, 0)), 3);
+        assert_eq!(hit(&mut world, Vector::new(-300, 0)), 2);
+    }
+
+    fn get_hero_speed(world: &mut World, hero: Entity, focus: bool) -> f32 {
+        set_key(world, KeyboardKeys::KeyFocus, focus);
+        run(world, HeroControlSystem);
+        world
+            .read_storage::<Velocity>()
+            .get(hero)
+            .unwrap()
+            .velocity
+            .x
+    }
+
+    #[test]
+    fn holding_focus_slows_the_hero_until_released() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            focus: Some(FocusConfig {
+                speed_factor: 0.4,
+                show_hitbox: false,
+            }),
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        set_key(&mut world, KeyboardKeys::KeyRight, true);
+
+        let full = get_hero_speed(&mut world, hero, false);
+        assert!(full > 0.0);
+        assert_eq!(get_hero_speed(&mut world, hero, true), full * 0.4);
+        assert_eq!(get_hero_speed(&mut world, hero, false), full);
+    }
+
+    fn get_boss_kill_score(spawned_at_ms: u64, killed_at_ms: u64) -> i32 {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        crate::enemy::create_boss(
+            &mut world,
+            BossConfig {
+                position: Vector::new(400, 400),
+                lives: 1,
+                time_bonus: Some(BossTimeBonusConfig {
+                    max_bonus: 1000,
+                    decay_per_second: 100.0,
+                }),
+                ..BossConfig::default()
+            },
+        );
+        let boss = (&world.entities(), &world.read_storage::<Boss>())
+            .join()
+            .map(|(e, _)| e)
+            .next()
+            .unwrap();
+        set_bounding_box(&mut world, boss, Vector::new(60, 60));
+        world
+            .write_storage::<Boss>()
+            .get_mut(boss)
+            .unwrap()
+            .spawned_at = Duration::from_millis(spawned_at_ms);
+        world.write_resource::<GameMetrics>().elapsed = Duration::from_millis(killed_at_ms);
+
+        fire_hero_fireball(&mut world, hero, Vector::new(400, 400));
+        run(&mut world, CollisionSystem);
+        let score = world.read_storage::<Hero>().get(hero).unwrap().score;
+        score
+    }
+
+    #[test]
+    fn fast_boss_kill_earns_a_larger_time_bonus() {
+        let fast = get_boss_kill_score(0, 1000);
+        let slow = get_boss_kill_score(0, 5000);
+        assert_eq!(fast - slow, 400);
+        assert_eq!(get_boss_kill_score(2000, 1000) - fast, 100);
+    }
+
+    #[test]
+    fn shield_donor_death_shields_the_nearest_survivor_once() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let donor = create_test_enemy(&mut world, Vector::new(100, 300), 1);
+        world
+            .write_storage::<ShieldDonor>()
+            .insert(donor, ShieldDonor)
+            .unwrap();
+        let near = create_test_enemy(&mut world, Vector::new(200, 300), 2);
+        let far = create_test_enemy(&mut world, Vector::new(600, 300), 2);
+
+        fire_hero_fireball(&mut world, hero, Vector::new(100, 300));
+        run(&mut world, CollisionSystem);
+        run(&mut world, KillSystem);
+        assert!(!world.is_alive(donor));
+        assert!(world.read_storage::<OneHitShield>().get(near).is_some());
+        assert!(world.read_storage::<OneHitShield>().get(far).is_none());
+
+        let health = |world: &mut World| {
+            fire_hero_fireball(world, hero, Vector::new(200, 300));
+            run(world, CollisionSystem);
+            world.read_storage::<Health>().get(near).unwrap().current
+        };
+        assert_eq!(health(&mut world), 2);
+        assert_eq!(health(&mut world), 1);
+    }
+
+    #[test]
+    fn enemy_fireballs_slow_down_near_the_hero() {
+        let mut world = create_world(&SceneConfig {
+            bullet_assist: Some(0.5),
+            bullet_assist_radius: 150.0,
+            ..SceneConfig::default()
+        });
+        let config = HeroConfig {
+            position: Vector::new(400, 500),
+            ..HeroConfig::default()
+        };
+        crate::hero::create_hero(&mut world, config, PlayerId::One);
+        for &position in [Vector::new(400, 420), Vector::new(400, 100)].iter() {
+            crate::enemy::create_fireball(
+                world.create_entity(),
+                None,
+                Faction::Enemy,
+                FireballConfig {
+                    sprite: "tiro".to_string(),
+                    position,
+                    velocity: Vector::new(100, 0),
+                    damage_over_time: None,
+                    contact_damage: None,
+                },
+            );
+        }
+
+        run(&mut world, WalkSystem);
+
+        let pos = world.read_storage::<Position>();
+        let fireball = world.read_storage::<Fireball>();
+        let mut moved: Vec<(f32, f32)> = (&pos, &fireball)
+            .join()
+            .map(|(pos, _)| (pos.position.y, pos.position.x - 400.0))
+            .collect();
+        moved.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        let moved: Vec<f32> = moved.into_iter().map(|(_, dx)| dx).collect();
+        assert!(moved[1] > 0.0);
+        assert!((moved[0] - moved[1] * 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn beam_damages_each_overlapping_tick_but_never_the_boss() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let enemy = create_test_enemy(&mut world, Vector::new(300, 400), 10);
+        crate::enemy::create_boss(
+            &mut world,
+            BossConfig {
+                position: Vector::new(600, 400),
+                normal_lives: 0,
+                ..BossConfig::default()
+            },
+        );
+        let boss = (&world.entities(), &world.read_storage::<Boss>())
+            .join()
+            .map(|(e, _)| e)
+            .next()
+            .unwrap();
+        set_bounding_box(&mut world, boss, Vector::new(60, 60));
+        let lives = world.read_storage::<Boss>().get(boss).unwrap().lives;
+        for position in [Vector::new(300, 400), Vector::new(600, 400)].iter() {
+            let beam = world.create_entity();
+            crate::enemy::create_fireball(
+                beam,
+                Some(hero.id()),
+                Faction::Hero,
+                FireballConfig {
+                    sprite: "tiro".to_string(),
+                    position: *position,
+                    velocity: Vector::ZERO,
+                    damage_over_time: None,
+                    contact_damage: Some(ContactDamage {
+                        per_tick: 2,
+                        ..ContactDamage::default()
+                    }),
+                },
+            );
+        }
+        {
+            let fireball = world.read_storage::<Fireball>();
+            let mut render = world.write_storage::<Render>();
+            for (_, render) in (&fireball, &mut render).join() {
+                render.bounding_box = Some(Rectangle::new(Vector::ZERO, Vector::new(10, 10)));
+            }
+        }
+
+        for _ in 0..3 {
+            run(&mut world, CollisionSystem);
+        }
+
+        let health = world.read_storage::<Health>().get(enemy).unwrap().current;
+        assert_eq!(health, 4);
+        assert_eq!(world.read_storage::<Boss>().get(boss).unwrap().lives, lives);
+        let beams = (
+            &world.read_storage::<Fireball>(),
+            &world.read_storage::<Lifetime>(),
+        )
+            .join()
+            .map(|(_, lifetime)| lifetime.remaining)
+            .collect::<Vec<_>>();
+        assert_eq!(beams, vec![Duration::from_millis(300); 2]);
+    }
+
+    #[test]
+    fn rubber_band_scales_shooter_cooldowns_with_configured_expectations() {
+        let config = SceneConfig {
+            rubber_band: Some(RubberBandConfig {
+                expected_lives: 5,
+                expected_score_rate: 10.0,
+                ..RubberBandConfig::default()
+            }),
+            ..SceneConfig::default()
+        };
+        let mut world = create_world(&config);
+        let rubber_band = world.read_resource::<RubberBand>().config.unwrap();
+        assert_eq!(rubber_band.expected_lives, 5);
+        assert_eq!(rubber_band.expected_score_rate, 10.0);
+        world.write_resource::<GameMetrics>().elapsed = Duration::from_secs(60);
+        let enemy = create_test_enemy(&mut world, Vector::new(600, 400), 1);
+        let cooldown = Duration::from_millis(500);
+        world
+            .write_storage::<Shooter>()
+            .insert(
+                enemy,
+                Shooter {
+                    projectile_sprite: "fogo".to_string(),
+                    maximum_fireballs: 0,
+                    fireball_amount: 0,
+                    coefficient: (0.0, 0.0),
+                    cooldown,
+                    timer: cooldown,
+                },
+            )
+            .unwrap();
+        let timer = |world: &World| world.read_storage::<Shooter>().get(enemy).unwrap().timer;
+
+        world.write_resource::<RubberBand>().factor = 1.2;
+        run(&mut world, FireballSystem);
+        assert_eq!(timer(&world), cooldown - FRAME_DURATION.mul_f32(1.2));
+
+        world.write_resource::<RubberBand>().factor = 0.8;
+        let before = timer(&world);
+        run(&mut world, FireballSystem);
+        assert_eq!(timer(&world), before - FRAME_DURATION.mul_f32(0.8));
+    }
+
+    #[test]
+    fn freezing_an_enemy_that_died_before_the_lazy_update_is_ignored() {
+        let mut world = create_world(&SceneConfig {
+            hit_freeze_ms: Some(100),
+            ..SceneConfig::default()
+        });
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let enemy = create_test_enemy(&mut world, Vector::new(400, 300), 2);
+
+        fire_hero_fireball(&mut world, hero, Vector::new(400, 300));
+        CollisionSystem.run_now(&world.res);
+        world.entities().delete(enemy).unwrap();
+        world.maintain();
+
+        assert!(!world.is_alive(enemy));
+        assert!(world.read_storage::<FreezeFor>().get(enemy).is_none());
+    }
+
+    #[test]
+    fn boss_parts_follow_the_boss_past_the_edge_without_escaping() {
+        let mut world = create_world(&SceneConfig::default());
+        crate::enemy::create_boss(
+            &mut world,
+            BossConfig {
+                position: Vector::new(850, 300),
+                parts: vec![BossPartConfig {
+                    sprite: "fogo".to_string(),
+                    offset: Vector::new(80, 0),
+                    health: 2,
+                    score: 50,
+                }],
+                ..BossConfig::default()
+            },
+        );
+        let (boss, part) = {
+            let entities = world.entities();
+            let boss = (&entities, &world.read_storage::<Boss>())
+                .join()
+                .next()
+                .unwrap()
+                .0;
+            let part = (&entities, &world.read_storage::<FollowParent>())
+                .join()
+                .next()
+                .unwrap()
+                .0;
+            (boss, part)
+        };
+        let position = |world: &World| world.read_storage::<Position>().get(part).unwrap().position;
+
+        run(&mut world, FollowParentSystem);
+        run(&mut world, OutOfBoundsSystem);
+        assert!(world.is_alive(part));
+        assert!(world.read_storage::<DespawnAnim>().get(part).is_none());
+        assert!(!world.read_resource::<WaveClear>().escaped);
+        assert_eq!(
+            world.read_storage::<Boss>().get(boss).unwrap().parts_alive,
+            1
+        );
+        assert_eq!(position(&world), Vector::new(930, 300));
+
+        world
+            .write_storage::<Position>()
+            .get_mut(boss)
+            .unwrap()
+            .position = Vector::new(700, 350);
+        run(&mut world, FollowParentSystem);
+        assert_eq!(position(&world), Vector::new(780, 350));
+    }
+
+    fn touch_drainer(weapon_levels: bool) -> (i32, Option<usize>) {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            position: Vector::new(400, 300),
+            weapon_modes: vec![weapon_mode(EmitterPattern::Aimed); 3],
+            weapon_levels,
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        set_bounding_box(&mut world, hero, Vector::new(40, 40));
+        let drainer = crate::enemy::create_drainer(
+            &mut world,
+            crate::enemy::DrainerConfig {
+                position: Vector::new(400, 300),
+                velocity: Vector::ZERO,
+                ..crate::enemy::DrainerConfig::default()
+            },
+        );
+        set_bounding_box(&mut world, drainer, Vector::new(40, 40));
+
+        for _ in 0..3 {
+            run(&mut world, CollisionSystem);
+        }
+        let lives = world.read_storage::<Hero>().get(hero).unwrap().lives;
+        let level = world.read_storage::<HeroWeapon>().get(hero).unwrap().level;
+        (lives, level)
+    }
+
+    #[test]
+    fn drainer_lowers_weapon_level_only_for_heroes_that_opt_in() {
+        assert_eq!(touch_drainer(true), (5, Some(2)));
+        assert_eq!(touch_drainer(false), (4, None));
+    }
+
+    #[test]
+    fn reflector_returns_frontal_hits_and_takes_rear_hits() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let reflector = create_test_enemy(&mut world, Vector::new(400, 300), 3);
+        {
+            let mut enemy = world.write_storage::<Enemy>();
+            let enemy = enemy.get_mut(reflector).unwrap();
+            enemy.armor = Some(Vector::new(-1, 0));
+            enemy.reflects = true;
+        }
+        let hit = |world: &mut World, velocity: Vector| {
+            fire_hero_fireball(world, hero, Vector::new(400, 300));
+            for (_, vel) in (
+                &world.read_storage::<Fireball>(),
+                &mut world.write_storage::<Velocity>(),
+            )
+                .join()
+            {
+                vel.velocity = velocity;
+            }
+            run(world, CollisionSystem);
+            world
+                .read_storage::<Health>()
+                .get(reflector)
+                .unwrap()
+                .current
+        };
+
+        assert_eq!(hit(&mut world, Vector::new(300, 0)), 3);
+        let reflected: Vec<(Faction, Vector)> = (
+            &world.read_storage::<Fireball>(),
+            &world.read_storage::<Velocity>(),
+        )
+            .join()
+            .map(|(fireball, vel)| (fireball.faction, vel.velocity))
+            .collect();
+        assert_eq!(reflected, vec![(Faction::Enemy, Vector::new(-300, 0))]);
+
+        assert_eq!(hit(&mut world, Vector::new(-300, 0)), 2);
+    }
+
+    #[test]
+    fn killing_an_enemy_enqueues_only_its_own_death_sound() {
+        let mut world = create_world(&SceneConfig::default());
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let kill = |world: &mut World, sounds: Option<EntitySounds>| {
+            let enemy = create_test_enemy(world, Vector::new(400, 300), 1);
+            if let Some(sounds) = sounds {
+                world.write_storage().insert(enemy, sounds).unwrap();
+            }
+            fire_hero_fireball(world, hero, Vector::new(400, 300));
+            run(world, CollisionSystem);
+            run(world, KillSystem);
+            assert!(!world.is_alive(enemy));
+            std::mem::take(&mut world.write_resource::<SoundQueue>().sounds)
+        };
+
+        let sounds = EntitySounds {
+            death: Some("explosion.ogg".to_string()),
+            ..EntitySounds::default()
+        };
+        let queued = kill(&mut world, Some(sounds));
+        assert_eq!(
+            queued,
+            vec![crate::resources::SoundRequest {
+                path: "explosion.ogg".to_string(),
+                priority: SoundPriority::High,
+            }]
+        );
+        assert!(kill(&mut world, None).is_empty());
+    }
+
+    #[test]
+    fn hero_holds_fire_inside_a_no_fire_zone() {
+        let config = SceneConfig {
+            no_fire_zones: vec![NoFireZoneConfig {
+                position: Vector::new(0, 0),
+                size: Vector::new(200, 600),
+            }],
+            ..SceneConfig::default()
+        };
+        let mut world = create_world(&config);
+        let hero_config = HeroConfig {
+            position: Vector::new(100, 300),
+            weapon_modes: vec![weapon_mode(EmitterPattern::Aimed)],
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, hero_config, PlayerId::One);
+        set_key(&mut world, KeyboardKeys::KeyFire, true);
+
+        run(&mut world, FireballSystem);
+        assert_eq!(count_fireballs(&world, Faction::Hero), 0);
+
+        world
+            .write_storage::<Position>()
+            .get_mut(hero)
+            .unwrap()
+            .position = Vector::new(400, 300);
+        run(&mut world, FireballSystem);
+        assert_eq!(count_fireballs(&world, Faction::Hero), 1);
+    }
+
+    #[test]
+    fn accelerating_enemy_speeds_up_until_its_max_speed() {
+        let mut world = create_world(&SceneConfig::default());
+        let enemy = create_test_enemy(&mut world, Vector::new(600, 300), 1);
+        world
+            .write_storage::<Velocity>()
+            .get_mut(enemy)
+            .unwrap()
+            .velocity = Vector::new(-100, 0);
+        world
+            .write_storage::<Acceleration>()
+            .insert(
+                enemy,
+                Acceleration {
+                    acceleration: 60.0,
+                    max_speed: Some(150.0),
+                },
+            )
+            .unwrap();
+        let get_velocity = |world: &World| {
+            world
+                .read_storage::<Velocity>()
+                .get(enemy)
+                .unwrap()
+                .velocity
+        };
+
+        for _ in 0..10 {
+            run(&mut world, WalkSystem);
+        }
+        let velocity = get_velocity(&world);
+        assert!((velocity.len() - (100.0 + 60.0 * 0.0167 * 10.0)).abs() < 0.01);
+        assert_eq!(velocity.y, 0.0);
+
+        for _ in 0..100 {
+            run(&mut world, WalkSystem);
+        }
+        assert_eq!(get_velocity(&world), Vector::new(-150, 0));
+    }
+
+    #[test]
+    fn max_combo_overdrive_shortens_fire_cooldown_until_it_expires() {
+        let mut world = create_world(&SceneConfig::default());
+        let config = HeroConfig {
+            weapon_modes: vec![weapon_mode(EmitterPattern::Aimed)],
+            combo: Some(ComboConfig {
+                window_ms: 10_000,
+                max_multiplier: 2,
+                break_penalty: 0,
+                break_flash_ms: 0,
+            }),
+            overdrive: Some(OverdriveConfig {
+                duration_ms: 50,
+                cooldown_factor: 0.5,
+                sound: None,
+            }),
+            ..HeroConfig::default()
+        };
+        let hero = crate::hero::create_hero(&mut world, config, PlayerId::One);
+        set_key(&mut world, KeyboardKeys::KeyFire, true);
+        let fire = |world: &mut World| {
+            world
+                .write_storage::<HeroWeapon>()
+                .get_mut(hero)
+                .unwrap()
+                .cooldown = Duration::from_millis(0);
+            run(world, FireballSystem);
+            world
+                .read_storage::<HeroWeapon>()
+                .get(hero)
+                .unwrap()
+                .cooldown
+        };
+        let active = |world: &World| world.read_storage::<Overdrive>().get(hero).unwrap().active;
+        let base_cooldown = fire(&mut world);
+
+        crate::hero::register_combo_kill(world.write_storage::<Combo>().get_mut(hero).unwrap());
+        run(&mut world, OverdriveSystem);
+        assert!(active(&world));
+        assert_eq!(fire(&mut world), base_cooldown.mul_f32(0.5));
+
+        for _ in 0..3 {
+            run(&mut world, OverdriveSystem);
         }
+        assert!(!active(&world));
+        assert_eq!(fire(&mut world), base_cooldown);
     }
 }
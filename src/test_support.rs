@@ -0,0 +1,96 @@
+use crate::{
+    component::{Faction, Fireball, Render},
+    enemy::{DamageOverTimeConfig, EmitterPattern, EnemyConfig, FireballConfig},
+    hero::WeaponModeConfig,
+    resources::{KeyboardKeys, PressedKeys},
+};
+use quicksilver::geom::{Rectangle, Vector};
+use specs::{Entity, Join, RunNow, System, World};
+
+pub fn run<S: for<'a> System<'a>>(world: &mut World, mut system: S) {
+    system.run_now(&world.res);
+    world.maintain();
+}
+
+pub fn set_bounding_box(world: &mut World, e: Entity, size: Vector) {
+    world
+        .write_storage::<Render>()
+        .get_mut(e)
+        .unwrap()
+        .bounding_box = Some(Rectangle::new(Vector::ZERO, size));
+}
+
+pub fn create_test_enemy(world: &mut World, position: Vector, health: i32) -> Entity {
+    let enemy = crate::enemy::create_enemy(
+        world,
+        EnemyConfig {
+            sprite: "andador".to_string(),
+            position,
+            velocity: Vector::ZERO,
+            score: 100,
+            shooter_config: None,
+            emitter_config: None,
+            despawn_ms: 0,
+            health,
+            armor: None,
+        },
+    );
+    set_bounding_box(world, enemy, Vector::new(40, 40));
+    enemy
+}
+
+pub fn fire_hero_fireball(world: &mut World, hero: Entity, position: Vector) {
+    fire_hero_dot_fireball(world, hero, position, None);
+}
+
+pub fn fire_hero_dot_fireball(
+    world: &mut World,
+    hero: Entity,
+    position: Vector,
+    damage_over_time: Option<DamageOverTimeConfig>,
+) {
+    crate::enemy::create_fireball(
+        world.create_entity(),
+        Some(hero.id()),
+        Faction::Hero,
+        FireballConfig {
+            sprite: "tiro".to_string(),
+            position,
+            velocity: Vector::ZERO,
+            damage_over_time,
+            contact_damage: None,
+        },
+    );
+    let fireball = world.read_storage::<Fireball>();
+    let mut render = world.write_storage::<Render>();
+    for (_, render) in (&fireball, &mut render).join() {
+        render.bounding_box = Some(Rectangle::new(Vector::ZERO, Vector::new(10, 10)));
+    }
+}
+
+pub fn weapon_mode(pattern: EmitterPattern) -> WeaponModeConfig {
+    WeaponModeConfig {
+        projectile_sprite: "tiro".to_string(),
+        pattern,
+        projectile_speed: 600.0,
+        cooldown_ms: 300,
+        damage_over_time: None,
+        contact_damage: None,
+    }
+}
+
+pub fn set_key(world: &mut World, key: KeyboardKeys, pressed: bool) {
+    let mut pressed_keys = world.write_resource::<PressedKeys>();
+    if pressed {
+        pressed_keys.pressed_keys[0].add(key as u32);
+    } else {
+        pressed_keys.pressed_keys[0].remove(key as u32);
+    }
+}
+
+pub fn count_fireballs(world: &World, faction: Faction) -> usize {
+    (&world.read_storage::<Fireball>())
+        .join()
+        .filter(|fireball| fireball.faction == faction)
+        .count()
+}
@@ -0,0 +1,74 @@
+use crate::instant::Instant;
+use std::time::Duration;
+
+pub struct FrameAccumulator {
+    last: Option<Instant>,
+    accumulated: Duration,
+    step: Duration,
+    max_delta: Duration,
+}
+
+impl FrameAccumulator {
+    pub fn new(step: Duration, max_delta: Duration) -> FrameAccumulator {
+        FrameAccumulator {
+            last: None,
+            accumulated: Duration::from_millis(0),
+            step,
+            max_delta,
+        }
+    }
+
+    pub fn tick(&mut self, now: Instant) {
+        if let Some(last) = self.last.take() {
+            self.feed(now.duration_since(last));
+        }
+        self.last = Some(now);
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    pub fn feed(&mut self, delta: Duration) {
+        self.accumulated += delta.min(self.max_delta);
+    }
+
+    pub fn accumulated(&self) -> Duration {
+        self.accumulated
+    }
+
+    pub fn take_steps(&mut self) -> u32 {
+        let mut steps = 0;
+        while self.accumulated >= self.step {
+            self.accumulated -= self.step;
+            steps += 1;
+        }
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accumulator() -> FrameAccumulator {
+        FrameAccumulator::new(Duration::from_millis(16), Duration::from_millis(250))
+    }
+
+    #[test]
+    fn long_frame_is_clamped_to_max_delta() {
+        let mut accumulator = accumulator();
+        accumulator.feed(Duration::from_millis(2000));
+        assert_eq!(accumulator.accumulated(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn reset_drops_the_time_spent_paused() {
+        let mut accumulator = accumulator();
+        accumulator.tick(Instant::now());
+        std::thread::sleep(Duration::from_millis(20));
+        accumulator.reset();
+        accumulator.tick(Instant::now());
+        assert_eq!(accumulator.accumulated(), Duration::from_millis(0));
+    }
+}
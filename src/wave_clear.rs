@@ -0,0 +1,122 @@
+use crate::{
+    component::{Boss, DespawnAnim, Enemy, Hero},
+    resources::{AnnouncementFilter, VariableDictionary},
+};
+use specs::{Entities, Join, LazyUpdate, Read, ReadStorage, System, Write, WriteStorage};
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct WaveClear {
+    pub bonus: i32,
+    pub active: bool,
+    pub spawning: bool,
+    pub seen_enemies: bool,
+    pub escaped: bool,
+}
+
+impl WaveClear {
+    pub fn start(&mut self) {
+        self.active = true;
+        self.spawning = true;
+        self.seen_enemies = false;
+        self.escaped = false;
+    }
+}
+
+pub struct WaveClearSystem;
+
+impl<'a> System<'a> for WaveClearSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, Boss>,
+        ReadStorage<'a, DespawnAnim>,
+        WriteStorage<'a, Hero>,
+        Write<'a, WaveClear>,
+        Write<'a, VariableDictionary>,
+        Read<'a, AnnouncementFilter>,
+        Read<'a, LazyUpdate>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            enemy,
+            boss,
+            despawn_anim,
+            mut hero,
+            mut wave_clear,
+            mut dictionary,
+            announcement_filter,
+            lazy,
+        ): Self::SystemData,
+    ) {
+        if !wave_clear.active {
+            return;
+        }
+        if (&enemy, !&despawn_anim).join().next().is_some() {
+            wave_clear.seen_enemies = true;
+            return;
+        }
+        if wave_clear.spawning || !wave_clear.seen_enemies {
+            return;
+        }
+
+        wave_clear.active = false;
+        if wave_clear.escaped || wave_clear.bonus <= 0 {
+            return;
+        }
+        for hero in (&mut hero).join() {
+            crate::hero::add_score(hero, wave_clear.bonus);
+        }
+        let boss_alive = (&boss, !&despawn_anim).join().next().is_some();
+        crate::system::create_announcement(
+            &lazy,
+            &entities,
+            &mut dictionary,
+            &announcement_filter,
+            boss_alive,
+            format!("Wave clear! +{}", wave_clear.bonus),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::PlayerId,
+        hero::HeroConfig,
+        scene::{create_world, SceneConfig},
+        test_support::{create_test_enemy, run},
+    };
+    use quicksilver::geom::Vector;
+    use specs::World;
+
+    #[test]
+    fn clearing_a_wave_awards_the_bonus_once() {
+        let config = SceneConfig {
+            wave_clear_bonus: 500,
+            ..SceneConfig::default()
+        };
+        let mut world = create_world(&config);
+        let hero = crate::hero::create_hero(&mut world, HeroConfig::default(), PlayerId::One);
+        let score = |world: &World| world.read_storage::<Hero>().get(hero).unwrap().score;
+        world.write_resource::<WaveClear>().start();
+        let first = create_test_enemy(&mut world, Vector::new(400, 300), 1);
+
+        run(&mut world, WaveClearSystem);
+        world.delete_entity(first).unwrap();
+        run(&mut world, WaveClearSystem);
+        assert_eq!(score(&world), 0);
+
+        let last = create_test_enemy(&mut world, Vector::new(400, 300), 1);
+        world.write_resource::<WaveClear>().spawning = false;
+        run(&mut world, WaveClearSystem);
+        world.delete_entity(last).unwrap();
+        for _ in 0..3 {
+            run(&mut world, WaveClearSystem);
+        }
+        assert_eq!(score(&world), 500);
+    }
+}